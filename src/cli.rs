@@ -8,6 +8,9 @@ use crate::{config, tui};
 pub struct GitMe {
     #[command(subcommand)]
     command: Option<Command>,
+    /// Only show the data cached by the previous run, without calling github
+    #[arg(long)]
+    offline: bool,
 }
 
 #[derive(Subcommand)]
@@ -36,7 +39,7 @@ impl GitMe {
                 Command::AddRepo => gitme_config.add_repository()?,
                 Command::DeleteRepo => gitme_config.remove_repository()?,
             },
-            None => tui::run(gitme_config).await?,
+            None => tui::run(gitme_config, cli.offline).await?,
         };
 
         Ok(())
@@ -2,7 +2,9 @@ use color_eyre::{
     Result,
     eyre::{Context, ContextCompat, bail},
 };
-use inquire::{Confirm, Text, required};
+use std::fmt;
+
+use inquire::{Confirm, Select, Text, required};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -12,6 +14,15 @@ pub struct Config {
     pub command: Option<String>,
     #[serde(default)]
     pub command_args: Vec<String>,
+    /// Maximum number of open pull requests fetched per repository
+    pub max_pull_requests: Option<usize>,
+    /// How many days the merged and closed pull requests stay listed
+    pub recently_closed_days: Option<u32>,
+    /// Maximum number of requests sent to github at the same time
+    pub max_concurrent_requests: Option<usize>,
+    /// Teams (as `org/team`) whose review requests are not shown
+    #[serde(default)]
+    pub ignored_teams: Vec<String>,
     #[serde(default)]
     pub repositories: Vec<Repository>,
 }
@@ -21,6 +32,36 @@ pub struct Repository {
     pub owner: String,
     pub name: String,
     pub system_path: Option<String>,
+    /// Where the repository is hosted
+    #[serde(default)]
+    pub forge: ForgeKind,
+    /// Host of the forge, e.g. `gitlab.example.com`, defaults to the public instance
+    pub host: Option<String>,
+    /// Token used for this repository instead of the `api_key`
+    pub token: Option<String>,
+    /// Username on the forge when it's not the same as the global one
+    pub username: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ForgeKind {
+    #[default]
+    Github,
+    Gitlab,
+    // Any Gitea compatible forge, e.g. Forgejo or Codeberg
+    #[serde(alias = "forgejo")]
+    Gitea,
+}
+
+impl fmt::Display for ForgeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ForgeKind::Github => write!(f, "GitHub"),
+            ForgeKind::Gitlab => write!(f, "GitLab"),
+            ForgeKind::Gitea => write!(f, "Gitea / Forgejo"),
+        }
+    }
 }
 
 impl Config {
@@ -147,11 +188,36 @@ impl Config {
             username: Some(username),
             command: Some(command),
             command_args,
+            max_pull_requests: None,
+            recently_closed_days: None,
+            max_concurrent_requests: None,
+            ignored_teams: vec![],
             repositories,
         })
     }
 
     fn prompt_repository_input() -> Result<Repository> {
+        let forge = Select::new(
+            "Where is the repository hosted?",
+            vec![ForgeKind::Github, ForgeKind::Gitlab, ForgeKind::Gitea],
+        )
+        .prompt()?;
+
+        let (host, token) = if forge == ForgeKind::Github {
+            (None, None)
+        } else {
+            let host = Text::new("Host of the forge (leave empty for the public instance):")
+                .prompt()?
+                .trim()
+                .to_string();
+            let token = Text::new("Access token for this repository:")
+                .with_validator(required!())
+                .prompt()?
+                .trim()
+                .to_string();
+            ((!host.is_empty()).then_some(host), Some(token))
+        };
+
         let owner = Text::new("Repository owner:")
             .with_validator(required!())
             .prompt()?
@@ -184,6 +250,10 @@ impl Config {
             owner,
             name,
             system_path,
+            forge,
+            host,
+            token,
+            username: None,
         })
     }
 
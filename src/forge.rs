@@ -0,0 +1,477 @@
+//! Forge agnostic access to pull requests, the tui only talks to a [`Forge`] so it doesn't care
+//! about where the prs are hosted.
+mod gitea;
+mod github;
+mod gitlab;
+#[cfg(test)]
+mod memory;
+mod rest;
+#[cfg(test)]
+pub(crate) mod stub;
+
+use std::{collections::HashSet, fmt};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+pub use crate::github::RateLimit;
+pub use gitea::Gitea;
+pub use gitlab::GitLab;
+#[cfg(test)]
+pub use memory::MemoryForge;
+
+#[async_trait]
+pub trait Forge: fmt::Debug + Send + Sync {
+    /// Fetches a page of open pull requests of the repository, starting after the given cursor
+    async fn pull_requests(
+        &self,
+        owner: &str,
+        name: &str,
+        first: usize,
+        after: Option<String>,
+    ) -> Result<Page<PullRequest>, Error>;
+
+    /// Fetches at most `max` pull requests of the repository that were merged or closed since the
+    /// given time, the latest first
+    async fn closed_pull_requests(
+        &self,
+        owner: &str,
+        name: &str,
+        since: DateTime<Utc>,
+        max: usize,
+    ) -> Result<Vec<PullRequest>, Error>;
+
+    /// Fetches the open issues of the repository assigned to the user
+    async fn assigned_issues(
+        &self,
+        owner: &str,
+        name: &str,
+        login: &str,
+    ) -> Result<Vec<Issue>, Error>;
+
+    /// Fetches every review submitted on the pull request
+    async fn reviews(&self, owner: &str, name: &str, number: u64) -> Result<Vec<Review>, Error>;
+
+    /// Fetches the conversation of the pull request, oldest first. Long conversations are cut to
+    /// their latest entries
+    async fn timeline(
+        &self,
+        owner: &str,
+        name: &str,
+        number: u64,
+    ) -> Result<Vec<TimelineItem>, Error>;
+
+    /// Fetches the summary of the repository
+    async fn overview(&self, owner: &str, name: &str) -> Result<RepositoryOverview, Error>;
+
+    /// Fetches the merge information of the pull request
+    async fn details(&self, owner: &str, name: &str, number: u64) -> Result<MergeInfo, Error>;
+
+    /// Fetches the checks of the given commit
+    async fn checks(&self, owner: &str, name: &str, sha: &str) -> Result<Vec<Check>, Error>;
+
+    /// Fetches the teams the user belongs to, as `org/team`
+    async fn teams(&self) -> Result<Vec<String>, Error>;
+
+    /// Searches the open pull requests of the repositories in which the user was mentioned or
+    /// commented, leaving out the ones the user authored. Forges without such a search find none
+    async fn involved(
+        &self,
+        _repositories: &[(String, String)],
+        _login: &str,
+    ) -> Result<Vec<Involved>, Error> {
+        Ok(vec![])
+    }
+
+    /// Fetches the latest notifications of the user in the repositories, read or not. Forges
+    /// without notifications have none
+    async fn notifications(
+        &self,
+        _repositories: &[(String, String)],
+    ) -> Result<Vec<Notification>, Error> {
+        Ok(vec![])
+    }
+
+    /// Acts on the notification thread
+    async fn update_notification(
+        &self,
+        _thread_id: &str,
+        _action: NotificationAction,
+    ) -> Result<(), Error> {
+        Err(unsupported("Notifications"))
+    }
+
+    /// Fetches the latest workflow runs of the repository, either of its default branch or of a
+    /// commit
+    async fn workflow_runs(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _filter: &RunFilter,
+    ) -> Result<Vec<WorkflowRun>, Error> {
+        Err(unsupported("Workflow runs"))
+    }
+
+    /// Fetches the jobs of the workflow run
+    async fn workflow_jobs(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _run_id: u64,
+    ) -> Result<Vec<WorkflowJob>, Error> {
+        Err(unsupported("Workflow runs"))
+    }
+
+    /// Fetches the log of the job, as plain text
+    async fn job_log(&self, _owner: &str, _name: &str, _job_id: u64) -> Result<String, Error> {
+        Err(unsupported("Workflow runs"))
+    }
+
+    /// Acts on the workflow run
+    async fn update_workflow_run(
+        &self,
+        _owner: &str,
+        _name: &str,
+        _run_id: u64,
+        _action: RunAction,
+    ) -> Result<(), Error> {
+        Err(unsupported("Workflow runs"))
+    }
+
+    /// Current rate limit of the forge, if it has any
+    fn rate_limit(&self) -> Option<RateLimit> {
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The forge answered with an error status
+    Status { status: u16, message: String },
+    /// Anything else, e.g. network errors or unexpected responses
+    Other(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Status { status, message } => write!(f, "{} ({})", message, status),
+            Error::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+// Error of the features some forges don't have
+fn unsupported(feature: &str) -> Error {
+    Error::Other(format!("{} are not supported by the forge", feature))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    // Cursor of the next page, `None` on the last one
+    pub next: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PullRequest {
+    pub number: u64,
+    pub title: String,
+    pub url: String,
+    pub owner: String,
+    pub repo: String,
+    pub body: String,
+    pub is_draft: bool,
+    pub state: PullRequestState,
+    // When the pr was merged or closed
+    pub closed_at: Option<DateTime<Utc>>,
+    // Login of the user who merged the pr
+    pub merged_by: Option<String>,
+    pub head_sha: String,
+    // Branch the changes come from, and the one they are merged into
+    pub head_branch: String,
+    pub base_branch: String,
+    // Whether the head branch belongs to a fork of the repository
+    pub is_fork: bool,
+    pub labels: Vec<Label>,
+    pub milestone: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    // Size of the changes, not every forge lists it along with the prs
+    pub additions: Option<u64>,
+    pub deletions: Option<u64>,
+    pub changed_files: Option<u64>,
+    pub author: Option<User>,
+    // Combined state of the checks of the head commit, `None` when there are no checks
+    pub checks: Option<CheckState>,
+    pub assignees: Vec<String>,
+    // Logins of the users whose review is pending
+    pub requested_reviewers: Vec<String>,
+    // Teams, as `org/team`, whose review is pending
+    pub requested_teams: Vec<String>,
+    // Latest review of each reviewer
+    pub latest_reviews: Vec<Review>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Issue {
+    pub number: u64,
+    pub title: String,
+    pub url: String,
+    pub owner: String,
+    pub repo: String,
+    pub body: String,
+    pub author: Option<User>,
+    pub labels: Vec<Label>,
+    pub assignees: Vec<String>,
+    pub milestone: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Label {
+    pub name: String,
+    // Hex color without the leading `#`, e.g. `d73a4a`
+    pub color: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PullRequestState {
+    #[default]
+    Open,
+    Closed,
+    Merged,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct User {
+    pub login: String,
+    // Only set for real users, not bots nor organizations
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Review {
+    pub author: String,
+    pub state: ReviewState,
+    // The commit the review was made on
+    pub commit: Option<String>,
+    pub body: String,
+    pub submitted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReviewState {
+    Pending,
+    Commented,
+    Approved,
+    ChangesRequested,
+    Dismissed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckState {
+    Success,
+    Failure,
+    Pending,
+}
+
+/// A pull request found by [`Forge::involved`], along with why it was found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Involved {
+    pub pull_request: PullRequest,
+    pub involvements: Vec<Involvement>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Involvement {
+    Mentioned,
+    Commented,
+}
+
+/// A notification of the inbox of the user, about a pr, an issue or anything else happening in
+/// a repository
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+    pub thread_id: String,
+    pub owner: String,
+    pub repo: String,
+    pub title: String,
+    // e.g. `PullRequest`, `Issue`, `CheckSuite`
+    pub kind: String,
+    // Number of the pr or issue, if the notification is about one
+    pub number: Option<u64>,
+    // Page of the subject, or of the repository when it has none
+    pub url: String,
+    // e.g. `review_requested`, `mention`, `ci_activity`
+    pub reason: String,
+    pub unread: bool,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationAction {
+    MarkRead,
+    /// Removes the notification from the inbox
+    MarkDone,
+    /// Stops the notifications of the thread and removes it from the inbox
+    Unsubscribe,
+}
+
+/// Summary of a repository, shown when no pr is selected
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepositoryOverview {
+    pub description: Option<String>,
+    pub url: String,
+    pub default_branch: Option<String>,
+    // Not every forge counts them
+    pub open_pull_requests: Option<u64>,
+    pub open_issues: Option<u64>,
+    pub latest_release: Option<Release>,
+    // Combined checks of the head of the default branch
+    pub default_branch_checks: Option<CheckState>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Release {
+    pub name: String,
+    pub tag: String,
+    pub published_at: Option<DateTime<Utc>>,
+}
+
+/// Which workflow runs of a repository to list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RunFilter {
+    DefaultBranch,
+    /// The runs triggered by the commit, e.g. the head of a pr
+    Commit(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunAction {
+    RerunFailedJobs,
+    Cancel,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkflowRun {
+    pub id: u64,
+    // Name of the workflow
+    pub name: String,
+    pub number: u64,
+    // What triggered the run, e.g. `push`, `pull_request`
+    pub event: String,
+    pub branch: Option<String>,
+    pub state: CheckState,
+    // Conclusion once completed, the status until then, e.g. `failure`, `in_progress`
+    pub conclusion: String,
+    pub url: String,
+    pub started_at: Option<DateTime<Utc>>,
+    // Last update of the run, its end once completed
+    pub updated_at: DateTime<Utc>,
+}
+
+/// An entry of the conversation of a pull request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimelineItem {
+    pub author: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub event: TimelineEvent,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimelineEvent {
+    Comment {
+        body: String,
+    },
+    Review {
+        state: ReviewState,
+        body: String,
+    },
+    Commit {
+        sha: String,
+        // First line of the message
+        message: String,
+    },
+    Labeled {
+        label: String,
+    },
+    Unlabeled {
+        label: String,
+    },
+    ForcePushed {
+        before: Option<String>,
+        after: Option<String>,
+    },
+    ReviewRequested {
+        reviewer: String,
+    },
+    ReadyForReview,
+    // Events the forge only describes as text, e.g. the GitLab system notes
+    Other(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkflowJob {
+    pub id: u64,
+    pub name: String,
+    pub state: CheckState,
+    pub conclusion: String,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub name: String,
+    pub state: CheckState,
+    // Conclusion as reported by the forge, e.g. `success`, `cancelled`, `in_progress`
+    pub conclusion: String,
+    pub url: Option<String>,
+}
+
+/// Mergeability of a pull request, `None` while the forge is still computing it
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MergeInfo {
+    pub mergeable: Option<bool>,
+    // Also `None` when the forge doesn't know, once the mergeability is computed
+    pub rebaseable: Option<bool>,
+    // e.g. `clean`, `blocked`, `behind`, `dirty`
+    pub mergeable_state: Option<String>,
+}
+
+impl PullRequest {
+    /// Whether the user is in the pending review requests of the pr
+    pub fn is_review_requested(&self, login: &str) -> bool {
+        self.requested_reviewers.iter().any(|r| r == login)
+    }
+
+    /// Returns the latest review submitted by the user on the pr
+    pub fn latest_review_by(&self, login: &str) -> Option<&Review> {
+        self.latest_reviews
+            .iter()
+            .find(|r| r.state != ReviewState::Pending && r.author == login)
+    }
+
+    /// Returns the first requested team (as `org/team`) that is part of the given teams
+    pub fn requested_team(&self, teams: &HashSet<String>) -> Option<String> {
+        self.requested_teams
+            .iter()
+            .find(|team| teams.contains(*team))
+            .cloned()
+    }
+
+    pub fn is_assigned_to(&self, login: &str) -> bool {
+        self.assignees.iter().any(|a| a == login)
+    }
+
+    pub fn is_authored_by(&self, login: &str) -> bool {
+        self.author.as_ref().is_some_and(|a| a.login == login)
+    }
+}
@@ -0,0 +1,772 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::{StreamExt, TryStreamExt, stream};
+use reqwest::header::{AUTHORIZATION, HeaderMap};
+use serde::Deserialize;
+
+use super::{
+    Check, CheckState, Error, Forge, Issue, Label, MergeInfo, Page, PullRequest, PullRequestState,
+    Release, RepositoryOverview, Review, ReviewState, TimelineEvent, TimelineItem, User,
+    rest::RestClient,
+};
+
+const DEFAULT_HOST: &str = "codeberg.org";
+// Default maximum page size of gitea instances
+const MAX_PAGE_SIZE: usize = 50;
+// Longer conversations are cut, the latest entries are kept
+const MAX_TIMELINE_ITEMS: usize = 500;
+// Reviews of a page of prs fetched at the same time, every pr needs its own request
+const MAX_CONCURRENT_REVIEWS: usize = 8;
+// Title prefixes marking a pr as work in progress on instances without drafts
+const WIP_PREFIXES: &[&str] = &["WIP:", "[WIP]"];
+
+/// Pull requests of a Gitea compatible instance, e.g. Forgejo or Codeberg
+#[derive(Debug)]
+pub struct Gitea {
+    client: RestClient,
+}
+
+#[derive(Debug, Deserialize)]
+struct GiteaPullRequest {
+    number: u64,
+    title: String,
+    html_url: String,
+    body: Option<String>,
+    #[serde(default)]
+    draft: bool,
+    // `open` or `closed`, merged prs being closed too
+    state: String,
+    #[serde(default)]
+    merged: bool,
+    merged_at: Option<DateTime<Utc>>,
+    closed_at: Option<DateTime<Utc>>,
+    merged_by: Option<GiteaUser>,
+    updated_at: DateTime<Utc>,
+    created_at: Option<DateTime<Utc>>,
+    head: Branch,
+    base: Branch,
+    #[serde(default)]
+    labels: Vec<GiteaLabel>,
+    milestone: Option<Milestone>,
+    // Only reported by recent instances
+    additions: Option<u64>,
+    deletions: Option<u64>,
+    changed_files: Option<u64>,
+    user: Option<GiteaUser>,
+    #[serde(default)]
+    assignees: Option<Vec<GiteaUser>>,
+    #[serde(default)]
+    requested_reviewers: Option<Vec<GiteaUser>>,
+    #[serde(default)]
+    requested_reviewers_teams: Option<Vec<Team>>,
+    mergeable: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Branch {
+    sha: String,
+    #[serde(rename = "ref")]
+    name: String,
+    repo_id: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GiteaIssue {
+    number: u64,
+    title: String,
+    html_url: String,
+    body: Option<String>,
+    user: Option<GiteaUser>,
+    #[serde(default)]
+    labels: Vec<GiteaLabel>,
+    #[serde(default)]
+    assignees: Option<Vec<GiteaUser>>,
+    milestone: Option<Milestone>,
+    created_at: Option<DateTime<Utc>>,
+    updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GiteaLabel {
+    name: String,
+    // Hex color, with or without the leading `#` depending on the version
+    color: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Milestone {
+    title: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GiteaUser {
+    id: u64,
+    login: String,
+    full_name: Option<String>,
+    email: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Team {
+    name: String,
+    organization: Option<Organization>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Organization {
+    // Older instances only expose the `username`
+    #[serde(alias = "username")]
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GiteaReview {
+    user: Option<GiteaUser>,
+    // `APPROVED`, `PENDING`, `COMMENT`, `REQUEST_CHANGES` or `REQUEST_REVIEW`
+    state: String,
+    commit_id: Option<String>,
+    body: Option<String>,
+    submitted_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    dismissed: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct GiteaTimelineItem {
+    // e.g. `comment`, `label`, `pull_push`, `review_request` or `close`
+    #[serde(rename = "type")]
+    kind: String,
+    user: Option<GiteaUser>,
+    body: Option<String>,
+    created_at: Option<DateTime<Utc>>,
+    label: Option<GiteaLabel>,
+    // The requested reviewer, either an user or a team
+    assignee: Option<GiteaUser>,
+    assignee_team: Option<Team>,
+}
+
+// Body of the `pull_push` timeline items
+#[derive(Debug, Deserialize)]
+struct PushedCommits {
+    #[serde(default)]
+    is_force_push: bool,
+    #[serde(default)]
+    commit_ids: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GiteaRepository {
+    description: String,
+    html_url: String,
+    default_branch: String,
+    open_issues_count: u64,
+    open_pr_counter: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct GiteaRelease {
+    name: String,
+    tag_name: String,
+    published_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CombinedStatus {
+    // Empty when the commit has no statuses
+    state: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CommitStatus {
+    context: String,
+    // `pending`, `success`, `error`, `failure` or `warning`
+    status: String,
+    target_url: Option<String>,
+}
+
+impl Gitea {
+    /// Client of the instance at the given host, defaults to codeberg.org
+    pub fn new(host: Option<&str>, token: Option<String>) -> Self {
+        let auth = token.map(|token| (AUTHORIZATION, format!("token {}", token)));
+
+        Self {
+            client: RestClient::new(host.unwrap_or(DEFAULT_HOST), "/api/v1", auth),
+        }
+    }
+
+    async fn gitea_reviews(
+        &self,
+        owner: &str,
+        name: &str,
+        number: u64,
+    ) -> Result<Vec<GiteaReview>, Error> {
+        let (reviews, _) = self
+            .client
+            .get(&format!(
+                "/repos/{}/{}/pulls/{}/reviews?limit={}",
+                owner, name, number, MAX_PAGE_SIZE
+            ))
+            .await?;
+
+        Ok(reviews)
+    }
+
+    // The timeline is listed oldest first, every page has to be gone through to get the latest
+    // entries
+    async fn gitea_timeline(
+        &self,
+        owner: &str,
+        name: &str,
+        number: u64,
+    ) -> Result<Vec<GiteaTimelineItem>, Error> {
+        let mut items = vec![];
+        let mut page = Some(1);
+        while let Some(current) = page {
+            let (page_items, headers): (Vec<GiteaTimelineItem>, _) = self
+                .client
+                .get(&format!(
+                    "/repos/{}/{}/issues/{}/timeline?limit={}&page={}",
+                    owner, name, number, MAX_PAGE_SIZE, current
+                ))
+                .await?;
+
+            items.extend(page_items);
+            page = next_page(&headers, current, MAX_PAGE_SIZE).and_then(|next| next.parse().ok());
+        }
+
+        Ok(items)
+    }
+
+    // Reviews are not part of the list, they're needed to know our review status
+    async fn with_reviews(
+        &self,
+        owner: &str,
+        name: &str,
+        pulls: Vec<GiteaPullRequest>,
+    ) -> Result<Vec<PullRequest>, Error> {
+        let numbers: Vec<u64> = pulls.iter().map(|pr| pr.number).collect();
+        let reviews: Vec<_> = stream::iter(numbers)
+            .map(|number| self.gitea_reviews(owner, name, number))
+            .buffered(MAX_CONCURRENT_REVIEWS)
+            .try_collect()
+            .await?;
+
+        Ok(pulls
+            .into_iter()
+            .zip(reviews)
+            .map(|(pr, reviews)| {
+                // Keep the latest review of each reviewer, they come oldest first
+                let mut latest_reviews: HashMap<String, Review> = HashMap::new();
+                for review in reviews.into_iter().filter_map(|r| r.into_review()) {
+                    if review.state != ReviewState::Pending {
+                        latest_reviews.insert(review.author.clone(), review);
+                    }
+                }
+                pr.into_pull_request(owner, name, latest_reviews.into_values().collect())
+            })
+            .collect())
+    }
+}
+
+// Gitea returns the total of items instead of a cursor, the next page is the cursor
+fn next_page(headers: &HeaderMap, page: usize, per_page: usize) -> Option<String> {
+    let total: usize = headers.get("x-total-count")?.to_str().ok()?.parse().ok()?;
+    (page * per_page < total).then(|| (page + 1).to_string())
+}
+
+#[async_trait]
+impl Forge for Gitea {
+    async fn pull_requests(
+        &self,
+        owner: &str,
+        name: &str,
+        first: usize,
+        after: Option<String>,
+    ) -> Result<Page<PullRequest>, Error> {
+        // The page number only means something for a given page size, so it stays the same and
+        // the last page is cut instead
+        let page = after.and_then(|a| a.parse().ok()).unwrap_or(1);
+        let (mut pulls, headers): (Vec<GiteaPullRequest>, _) = self
+            .client
+            .get(&format!(
+                "/repos/{}/{}/pulls?state=open&sort=newest&limit={}&page={}",
+                owner, name, MAX_PAGE_SIZE, page
+            ))
+            .await?;
+        pulls.truncate(first);
+
+        Ok(Page {
+            items: self.with_reviews(owner, name, pulls).await?,
+            next: next_page(&headers, page, MAX_PAGE_SIZE),
+        })
+    }
+
+    async fn closed_pull_requests(
+        &self,
+        owner: &str,
+        name: &str,
+        since: DateTime<Utc>,
+        max: usize,
+    ) -> Result<Vec<PullRequest>, Error> {
+        let mut closed = vec![];
+        let mut page = 1;
+        loop {
+            let (pulls, _): (Vec<GiteaPullRequest>, _) = self
+                .client
+                .get(&format!(
+                    "/repos/{}/{}/pulls?state=closed&sort=recentupdate&limit={}&page={}",
+                    owner, name, MAX_PAGE_SIZE, page
+                ))
+                .await?;
+
+            // Sorted by last update, the ones updated before the window can't have been closed
+            // in it
+            let exhausted =
+                pulls.len() < MAX_PAGE_SIZE || pulls.last().is_none_or(|pr| pr.updated_at < since);
+            closed.extend(
+                pulls
+                    .into_iter()
+                    .filter(|pr| pr.merged_at.or(pr.closed_at).is_some_and(|at| at >= since)),
+            );
+
+            if exhausted || closed.len() >= max {
+                break;
+            }
+            page += 1;
+        }
+        closed.truncate(max);
+
+        self.with_reviews(owner, name, closed).await
+    }
+
+    async fn assigned_issues(
+        &self,
+        owner: &str,
+        name: &str,
+        login: &str,
+    ) -> Result<Vec<Issue>, Error> {
+        let (issues, _): (Vec<GiteaIssue>, _) = self
+            .client
+            .get(&format!(
+                "/repos/{}/{}/issues?state=open&type=issues&assigned_by={}&limit={}",
+                owner, name, login, MAX_PAGE_SIZE
+            ))
+            .await?;
+
+        Ok(issues
+            .into_iter()
+            .map(|issue| Issue {
+                number: issue.number,
+                title: issue.title,
+                url: issue.html_url,
+                owner: owner.to_string(),
+                repo: name.to_string(),
+                body: issue.body.unwrap_or_default(),
+                author: issue.user.map(Into::into),
+                labels: issue.labels.into_iter().map(Into::into).collect(),
+                assignees: issue
+                    .assignees
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|a| a.login)
+                    .collect(),
+                milestone: issue.milestone.map(|m| m.title),
+                created_at: issue.created_at,
+                updated_at: issue.updated_at,
+            })
+            .collect())
+    }
+
+    async fn reviews(&self, owner: &str, name: &str, number: u64) -> Result<Vec<Review>, Error> {
+        Ok(self
+            .gitea_reviews(owner, name, number)
+            .await?
+            .into_iter()
+            .filter_map(|r| r.into_review())
+            .collect())
+    }
+
+    async fn timeline(
+        &self,
+        owner: &str,
+        name: &str,
+        number: u64,
+    ) -> Result<Vec<TimelineItem>, Error> {
+        // The reviews in the timeline lack their state, they are taken from the reviews instead
+        let (items, reviews) = tokio::try_join!(
+            self.gitea_timeline(owner, name, number),
+            self.gitea_reviews(owner, name, number)
+        )?;
+
+        let mut timeline: Vec<TimelineItem> = items
+            .into_iter()
+            .flat_map(GiteaTimelineItem::into_timeline)
+            .collect();
+        timeline.extend(
+            reviews
+                .into_iter()
+                .filter_map(|r| r.into_review())
+                .filter(|r| r.state != ReviewState::Pending)
+                .map(|r| TimelineItem {
+                    author: Some(r.author),
+                    created_at: r.submitted_at,
+                    event: TimelineEvent::Review {
+                        state: r.state,
+                        body: r.body,
+                    },
+                }),
+        );
+        timeline.sort_by_key(|item| item.created_at);
+        let cut = timeline.len().saturating_sub(MAX_TIMELINE_ITEMS);
+        timeline.drain(..cut);
+
+        Ok(timeline)
+    }
+
+    async fn overview(&self, owner: &str, name: &str) -> Result<RepositoryOverview, Error> {
+        let (repository, _): (GiteaRepository, _) = self
+            .client
+            .get(&format!("/repos/{}/{}", owner, name))
+            .await?;
+
+        // A repository without releases or statuses answers with a not found
+        let release_route = format!("/repos/{}/{}/releases/latest", owner, name);
+        let status_route = format!(
+            "/repos/{}/{}/commits/{}/status",
+            owner, name, repository.default_branch
+        );
+        let (release, status) = tokio::join!(
+            self.client.get::<GiteaRelease>(&release_route),
+            self.client.get::<CombinedStatus>(&status_route),
+        );
+
+        Ok(RepositoryOverview {
+            description: Some(repository.description).filter(|d| !d.is_empty()),
+            url: repository.html_url,
+            default_branch: Some(repository.default_branch),
+            open_pull_requests: Some(repository.open_pr_counter),
+            open_issues: Some(repository.open_issues_count),
+            latest_release: release.ok().map(|(release, _)| Release {
+                name: release.name,
+                tag: release.tag_name,
+                published_at: release.published_at,
+            }),
+            default_branch_checks: status.ok().and_then(|(status, _)| {
+                match status.state.as_str() {
+                    "" => None,
+                    "success" | "warning" => Some(CheckState::Success),
+                    "pending" => Some(CheckState::Pending),
+                    _ => Some(CheckState::Failure),
+                }
+            }),
+        })
+    }
+
+    async fn details(&self, owner: &str, name: &str, number: u64) -> Result<MergeInfo, Error> {
+        let (pr, _): (GiteaPullRequest, _) = self
+            .client
+            .get(&format!("/repos/{}/{}/pulls/{}", owner, name, number))
+            .await?;
+
+        Ok(MergeInfo {
+            mergeable: pr.mergeable,
+            // Gitea doesn't expose whether the pr can be rebased
+            rebaseable: None,
+            mergeable_state: None,
+        })
+    }
+
+    async fn checks(&self, owner: &str, name: &str, sha: &str) -> Result<Vec<Check>, Error> {
+        let (statuses, _): (Vec<CommitStatus>, _) = self
+            .client
+            .get(&format!(
+                "/repos/{}/{}/commits/{}/statuses?limit={}",
+                owner, name, sha, MAX_PAGE_SIZE
+            ))
+            .await?;
+
+        Ok(statuses
+            .into_iter()
+            .map(|status| Check {
+                state: match status.status.as_str() {
+                    "success" | "warning" => CheckState::Success,
+                    "pending" => CheckState::Pending,
+                    _ => CheckState::Failure,
+                },
+                name: status.context,
+                conclusion: status.status,
+                url: status.target_url,
+            })
+            .collect())
+    }
+
+    async fn teams(&self) -> Result<Vec<String>, Error> {
+        let (teams, _): (Vec<Team>, _) = self
+            .client
+            .get(&format!("/user/teams?limit={}", MAX_PAGE_SIZE))
+            .await?;
+
+        Ok(teams
+            .into_iter()
+            .filter_map(|t| Some(format!("{}/{}", t.organization?.name, t.name)))
+            .collect())
+    }
+}
+
+impl GiteaPullRequest {
+    fn into_pull_request(self, owner: &str, name: &str, reviews: Vec<Review>) -> PullRequest {
+        let is_wip = WIP_PREFIXES
+            .iter()
+            .any(|prefix| self.title.to_uppercase().starts_with(prefix));
+
+        PullRequest {
+            number: self.number,
+            is_draft: self.draft || is_wip,
+            state: match (self.merged, self.state.as_str()) {
+                (true, _) => PullRequestState::Merged,
+                (false, "closed") => PullRequestState::Closed,
+                _ => PullRequestState::Open,
+            },
+            closed_at: self.merged_at.or(self.closed_at),
+            merged_by: self.merged_by.map(|u| u.login),
+            title: self.title,
+            url: self.html_url,
+            owner: owner.to_string(),
+            repo: name.to_string(),
+            body: self.body.unwrap_or_default(),
+            is_fork: self.head.repo_id != self.base.repo_id,
+            head_sha: self.head.sha,
+            head_branch: self.head.name,
+            base_branch: self.base.name,
+            labels: self.labels.into_iter().map(Into::into).collect(),
+            milestone: self.milestone.map(|m| m.title),
+            created_at: self.created_at,
+            updated_at: Some(self.updated_at),
+            additions: self.additions,
+            deletions: self.deletions,
+            changed_files: self.changed_files,
+            author: self.user.map(Into::into),
+            // The combined status is not part of the list of pull requests
+            checks: None,
+            assignees: self
+                .assignees
+                .unwrap_or_default()
+                .into_iter()
+                .map(|a| a.login)
+                .collect(),
+            requested_reviewers: self
+                .requested_reviewers
+                .unwrap_or_default()
+                .into_iter()
+                .map(|r| r.login)
+                .collect(),
+            // Teams reviewing belong to the organization owning the repository
+            requested_teams: self
+                .requested_reviewers_teams
+                .unwrap_or_default()
+                .into_iter()
+                .map(|t| format!("{}/{}", owner, t.name))
+                .collect(),
+            latest_reviews: reviews,
+        }
+    }
+}
+
+impl GiteaReview {
+    // Review requests are also listed as reviews, they're not actual reviews
+    fn into_review(self) -> Option<Review> {
+        let state = match self.state.as_str() {
+            _ if self.dismissed => ReviewState::Dismissed,
+            "APPROVED" => ReviewState::Approved,
+            "REQUEST_CHANGES" => ReviewState::ChangesRequested,
+            "COMMENT" => ReviewState::Commented,
+            "PENDING" => ReviewState::Pending,
+            _ => return None,
+        };
+
+        Some(Review {
+            author: self.user.map(|u| u.login).unwrap_or_default(),
+            state,
+            commit: self.commit_id,
+            body: self.body.unwrap_or_default(),
+            submitted_at: self.submitted_at,
+        })
+    }
+}
+
+impl GiteaTimelineItem {
+    // A push becomes an item per commit
+    fn into_timeline(self) -> Vec<TimelineItem> {
+        let item = |event| TimelineItem {
+            author: self.user.as_ref().map(|u| u.login.clone()),
+            created_at: self.created_at,
+            event,
+        };
+        let body = self.body.clone().unwrap_or_default();
+
+        match self.kind.as_str() {
+            "comment" => vec![item(TimelineEvent::Comment { body })],
+            // Already listed from the reviews
+            "review" => vec![],
+            "label" => {
+                let Some(label) = self.label.as_ref().map(|l| l.name.clone()) else {
+                    return vec![];
+                };
+                // The body is `1` when the label was added
+                if body == "1" {
+                    vec![item(TimelineEvent::Labeled { label })]
+                } else {
+                    vec![item(TimelineEvent::Unlabeled { label })]
+                }
+            }
+            "pull_push" => {
+                let Ok(pushed) = serde_json::from_str::<PushedCommits>(&body) else {
+                    return vec![];
+                };
+                let short = |sha: &String| sha.chars().take(7).collect::<String>();
+                if pushed.is_force_push {
+                    vec![item(TimelineEvent::ForcePushed {
+                        before: pushed.commit_ids.first().map(short),
+                        after: pushed.commit_ids.get(1).map(short),
+                    })]
+                } else {
+                    pushed
+                        .commit_ids
+                        .iter()
+                        .map(|sha| {
+                            item(TimelineEvent::Commit {
+                                sha: short(sha),
+                                message: String::new(),
+                            })
+                        })
+                        .collect()
+                }
+            }
+            "review_request" => {
+                let reviewer = self
+                    .assignee
+                    .as_ref()
+                    .map(|u| u.login.clone())
+                    .or_else(|| self.assignee_team.as_ref().map(|t| t.name.clone()))
+                    .unwrap_or_default();
+                vec![item(TimelineEvent::ReviewRequested { reviewer })]
+            }
+            // e.g. `close`, `reopen`, `merge_pull` or `change_title`
+            kind => vec![item(TimelineEvent::Other(kind.replace('_', " ")))],
+        }
+    }
+}
+
+impl From<GiteaLabel> for Label {
+    fn from(label: GiteaLabel) -> Self {
+        Self {
+            name: label.name,
+            color: label.color.trim_start_matches('#').to_string(),
+        }
+    }
+}
+
+impl From<GiteaUser> for User {
+    fn from(user: GiteaUser) -> Self {
+        Self {
+            login: user.login,
+            id: Some(user.id.to_string()),
+            name: user.full_name.filter(|n| !n.is_empty()),
+            email: user.email.filter(|e| !e.is_empty()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::*;
+    use crate::forge::stub::{Response, StubServer};
+
+    fn pull_request(number: u64) -> Value {
+        let branch =
+            |name: &str| json!({ "sha": format!("sha{}", number), "ref": name, "repo_id": 1 });
+        json!({
+            "number": number,
+            "title": format!("Pull request {}", number),
+            "html_url": format!("https://codeberg.org/octo/app/pulls/{}", number),
+            "state": "open",
+            "updated_at": "2025-01-01T00:00:00Z",
+            "head": branch("feature"),
+            "base": branch("main"),
+            "user": { "id": 1, "login": "alice" },
+            "requested_reviewers": [{ "id": 2, "login": "me" }],
+        })
+    }
+
+    // Every pr has been approved by its own reviewer, e.g. `reviewer3` for #3
+    fn handler(path: &str) -> Response {
+        if let Some(rest) = path.strip_prefix("/api/v1/repos/octo/app/pulls/")
+            && let Some((number, _)) = rest.split_once("/reviews")
+        {
+            return Response::json(json!([{
+                "user": { "id": 3, "login": format!("reviewer{}", number) },
+                "state": "APPROVED",
+                "commit_id": format!("sha{}", number),
+            }]));
+        }
+
+        let page: u64 = path
+            .rsplit_once("&page=")
+            .and_then(|(_, page)| page.parse().ok())
+            .unwrap_or(1);
+        let pulls: Vec<Value> = (1..=MAX_PAGE_SIZE as u64)
+            .map(|i| pull_request((page - 1) * MAX_PAGE_SIZE as u64 + i))
+            .collect();
+        Response::json(json!(pulls)).header("x-total-count", "120")
+    }
+
+    #[tokio::test]
+    async fn pull_requests_come_with_their_reviews() {
+        let server = StubServer::start(handler).await;
+        let gitea = Gitea::new(Some(&server.url), None);
+
+        let page = gitea.pull_requests("octo", "app", 20, None).await.unwrap();
+
+        assert_eq!(page.items.len(), 20);
+        assert_eq!(page.next.as_deref(), Some("2"));
+        for pr in &page.items {
+            assert!(pr.is_authored_by("alice"));
+            assert!(pr.is_review_requested("me"));
+            let reviewer = format!("reviewer{}", pr.number);
+            assert_eq!(
+                pr.latest_review_by(&reviewer).map(|r| r.state),
+                Some(ReviewState::Approved)
+            );
+        }
+        // Only the reviews of the kept prs are fetched
+        assert_eq!(server.requests().len(), 21);
+    }
+
+    #[tokio::test]
+    async fn pages_keep_the_same_size() {
+        let server = StubServer::start(handler).await;
+        let gitea = Gitea::new(Some(&server.url), None);
+
+        let first = gitea.pull_requests("octo", "app", 70, None).await.unwrap();
+        let second = gitea
+            .pull_requests("octo", "app", 20, first.next.clone())
+            .await
+            .unwrap();
+
+        assert_eq!(first.items.len(), 50);
+        // The second page follows the first, only cut to what was asked
+        assert_eq!(second.items.len(), 20);
+        assert_eq!(second.items[0].number, 51);
+        assert_eq!(second.next.as_deref(), Some("3"));
+        let pages: Vec<String> = server
+            .requests()
+            .into_iter()
+            .filter(|path| path.contains("/pulls?"))
+            .collect();
+        assert!(pages.iter().all(|path| path.contains("limit=50&")));
+    }
+}
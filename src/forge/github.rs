@@ -0,0 +1,1080 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::future::try_join_all;
+
+use super::{
+    Check, CheckState, Error, Forge, Involved, Involvement, Issue, Label, MergeInfo, Notification,
+    NotificationAction, Page, PullRequest, PullRequestState, RateLimit, Release,
+    RepositoryOverview, Review, ReviewState, RunAction, RunFilter, TimelineEvent, TimelineItem,
+    User, WorkflowJob, WorkflowRun,
+};
+use crate::github;
+
+// Github rejects search queries longer than this
+const MAX_SEARCH_QUERY_LENGTH: usize = 256;
+
+// Splits the repositories into `repo:` qualifiers short enough to fit in queries with the given
+// prefix, a search being limited in length
+fn repository_qualifiers(prefix: &str, repositories: &[(String, String)]) -> Vec<String> {
+    let mut queries: Vec<String> = vec![];
+    for (owner, name) in repositories {
+        let qualifier = format!(" repo:{}/{}", owner, name);
+        match queries.last_mut() {
+            Some(query) if query.len() + qualifier.len() <= MAX_SEARCH_QUERY_LENGTH => {
+                query.push_str(&qualifier)
+            }
+            _ => queries.push(format!("{}{}", prefix, qualifier)),
+        }
+    }
+
+    queries
+}
+
+#[async_trait]
+impl Forge for github::Client {
+    async fn pull_requests(
+        &self,
+        owner: &str,
+        name: &str,
+        first: usize,
+        after: Option<String>,
+    ) -> Result<Page<PullRequest>, Error> {
+        let connection = self.open_pull_requests(owner, name, first, after).await?;
+
+        Ok(Page {
+            items: connection.nodes.into_iter().map(Into::into).collect(),
+            next: connection
+                .page_info
+                .end_cursor
+                .filter(|_| connection.page_info.has_next_page),
+        })
+    }
+
+    async fn closed_pull_requests(
+        &self,
+        owner: &str,
+        name: &str,
+        since: DateTime<Utc>,
+        max: usize,
+    ) -> Result<Vec<PullRequest>, Error> {
+        let mut pulls = vec![];
+        let mut after = None;
+        loop {
+            let connection = github::Client::closed_pull_requests(
+                self,
+                owner,
+                name,
+                max - pulls.len(),
+                after.take(),
+            )
+            .await?;
+
+            // Sorted by last update, the ones updated before the window can't have been closed
+            // in it
+            let exhausted = connection
+                .nodes
+                .last()
+                .is_none_or(|pr| pr.updated_at < since);
+            pulls.extend(
+                connection
+                    .nodes
+                    .into_iter()
+                    .filter(|pr| pr.closed_at.is_some_and(|at| at >= since))
+                    .map(PullRequest::from),
+            );
+
+            if exhausted || !connection.page_info.has_next_page || pulls.len() >= max {
+                break;
+            }
+            after = connection.page_info.end_cursor;
+        }
+
+        Ok(pulls)
+    }
+
+    async fn assigned_issues(
+        &self,
+        owner: &str,
+        name: &str,
+        login: &str,
+    ) -> Result<Vec<Issue>, Error> {
+        Ok(github::Client::assigned_issues(self, owner, name, login)
+            .await?
+            .into_iter()
+            .map(|issue| Issue {
+                number: issue.number,
+                title: issue.title,
+                url: issue.html_url,
+                owner: owner.to_string(),
+                repo: name.to_string(),
+                body: issue.body.unwrap_or_default(),
+                author: issue.user.map(Into::into),
+                labels: issue.labels.into_iter().map(Into::into).collect(),
+                assignees: issue.assignees.into_iter().map(|a| a.login).collect(),
+                milestone: issue.milestone.map(|m| m.title),
+                created_at: Some(issue.created_at),
+                updated_at: Some(issue.updated_at),
+            })
+            .collect())
+    }
+
+    async fn reviews(&self, owner: &str, name: &str, number: u64) -> Result<Vec<Review>, Error> {
+        Ok(github::Client::reviews(self, owner, name, number)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    async fn timeline(
+        &self,
+        owner: &str,
+        name: &str,
+        number: u64,
+    ) -> Result<Vec<TimelineItem>, Error> {
+        Ok(github::Client::timeline(self, owner, name, number)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    async fn overview(&self, owner: &str, name: &str) -> Result<RepositoryOverview, Error> {
+        let overview = self.repository_overview(owner, name).await?;
+        let checks = overview
+            .default_branch_ref
+            .as_ref()
+            .and_then(|b| b.target.as_ref()?.status_check_rollup.as_ref())
+            .map(|r| r.state.into());
+
+        Ok(RepositoryOverview {
+            description: overview.description.filter(|d| !d.is_empty()),
+            url: overview.url,
+            default_branch: overview.default_branch_ref.map(|b| b.name),
+            open_pull_requests: Some(overview.pull_requests.total_count),
+            open_issues: Some(overview.issues.total_count),
+            latest_release: overview.latest_release.map(|r| Release {
+                name: r.name.unwrap_or_else(|| r.tag_name.clone()),
+                tag: r.tag_name,
+                published_at: r.published_at,
+            }),
+            default_branch_checks: checks,
+        })
+    }
+
+    async fn details(&self, owner: &str, name: &str, number: u64) -> Result<MergeInfo, Error> {
+        let details = self.pull_request_details(owner, name, number).await?;
+
+        Ok(MergeInfo {
+            mergeable: details.mergeable,
+            rebaseable: details.rebaseable,
+            mergeable_state: details.mergeable_state,
+        })
+    }
+
+    async fn checks(&self, owner: &str, name: &str, sha: &str) -> Result<Vec<Check>, Error> {
+        let (runs, statuses) = tokio::join!(
+            self.check_runs(owner, name, sha),
+            self.commit_statuses(owner, name, sha)
+        );
+
+        Ok(runs?
+            .iter()
+            .map(Check::from)
+            .chain(statuses?.iter().map(Check::from))
+            .collect())
+    }
+
+    async fn teams(&self) -> Result<Vec<String>, Error> {
+        Ok(self.user_teams().await?)
+    }
+
+    async fn involved(
+        &self,
+        repositories: &[(String, String)],
+        login: &str,
+    ) -> Result<Vec<Involved>, Error> {
+        let mut involved: Vec<Involved> = vec![];
+
+        let searches = [
+            ("mentions", Involvement::Mentioned),
+            ("commenter", Involvement::Commented),
+        ];
+        for (qualifier, involvement) in searches {
+            let prefix = format!("is:pr is:open -author:{} {}:{}", login, qualifier, login);
+            for query in repository_qualifiers(&prefix, repositories) {
+                for pr in self
+                    .search_pull_requests(&query, github::MAX_PAGE_SIZE)
+                    .await?
+                {
+                    let pr = PullRequest::from(pr);
+                    // A pr can be found by both searches
+                    match involved.iter_mut().find(|i| i.pull_request.url == pr.url) {
+                        Some(found) => found.involvements.push(involvement),
+                        None => involved.push(Involved {
+                            pull_request: pr,
+                            involvements: vec![involvement],
+                        }),
+                    }
+                }
+            }
+        }
+
+        Ok(involved)
+    }
+
+    async fn notifications(
+        &self,
+        repositories: &[(String, String)],
+    ) -> Result<Vec<Notification>, Error> {
+        // The inbox of the user spans every repository, listing it would leave out the configured
+        // repositories whose notifications are older than the first page
+        let inboxes = try_join_all(
+            repositories
+                .iter()
+                .map(|(owner, name)| github::Client::notifications(self, owner, name)),
+        )
+        .await?;
+
+        Ok(repositories
+            .iter()
+            .zip(inboxes)
+            .flat_map(|((owner, name), notifications)| {
+                // Names are case insensitive, keep the ones of the configuration
+                notifications.into_iter().map(|n| Notification {
+                    owner: owner.clone(),
+                    repo: name.clone(),
+                    ..n.into()
+                })
+            })
+            .collect())
+    }
+
+    async fn update_notification(
+        &self,
+        thread_id: &str,
+        action: NotificationAction,
+    ) -> Result<(), Error> {
+        match action {
+            NotificationAction::MarkRead => self.mark_notification_read(thread_id).await?,
+            NotificationAction::MarkDone => self.mark_notification_done(thread_id).await?,
+            NotificationAction::Unsubscribe => {
+                github::Client::unsubscribe(self, thread_id).await?;
+                self.mark_notification_done(thread_id).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn workflow_runs(
+        &self,
+        owner: &str,
+        name: &str,
+        filter: &RunFilter,
+    ) -> Result<Vec<WorkflowRun>, Error> {
+        let filter = match filter {
+            RunFilter::DefaultBranch => {
+                let repository = self.repository(owner, name).await?;
+                format!("branch={}", repository.default_branch)
+            }
+            RunFilter::Commit(sha) => format!("head_sha={}", sha),
+        };
+
+        Ok(github::Client::workflow_runs(self, owner, name, &filter)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    async fn workflow_jobs(
+        &self,
+        owner: &str,
+        name: &str,
+        run_id: u64,
+    ) -> Result<Vec<WorkflowJob>, Error> {
+        Ok(github::Client::workflow_jobs(self, owner, name, run_id)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    async fn job_log(&self, owner: &str, name: &str, job_id: u64) -> Result<String, Error> {
+        Ok(github::Client::job_log(self, owner, name, job_id).await?)
+    }
+
+    async fn update_workflow_run(
+        &self,
+        owner: &str,
+        name: &str,
+        run_id: u64,
+        action: RunAction,
+    ) -> Result<(), Error> {
+        match action {
+            RunAction::RerunFailedJobs => self.rerun_failed_jobs(owner, name, run_id).await?,
+            RunAction::Cancel => self.cancel_run(owner, name, run_id).await?,
+        }
+
+        Ok(())
+    }
+
+    fn rate_limit(&self) -> Option<RateLimit> {
+        github::Client::rate_limit(self)
+    }
+}
+
+// State of a run or job from its status and, once completed, its conclusion
+fn run_state(status: &str, conclusion: Option<&str>) -> CheckState {
+    match (status, conclusion) {
+        ("completed", Some("success" | "neutral" | "skipped")) => CheckState::Success,
+        ("completed", _) => CheckState::Failure,
+        _ => CheckState::Pending,
+    }
+}
+
+impl From<github::Error> for Error {
+    fn from(err: github::Error) -> Self {
+        match &err {
+            github::Error::Api(octocrab::Error::GitHub { source, .. }) => Self::Status {
+                status: source.status_code.as_u16(),
+                message: source.message.clone(),
+            },
+            // Like the rest api, a repository that is gone is a 404
+            github::Error::NotFound(message) => Self::Status {
+                status: 404,
+                message: message.clone(),
+            },
+            _ => Self::Other(err.to_string()),
+        }
+    }
+}
+
+impl From<github::PullRequest> for PullRequest {
+    fn from(pr: github::PullRequest) -> Self {
+        let checks = pr
+            .commits
+            .nodes
+            .last()
+            .and_then(|c| c.commit.status_check_rollup.as_ref())
+            .map(|r| r.state.into());
+        let (owner, repo) = pr
+            .base_repository
+            .map(|r| (r.owner.login, r.name))
+            .unwrap_or_default();
+        let requested_reviewers = pr
+            .review_requests
+            .nodes
+            .iter()
+            .filter_map(|r| r.requested_reviewer.as_ref()?.login.clone())
+            .collect();
+        let requested_teams = pr
+            .review_requests
+            .nodes
+            .iter()
+            .filter_map(|r| r.requested_reviewer.as_ref()?.team())
+            .collect();
+
+        Self {
+            number: pr.number,
+            title: pr.title,
+            url: pr.url,
+            owner,
+            repo,
+            body: pr.body,
+            is_draft: pr.is_draft,
+            state: match pr.state {
+                github::PullRequestState::Open => PullRequestState::Open,
+                github::PullRequestState::Closed => PullRequestState::Closed,
+                github::PullRequestState::Merged => PullRequestState::Merged,
+            },
+            closed_at: pr.closed_at,
+            merged_by: pr.merged_by.map(|m| m.login),
+            head_sha: pr.head_ref_oid,
+            head_branch: pr.head_ref_name,
+            base_branch: pr.base_ref_name,
+            is_fork: pr.is_cross_repository,
+            labels: pr
+                .labels
+                .map(|l| l.nodes)
+                .unwrap_or_default()
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            milestone: pr.milestone.map(|m| m.title),
+            created_at: Some(pr.created_at),
+            updated_at: Some(pr.updated_at),
+            additions: Some(pr.additions),
+            deletions: Some(pr.deletions),
+            changed_files: Some(pr.changed_files),
+            author: pr.author.map(Into::into),
+            checks,
+            assignees: pr.assignees.nodes.into_iter().map(|a| a.login).collect(),
+            requested_reviewers,
+            requested_teams,
+            latest_reviews: pr
+                .latest_reviews
+                .nodes
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        }
+    }
+}
+
+impl From<github::Notification> for Notification {
+    fn from(notification: github::Notification) -> Self {
+        let github::Notification {
+            id,
+            unread,
+            reason,
+            updated_at,
+            subject,
+            repository,
+        } = notification;
+        // The subject is only linked through the api, e.g. `.../repos/owner/name/pulls/12`
+        let number = subject
+            .url
+            .as_deref()
+            .and_then(|url| url.rsplit('/').next()?.parse().ok());
+        let url = match (subject.kind.as_str(), number) {
+            ("PullRequest", Some(number)) => format!("{}/pull/{}", repository.html_url, number),
+            ("Issue", Some(number)) => format!("{}/issues/{}", repository.html_url, number),
+            _ => repository.html_url,
+        };
+
+        Self {
+            thread_id: id,
+            owner: repository.owner.login,
+            repo: repository.name,
+            title: subject.title,
+            kind: subject.kind,
+            number,
+            url,
+            reason,
+            unread,
+            updated_at,
+        }
+    }
+}
+
+impl From<github::StatusState> for CheckState {
+    fn from(state: github::StatusState) -> Self {
+        match state {
+            github::StatusState::Success => Self::Success,
+            github::StatusState::Failure | github::StatusState::Error => Self::Failure,
+            github::StatusState::Pending | github::StatusState::Expected => Self::Pending,
+        }
+    }
+}
+
+impl From<github::WorkflowRun> for WorkflowRun {
+    fn from(run: github::WorkflowRun) -> Self {
+        let status = run.status.unwrap_or_default();
+
+        Self {
+            id: run.id,
+            name: run.name.unwrap_or_default(),
+            number: run.run_number,
+            event: run.event,
+            branch: run.head_branch,
+            state: run_state(&status, run.conclusion.as_deref()),
+            conclusion: run.conclusion.unwrap_or(status),
+            url: run.html_url,
+            started_at: run.run_started_at,
+            updated_at: run.updated_at,
+        }
+    }
+}
+
+impl From<github::WorkflowJob> for WorkflowJob {
+    fn from(job: github::WorkflowJob) -> Self {
+        Self {
+            id: job.id,
+            name: job.name,
+            state: run_state(&job.status, job.conclusion.as_deref()),
+            conclusion: job.conclusion.unwrap_or(job.status),
+            started_at: job.started_at,
+            completed_at: job.completed_at,
+            url: job.html_url,
+        }
+    }
+}
+
+impl From<github::Label> for Label {
+    fn from(label: github::Label) -> Self {
+        Self {
+            name: label.name,
+            color: label.color,
+        }
+    }
+}
+
+impl From<github::Actor> for User {
+    fn from(actor: github::Actor) -> Self {
+        Self {
+            login: actor.login,
+            id: actor.database_id.map(|id| id.to_string()),
+            name: actor.name,
+            email: actor.email,
+        }
+    }
+}
+
+impl From<github::Review> for Review {
+    fn from(review: github::Review) -> Self {
+        Self {
+            author: review.author.map(|a| a.login).unwrap_or_default(),
+            state: review.state.into(),
+            commit: review.commit.map(|c| c.oid),
+            body: review.body,
+            submitted_at: review.submitted_at,
+        }
+    }
+}
+
+impl From<github::PullRequestReview> for Review {
+    fn from(review: github::PullRequestReview) -> Self {
+        Self {
+            author: review.user.map(|u| u.login).unwrap_or_default(),
+            state: review.state.into(),
+            commit: review.commit_id,
+            body: review.body.unwrap_or_default(),
+            submitted_at: review.submitted_at,
+        }
+    }
+}
+
+impl From<github::TimelineItem> for TimelineItem {
+    fn from(item: github::TimelineItem) -> Self {
+        let login = |actor: Option<github::Actor>| actor.map(|a| a.login);
+        let (author, created_at, event) = match item {
+            github::TimelineItem::IssueComment {
+                author,
+                body,
+                created_at,
+            } => (
+                login(author),
+                Some(created_at),
+                TimelineEvent::Comment { body },
+            ),
+            github::TimelineItem::PullRequestReview {
+                author,
+                state,
+                body,
+                submitted_at,
+            } => (
+                login(author),
+                submitted_at,
+                TimelineEvent::Review {
+                    state: state.into(),
+                    body,
+                },
+            ),
+            github::TimelineItem::PullRequestCommit { commit } => (
+                // Commits made with an email unknown to github only have a name
+                commit
+                    .author
+                    .and_then(|a| a.user.map(|u| u.login).or(a.name)),
+                Some(commit.committed_date),
+                TimelineEvent::Commit {
+                    sha: commit.abbreviated_oid,
+                    message: commit.message_headline,
+                },
+            ),
+            github::TimelineItem::LabeledEvent {
+                actor,
+                label,
+                created_at,
+            } => (
+                login(actor),
+                Some(created_at),
+                TimelineEvent::Labeled { label: label.name },
+            ),
+            github::TimelineItem::UnlabeledEvent {
+                actor,
+                label,
+                created_at,
+            } => (
+                login(actor),
+                Some(created_at),
+                TimelineEvent::Unlabeled { label: label.name },
+            ),
+            github::TimelineItem::HeadRefForcePushedEvent {
+                actor,
+                before_commit,
+                after_commit,
+                created_at,
+            } => (
+                login(actor),
+                Some(created_at),
+                TimelineEvent::ForcePushed {
+                    before: before_commit.map(|c| c.abbreviated_oid),
+                    after: after_commit.map(|c| c.abbreviated_oid),
+                },
+            ),
+            github::TimelineItem::ReviewRequestedEvent {
+                actor,
+                requested_reviewer,
+                created_at,
+            } => (
+                login(actor),
+                Some(created_at),
+                TimelineEvent::ReviewRequested {
+                    reviewer: requested_reviewer
+                        .and_then(|r| r.login.clone().or_else(|| r.team()))
+                        .unwrap_or_default(),
+                },
+            ),
+            github::TimelineItem::ReadyForReviewEvent { actor, created_at } => (
+                login(actor),
+                Some(created_at),
+                TimelineEvent::ReadyForReview,
+            ),
+        };
+
+        Self {
+            author,
+            created_at,
+            event,
+        }
+    }
+}
+
+impl From<github::ReviewState> for ReviewState {
+    fn from(state: github::ReviewState) -> Self {
+        match state {
+            github::ReviewState::Pending => Self::Pending,
+            github::ReviewState::Commented => Self::Commented,
+            github::ReviewState::Approved => Self::Approved,
+            github::ReviewState::ChangesRequested => Self::ChangesRequested,
+            github::ReviewState::Dismissed => Self::Dismissed,
+        }
+    }
+}
+
+impl From<&github::CheckRun> for Check {
+    fn from(run: &github::CheckRun) -> Self {
+        let state = match run.conclusion.as_deref() {
+            None => CheckState::Pending,
+            Some("success" | "neutral" | "skipped") => CheckState::Success,
+            Some(_) => CheckState::Failure,
+        };
+
+        Self {
+            name: run.name.clone(),
+            state,
+            conclusion: run.conclusion.clone().unwrap_or_else(|| run.status.clone()),
+            url: run.html_url.clone(),
+        }
+    }
+}
+
+impl From<&github::CommitStatus> for Check {
+    fn from(commit_status: &github::CommitStatus) -> Self {
+        let state = match commit_status.state.as_str() {
+            "success" => CheckState::Success,
+            "pending" => CheckState::Pending,
+            _ => CheckState::Failure,
+        };
+
+        Self {
+            name: commit_status.context.clone(),
+            state,
+            conclusion: commit_status.state.clone(),
+            url: commit_status.target_url.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::forge::stub::{Response, StubServer};
+
+    async fn client(response: Response) -> github::Client {
+        let server = StubServer::start(move |_| response.clone()).await;
+        let octocrab = octocrab::Octocrab::builder()
+            .base_uri(server.url.as_str())
+            .unwrap()
+            .personal_token("token".to_string())
+            .build()
+            .unwrap();
+        github::Client::new(octocrab, 1)
+    }
+
+    // Pull request as returned by the graphql api
+    fn pull_request_node(number: u64) -> serde_json::Value {
+        json!({
+            "number": number,
+            "title": format!("Pr {}", number),
+            "url": format!("https://github.com/octo/app/pull/{}", number),
+            "body": "",
+            "isDraft": false,
+            "state": "OPEN",
+            "closedAt": null,
+            "updatedAt": "2025-01-02T00:00:00Z",
+            "mergedBy": null,
+            "headRefOid": "abc",
+            "headRefName": "fix",
+            "baseRefName": "main",
+            "isCrossRepository": false,
+            "labels": { "nodes": [] },
+            "milestone": null,
+            "createdAt": "2025-01-01T00:00:00Z",
+            "additions": 0,
+            "deletions": 0,
+            "changedFiles": 0,
+            "commits": { "nodes": [] },
+            "baseRepository": { "name": "app", "owner": { "login": "octo" } },
+            "author": { "login": "alice" },
+            "assignees": { "nodes": [] },
+            "reviewRequests": { "nodes": [] },
+            "latestReviews": { "nodes": [] },
+        })
+    }
+
+    fn open_pull_requests(nodes: Vec<serde_json::Value>) -> Response {
+        Response::json(json!({
+            "data": {
+                "repository": {
+                    "pullRequests": {
+                        "pageInfo": { "hasNextPage": false, "endCursor": null },
+                        "nodes": nodes,
+                    },
+                },
+            },
+        }))
+    }
+
+    #[tokio::test]
+    async fn pull_requests_come_with_their_reviews_and_authors() {
+        let mut node = pull_request_node(1);
+        node["author"] = json!({
+            "login": "alice",
+            "databaseId": 7,
+            "name": "Alice",
+            "email": "alice@example.com",
+        });
+        node["reviewRequests"] = json!({ "nodes": [
+            { "requestedReviewer": { "login": "me" } },
+            { "requestedReviewer": { "slug": "core", "organization": { "login": "octo" } } },
+            { "requestedReviewer": null },
+        ]});
+        node["latestReviews"] = json!({ "nodes": [{
+            "author": { "login": "bob" },
+            "state": "APPROVED",
+            "commit": { "oid": "abc" },
+            "body": "Looks good",
+            "submittedAt": "2025-01-02T00:00:00Z",
+        }]});
+        let server = StubServer::start(move |_| open_pull_requests(vec![node.clone()])).await;
+        let octocrab = octocrab::Octocrab::builder()
+            .base_uri(server.url.as_str())
+            .unwrap()
+            .build()
+            .unwrap();
+        let forge = github::Client::new(octocrab, 1);
+
+        let page = forge.pull_requests("octo", "app", 10, None).await.unwrap();
+
+        // Everything comes from a single query
+        assert_eq!(server.requests(), ["/graphql"]);
+        let pr = &page.items[0];
+        let author = pr.author.as_ref().unwrap();
+        assert_eq!(author.login, "alice");
+        assert_eq!(author.id.as_deref(), Some("7"));
+        assert_eq!(author.name.as_deref(), Some("Alice"));
+        assert_eq!(pr.requested_reviewers, ["me"]);
+        assert_eq!(pr.requested_teams, ["octo/core"]);
+        assert_eq!(
+            pr.latest_reviews,
+            [Review {
+                author: "bob".to_string(),
+                state: ReviewState::Approved,
+                commit: Some("abc".to_string()),
+                body: "Looks good".to_string(),
+                submitted_at: Some("2025-01-02T00:00:00Z".parse().unwrap()),
+            }]
+        );
+        assert!(page.next.is_none());
+    }
+
+    #[tokio::test]
+    async fn pull_requests_carry_their_labels_branches_and_changes() {
+        let mut node = pull_request_node(1);
+        node["labels"] = json!({ "nodes": [{ "name": "bug", "color": "d73a4a" }] });
+        node["milestone"] = json!({ "title": "v1.0" });
+        node["isCrossRepository"] = json!(true);
+        node["additions"] = json!(10);
+        node["deletions"] = json!(2);
+        node["changedFiles"] = json!(3);
+        node["assignees"] = json!({ "nodes": [{ "login": "me" }] });
+        node["commits"] = json!({ "nodes": [
+            { "commit": { "statusCheckRollup": { "state": "FAILURE" } } },
+        ]});
+        let forge = client(open_pull_requests(vec![node])).await;
+
+        let page = forge.pull_requests("octo", "app", 10, None).await.unwrap();
+
+        let pr = &page.items[0];
+        assert_eq!(
+            pr.labels,
+            [Label {
+                name: "bug".to_string(),
+                color: "d73a4a".to_string(),
+            }]
+        );
+        assert_eq!(pr.milestone.as_deref(), Some("v1.0"));
+        assert_eq!(
+            (pr.head_branch.as_str(), pr.base_branch.as_str()),
+            ("fix", "main")
+        );
+        assert!(pr.is_fork);
+        assert_eq!(
+            (pr.additions, pr.deletions, pr.changed_files),
+            (Some(10), Some(2), Some(3))
+        );
+        assert_eq!(pr.assignees, ["me"]);
+        assert_eq!(pr.checks, Some(CheckState::Failure));
+        assert_eq!(pr.created_at, Some("2025-01-01T00:00:00Z".parse().unwrap()));
+        assert_eq!(pr.updated_at, Some("2025-01-02T00:00:00Z".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn missing_repository_is_not_found() {
+        let forge = client(Response::json(json!({
+            "data": { "repository": null },
+            "errors": [{
+                "type": "NOT_FOUND",
+                "message": "Could not resolve to a Repository with the name 'octo/gone'.",
+            }],
+        })))
+        .await;
+
+        let err = forge
+            .pull_requests("octo", "gone", 10, None)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, Error::Status { status: 404, .. }));
+    }
+
+    #[tokio::test]
+    async fn not_found_errors_without_data_are_not_found() {
+        let forge = client(Response::json(json!({
+            "data": null,
+            "errors": [{ "type": "NOT_FOUND", "message": "Could not resolve to a User" }],
+        })))
+        .await;
+
+        let err = forge.overview("octo", "gone").await.unwrap_err();
+
+        assert!(matches!(err, Error::Status { status: 404, .. }));
+    }
+
+    #[tokio::test]
+    async fn assigned_issues_follow_the_pages() {
+        let server = StubServer::start(|path| {
+            let page = if path.ends_with("&page=1") { 0 } else { 1 };
+            // The first page is full of prs, the second one has the issue
+            let issues: Vec<serde_json::Value> = (0..[100, 1][page])
+                .map(|i| {
+                    json!({
+                        "number": page * 100 + i + 1,
+                        "title": "Issue",
+                        "html_url": "https://github.com/octo/app/issues/1",
+                        "created_at": "2025-01-01T00:00:00Z",
+                        "updated_at": "2025-01-01T00:00:00Z",
+                        "pull_request": (page == 0).then(|| json!({})),
+                    })
+                })
+                .collect();
+            Response::json(json!(issues))
+        })
+        .await;
+        let octocrab = octocrab::Octocrab::builder()
+            .base_uri(server.url.as_str())
+            .unwrap()
+            .build()
+            .unwrap();
+        let forge = github::Client::new(octocrab, 1);
+
+        let issues = forge.assigned_issues("octo", "app", "me").await.unwrap();
+
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].number, 101);
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn checks_follow_the_pages() {
+        let server = StubServer::start(|path| {
+            if path.contains("/check-runs?") {
+                let runs: Vec<serde_json::Value> = if path.ends_with("&page=1") {
+                    (0..100)
+                        .map(|i| json!({ "name": format!("job {}", i), "status": "completed", "conclusion": "success" }))
+                        .collect()
+                } else {
+                    vec![json!({ "name": "deploy", "status": "in_progress", "conclusion": null })]
+                };
+                Response::json(json!({ "total_count": 101, "check_runs": runs }))
+            } else {
+                Response::json(json!({
+                    "total_count": 1,
+                    "statuses": [{ "context": "ci/legacy", "state": "pending" }],
+                }))
+            }
+        })
+        .await;
+        let octocrab = octocrab::Octocrab::builder()
+            .base_uri(server.url.as_str())
+            .unwrap()
+            .build()
+            .unwrap();
+        let forge = github::Client::new(octocrab, 1);
+
+        let checks = forge.checks("octo", "app", "sha").await.unwrap();
+
+        assert_eq!(checks.len(), 102);
+        assert_eq!(checks[0].state, CheckState::Success);
+        // Still running, its status stands for the conclusion
+        assert_eq!(checks[100].state, CheckState::Pending);
+        assert_eq!(checks[100].conclusion, "in_progress");
+        assert_eq!(checks[101].name, "ci/legacy");
+        assert_eq!(checks[101].state, CheckState::Pending);
+        let mut requests = server.requests();
+        requests.sort();
+        assert_eq!(
+            requests,
+            [
+                "/repos/octo/app/commits/sha/check-runs?per_page=100&page=1",
+                "/repos/octo/app/commits/sha/check-runs?per_page=100&page=2",
+                "/repos/octo/app/commits/sha/status?per_page=100&page=1",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn notifications_are_listed_per_repository() {
+        let server = StubServer::start(|path| {
+            // The api answers with the actual case of the names
+            let (owner, name, id) = if path.starts_with("/repos/octo/app/") {
+                ("Octo", "App", "1")
+            } else {
+                ("octo", "lib", "2")
+            };
+            Response::json(json!([{
+                "id": id,
+                "unread": true,
+                "reason": "mention",
+                "updated_at": "2025-01-01T00:00:00Z",
+                "subject": {
+                    "title": "Fix",
+                    "url": format!("https://api.github.com/repos/{owner}/{name}/pulls/{id}"),
+                    "type": "PullRequest",
+                },
+                "repository": {
+                    "name": name,
+                    "owner": { "login": owner },
+                    "html_url": format!("https://github.com/{owner}/{name}"),
+                },
+            }]))
+        })
+        .await;
+        let octocrab = octocrab::Octocrab::builder()
+            .base_uri(server.url.as_str())
+            .unwrap()
+            .build()
+            .unwrap();
+        let forge = github::Client::new(octocrab, 1);
+        let repositories = [
+            ("octo".to_string(), "app".to_string()),
+            ("octo".to_string(), "lib".to_string()),
+        ];
+
+        let notifications = Forge::notifications(&forge, &repositories).await.unwrap();
+
+        let repos: Vec<(&str, &str, &str)> = notifications
+            .iter()
+            .map(|n| (n.thread_id.as_str(), n.owner.as_str(), n.repo.as_str()))
+            .collect();
+        assert_eq!(repos, [("1", "octo", "app"), ("2", "octo", "lib")]);
+        assert_eq!(notifications[0].url, "https://github.com/Octo/App/pull/1");
+        assert_eq!(
+            server.requests(),
+            [
+                "/repos/octo/app/notifications?all=true&per_page=50",
+                "/repos/octo/lib/notifications?all=true&per_page=50",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn timeline_pages_backwards_from_the_latest_entry() {
+        let pages = std::sync::atomic::AtomicUsize::new(0);
+        let server = StubServer::start(move |_| {
+            let comment = |n: u32| {
+                json!({
+                    "__typename": "IssueComment",
+                    "author": { "login": "alice" },
+                    "body": format!("Comment {n}"),
+                    "createdAt": format!("2025-01-0{n}T00:00:00Z"),
+                })
+            };
+            // The latest page comes first
+            let (nodes, has_previous_page) =
+                match pages.fetch_add(1, std::sync::atomic::Ordering::SeqCst) {
+                    0 => (vec![comment(3), comment(4)], true),
+                    _ => (vec![comment(1), comment(2)], false),
+                };
+            Response::json(json!({
+                "data": { "repository": { "pullRequest": { "timelineItems": {
+                    "pageInfo": {
+                        "hasNextPage": false,
+                        "endCursor": null,
+                        "hasPreviousPage": has_previous_page,
+                        "startCursor": "cursor",
+                    },
+                    "nodes": nodes,
+                }}}},
+            }))
+        })
+        .await;
+        let octocrab = octocrab::Octocrab::builder()
+            .base_uri(server.url.as_str())
+            .unwrap()
+            .build()
+            .unwrap();
+        let forge = github::Client::new(octocrab, 1);
+
+        let timeline = Forge::timeline(&forge, "octo", "app", 1).await.unwrap();
+
+        let bodies: Vec<String> = timeline
+            .into_iter()
+            .map(|item| match item.event {
+                TimelineEvent::Comment { body } => body,
+                event => panic!("unexpected {:?}", event),
+            })
+            .collect();
+        assert_eq!(bodies, ["Comment 1", "Comment 2", "Comment 3", "Comment 4"]);
+    }
+
+    #[tokio::test]
+    async fn other_graphql_errors_are_kept() {
+        let forge = client(Response::json(json!({
+            "errors": [{ "type": "MAX_NODE_LIMIT_EXCEEDED", "message": "Too many nodes" }],
+        })))
+        .await;
+
+        let err = forge
+            .pull_requests("octo", "app", 10, None)
+            .await
+            .unwrap_err();
+
+        assert_eq!(err, Error::Other("Too many nodes".to_string()));
+    }
+}
@@ -0,0 +1,694 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::header::HeaderName;
+use serde::Deserialize;
+
+use super::{
+    Check, CheckState, Error, Forge, Issue, Label, MergeInfo, Page, PullRequest, PullRequestState,
+    Release, RepositoryOverview, Review, ReviewState, TimelineEvent, TimelineItem, User,
+    rest::RestClient,
+};
+
+const DEFAULT_HOST: &str = "gitlab.com";
+// Maximum page size allowed by the gitlab api
+const MAX_PAGE_SIZE: usize = 100;
+// Longer conversations are cut, the latest entries are kept
+const MAX_TIMELINE_ITEMS: usize = 500;
+
+/// Merge requests of a GitLab instance, mapped to pull requests
+#[derive(Debug)]
+pub struct GitLab {
+    client: RestClient,
+}
+
+#[derive(Debug, Deserialize)]
+struct MergeRequest {
+    iid: u64,
+    title: String,
+    web_url: String,
+    description: Option<String>,
+    #[serde(default)]
+    draft: bool,
+    // Older instances only know about the work in progress flag
+    #[serde(default)]
+    work_in_progress: bool,
+    // `opened`, `closed`, `locked` or `merged`
+    state: String,
+    merged_at: Option<DateTime<Utc>>,
+    closed_at: Option<DateTime<Utc>>,
+    // Older instances only know about the deprecated `merged_by`
+    #[serde(alias = "merged_by")]
+    merge_user: Option<GitLabUser>,
+    sha: Option<String>,
+    source_branch: String,
+    target_branch: String,
+    source_project_id: u64,
+    target_project_id: u64,
+    // Only detailed when asked for with `with_labels_details`
+    #[serde(default)]
+    labels: Vec<GitLabLabel>,
+    milestone: Option<Milestone>,
+    created_at: Option<DateTime<Utc>>,
+    updated_at: Option<DateTime<Utc>>,
+    author: Option<GitLabUser>,
+    #[serde(default)]
+    assignees: Vec<GitLabUser>,
+    #[serde(default)]
+    reviewers: Vec<GitLabUser>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitLabIssue {
+    iid: u64,
+    title: String,
+    web_url: String,
+    description: Option<String>,
+    author: Option<GitLabUser>,
+    // Only detailed when asked for with `with_labels_details`
+    #[serde(default)]
+    labels: Vec<GitLabLabel>,
+    #[serde(default)]
+    assignees: Vec<GitLabUser>,
+    milestone: Option<Milestone>,
+    created_at: Option<DateTime<Utc>>,
+    updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitLabLabel {
+    name: String,
+    // e.g. `#d73a4a`
+    color: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Milestone {
+    title: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct MergeRequestDetails {
+    // `can_be_merged`, `cannot_be_merged`, `checking` or `unchecked`
+    merge_status: Option<String>,
+    // e.g. `mergeable`, `conflict`, `need_rebase`, `ci_must_pass`
+    detailed_merge_status: Option<String>,
+    #[serde(default)]
+    has_conflicts: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitLabUser {
+    id: u64,
+    username: String,
+    name: Option<String>,
+    // Only visible to admins or for the token's own user
+    public_email: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Approvals {
+    #[serde(default)]
+    approved_by: Vec<Approval>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Approval {
+    user: GitLabUser,
+}
+
+#[derive(Debug, Deserialize)]
+struct Note {
+    author: GitLabUser,
+    body: String,
+    created_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    system: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct Project {
+    description: Option<String>,
+    web_url: String,
+    // Missing for empty projects
+    default_branch: Option<String>,
+    // Missing when the issues are disabled
+    open_issues_count: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitLabRelease {
+    name: Option<String>,
+    tag_name: String,
+    released_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Pipeline {
+    // e.g. `success`, `failed`, `running`, `canceled`
+    status: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CommitStatus {
+    name: String,
+    // e.g. `success`, `failed`, `running`, `pending`, `canceled`, `skipped`
+    status: String,
+    target_url: Option<String>,
+}
+
+impl GitLab {
+    /// Client of the instance at the given host, defaults to gitlab.com
+    pub fn new(host: Option<&str>, token: Option<String>) -> Self {
+        let auth = token.map(|token| (HeaderName::from_static("private-token"), token));
+
+        Self {
+            client: RestClient::new(host.unwrap_or(DEFAULT_HOST), "/api/v4", auth),
+        }
+    }
+}
+
+// Projects are addressed by their url encoded `namespace/name` path
+fn project_path(owner: &str, name: &str) -> String {
+    format!(
+        "/projects/{}",
+        format!("{}/{}", owner, name).replace('/', "%2F")
+    )
+}
+
+// The events of a merge request are notes written by gitlab, e.g. `approved this merge request`
+fn system_event(body: &str) -> TimelineEvent {
+    let first_line = body.lines().next().unwrap_or_default();
+    if let Some(reviewer) = first_line.strip_prefix("requested review from @") {
+        TimelineEvent::ReviewRequested {
+            reviewer: reviewer.to_string(),
+        }
+    } else if first_line.starts_with("marked this merge request as **ready**") {
+        TimelineEvent::ReadyForReview
+    } else if first_line == "approved this merge request" {
+        TimelineEvent::Review {
+            state: ReviewState::Approved,
+            body: String::new(),
+        }
+    } else {
+        TimelineEvent::Other(first_line.to_string())
+    }
+}
+
+#[async_trait]
+impl Forge for GitLab {
+    async fn pull_requests(
+        &self,
+        owner: &str,
+        name: &str,
+        first: usize,
+        after: Option<String>,
+    ) -> Result<Page<PullRequest>, Error> {
+        // Gitlab paginates by page number, which is used as cursor. The page number only means
+        // something for a given page size, so it stays the same and the last page is cut instead
+        let page = after.unwrap_or_else(|| "1".to_string());
+        let path = format!(
+            "{}/merge_requests?state=opened&order_by=created_at&sort=desc&with_labels_details=true&per_page={}&page={}",
+            project_path(owner, name),
+            MAX_PAGE_SIZE,
+            page
+        );
+        let (merge_requests, headers): (Vec<MergeRequest>, _) = self.client.get(&path).await?;
+
+        Ok(Page {
+            items: merge_requests
+                .into_iter()
+                .take(first)
+                .map(|mr| mr.into_pull_request(owner, name))
+                .collect(),
+            next: headers
+                .get("x-next-page")
+                .and_then(|v| v.to_str().ok())
+                .filter(|v| !v.is_empty())
+                .map(str::to_string),
+        })
+    }
+
+    async fn closed_pull_requests(
+        &self,
+        owner: &str,
+        name: &str,
+        since: DateTime<Utc>,
+        max: usize,
+    ) -> Result<Vec<PullRequest>, Error> {
+        let mut pulls = vec![];
+        let mut page = Some("1".to_string());
+        while let Some(current) = page.take()
+            && pulls.len() < max
+        {
+            // Merge requests closed in the window have been updated in it too
+            let path = format!(
+                "{}/merge_requests?updated_after={}&order_by=updated_at&sort=desc&with_labels_details=true&per_page={}&page={}",
+                project_path(owner, name),
+                since.format("%Y-%m-%dT%H:%M:%SZ"),
+                MAX_PAGE_SIZE,
+                current
+            );
+            let (merge_requests, headers): (Vec<MergeRequest>, _) = self.client.get(&path).await?;
+
+            pulls.extend(
+                merge_requests
+                    .into_iter()
+                    .map(|mr| mr.into_pull_request(owner, name))
+                    .filter(|pr| pr.state != PullRequestState::Open)
+                    .filter(|pr| pr.closed_at.is_some_and(|at| at >= since)),
+            );
+            page = headers
+                .get("x-next-page")
+                .and_then(|v| v.to_str().ok())
+                .filter(|v| !v.is_empty())
+                .map(str::to_string);
+        }
+        pulls.truncate(max);
+
+        Ok(pulls)
+    }
+
+    async fn assigned_issues(
+        &self,
+        owner: &str,
+        name: &str,
+        login: &str,
+    ) -> Result<Vec<Issue>, Error> {
+        let (issues, _): (Vec<GitLabIssue>, _) = self
+            .client
+            .get(&format!(
+                "{}/issues?state=opened&assignee_username={}&with_labels_details=true&per_page={}",
+                project_path(owner, name),
+                login,
+                MAX_PAGE_SIZE
+            ))
+            .await?;
+
+        Ok(issues
+            .into_iter()
+            .map(|issue| Issue {
+                number: issue.iid,
+                title: issue.title,
+                url: issue.web_url,
+                owner: owner.to_string(),
+                repo: name.to_string(),
+                body: issue.description.unwrap_or_default(),
+                author: issue.author.map(Into::into),
+                labels: issue.labels.into_iter().map(Into::into).collect(),
+                assignees: issue.assignees.into_iter().map(|a| a.username).collect(),
+                milestone: issue.milestone.map(|m| m.title),
+                created_at: issue.created_at,
+                updated_at: issue.updated_at,
+            })
+            .collect())
+    }
+
+    /// Gitlab has no reviews as such, approvals and comments are reported instead
+    async fn reviews(&self, owner: &str, name: &str, number: u64) -> Result<Vec<Review>, Error> {
+        let merge_request_path = format!("{}/merge_requests/{}", project_path(owner, name), number);
+        let approvals_path = format!("{}/approvals", merge_request_path);
+        let notes_path = format!(
+            "{}/notes?sort=asc&per_page={}",
+            merge_request_path, MAX_PAGE_SIZE
+        );
+        let ((approvals, _), (notes, _)): ((Approvals, _), (Vec<Note>, _)) = tokio::try_join!(
+            self.client.get(&approvals_path),
+            self.client.get(&notes_path)
+        )?;
+
+        let approvals = approvals.approved_by.into_iter().map(|a| Review {
+            author: a.user.username,
+            state: ReviewState::Approved,
+            commit: None,
+            body: String::new(),
+            submitted_at: None,
+        });
+        let comments = notes.into_iter().filter(|n| !n.system).map(|n| Review {
+            author: n.author.username,
+            state: ReviewState::Commented,
+            commit: None,
+            body: n.body,
+            submitted_at: n.created_at,
+        });
+
+        Ok(comments.chain(approvals).collect())
+    }
+
+    async fn timeline(
+        &self,
+        owner: &str,
+        name: &str,
+        number: u64,
+    ) -> Result<Vec<TimelineItem>, Error> {
+        // Latest first, so that the entries cut are the oldest ones
+        let mut notes: Vec<Note> = vec![];
+        let mut page = Some("1".to_string());
+        while let Some(current) = page.take()
+            && notes.len() < MAX_TIMELINE_ITEMS
+        {
+            let notes_path = format!(
+                "{}/merge_requests/{}/notes?sort=desc&order_by=created_at&per_page={}&page={}",
+                project_path(owner, name),
+                number,
+                MAX_PAGE_SIZE,
+                current
+            );
+            let (page_notes, headers): (Vec<Note>, _) = self.client.get(&notes_path).await?;
+
+            notes.extend(page_notes);
+            page = headers
+                .get("x-next-page")
+                .and_then(|v| v.to_str().ok())
+                .filter(|v| !v.is_empty())
+                .map(str::to_string);
+        }
+        notes.truncate(MAX_TIMELINE_ITEMS);
+
+        Ok(notes
+            .into_iter()
+            .rev()
+            .map(|note| TimelineItem {
+                author: Some(note.author.username),
+                created_at: note.created_at,
+                event: if note.system {
+                    system_event(&note.body)
+                } else {
+                    TimelineEvent::Comment { body: note.body }
+                },
+            })
+            .collect())
+    }
+
+    async fn overview(&self, owner: &str, name: &str) -> Result<RepositoryOverview, Error> {
+        let project_path = project_path(owner, name);
+        let (project, _): (Project, _) = self.client.get(&project_path).await?;
+
+        // Only the headers of the merge requests are needed, they carry the total
+        let merge_requests_route =
+            format!("{}/merge_requests?state=opened&per_page=1", project_path);
+        let releases_route = format!("{}/releases?per_page=1", project_path);
+        let (merge_requests, releases, pipelines) = tokio::join!(
+            self.client
+                .get::<Vec<serde_json::Value>>(&merge_requests_route),
+            self.client.get::<Vec<GitLabRelease>>(&releases_route),
+            async {
+                match &project.default_branch {
+                    Some(branch) => self
+                        .client
+                        .get::<Vec<Pipeline>>(&format!(
+                            "{}/pipelines?ref={}&per_page=1",
+                            project_path,
+                            branch.replace('/', "%2F")
+                        ))
+                        .await
+                        .map(|(pipelines, _)| pipelines),
+                    None => Ok(vec![]),
+                }
+            },
+        );
+
+        Ok(RepositoryOverview {
+            description: project.description.filter(|d| !d.is_empty()),
+            url: project.web_url,
+            default_branch: project.default_branch,
+            open_pull_requests: merge_requests
+                .ok()
+                .and_then(|(_, headers)| headers.get("x-total")?.to_str().ok()?.parse().ok()),
+            open_issues: project.open_issues_count,
+            latest_release: releases
+                .ok()
+                .and_then(|(releases, _)| releases.into_iter().next())
+                .map(|release| Release {
+                    name: release.name.unwrap_or_else(|| release.tag_name.clone()),
+                    tag: release.tag_name,
+                    published_at: release.released_at,
+                }),
+            default_branch_checks: pipelines
+                .ok()
+                .and_then(|pipelines| pipelines.into_iter().next())
+                .map(|pipeline| match pipeline.status.as_str() {
+                    "success" | "skipped" | "manual" => CheckState::Success,
+                    "failed" | "canceled" => CheckState::Failure,
+                    _ => CheckState::Pending,
+                }),
+        })
+    }
+
+    async fn details(&self, owner: &str, name: &str, number: u64) -> Result<MergeInfo, Error> {
+        let (details, _): (MergeRequestDetails, _) = self
+            .client
+            .get(&format!(
+                "{}/merge_requests/{}",
+                project_path(owner, name),
+                number
+            ))
+            .await?;
+
+        let mergeable = match details.merge_status.as_deref() {
+            Some("can_be_merged") => Some(true),
+            Some("cannot_be_merged") => Some(false),
+            _ => None,
+        };
+
+        Ok(MergeInfo {
+            mergeable,
+            // Gitlab can rebase as long as there are no conflicts
+            rebaseable: mergeable.map(|_| !details.has_conflicts),
+            mergeable_state: details.detailed_merge_status,
+        })
+    }
+
+    async fn checks(&self, owner: &str, name: &str, sha: &str) -> Result<Vec<Check>, Error> {
+        let (statuses, _): (Vec<CommitStatus>, _) = self
+            .client
+            .get(&format!(
+                "{}/repository/commits/{}/statuses?per_page={}",
+                project_path(owner, name),
+                sha,
+                MAX_PAGE_SIZE
+            ))
+            .await?;
+
+        Ok(statuses
+            .into_iter()
+            .map(|status| Check {
+                state: match status.status.as_str() {
+                    "success" | "skipped" | "manual" => CheckState::Success,
+                    "failed" | "canceled" => CheckState::Failure,
+                    _ => CheckState::Pending,
+                },
+                name: status.name,
+                conclusion: status.status,
+                url: status.target_url,
+            })
+            .collect())
+    }
+
+    /// Review requests can't target groups in gitlab
+    async fn teams(&self) -> Result<Vec<String>, Error> {
+        Ok(vec![])
+    }
+}
+
+impl MergeRequest {
+    fn into_pull_request(self, owner: &str, name: &str) -> PullRequest {
+        PullRequest {
+            number: self.iid,
+            title: self.title,
+            url: self.web_url,
+            owner: owner.to_string(),
+            repo: name.to_string(),
+            body: self.description.unwrap_or_default(),
+            is_draft: self.draft || self.work_in_progress,
+            state: match self.state.as_str() {
+                "merged" => PullRequestState::Merged,
+                "closed" => PullRequestState::Closed,
+                _ => PullRequestState::Open,
+            },
+            closed_at: self.merged_at.or(self.closed_at),
+            merged_by: self.merge_user.map(|u| u.username),
+            head_sha: self.sha.unwrap_or_default(),
+            head_branch: self.source_branch,
+            base_branch: self.target_branch,
+            is_fork: self.source_project_id != self.target_project_id,
+            labels: self.labels.into_iter().map(Into::into).collect(),
+            milestone: self.milestone.map(|m| m.title),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            // The size of the changes is only part of the single merge request
+            additions: None,
+            deletions: None,
+            changed_files: None,
+            author: self.author.map(Into::into),
+            // The pipeline status is not part of the list of merge requests
+            checks: None,
+            assignees: self.assignees.into_iter().map(|a| a.username).collect(),
+            requested_reviewers: self.reviewers.into_iter().map(|r| r.username).collect(),
+            requested_teams: vec![],
+            latest_reviews: vec![],
+        }
+    }
+}
+
+impl From<GitLabLabel> for Label {
+    fn from(label: GitLabLabel) -> Self {
+        Self {
+            name: label.name,
+            color: label.color.trim_start_matches('#').to_string(),
+        }
+    }
+}
+
+impl From<GitLabUser> for User {
+    fn from(user: GitLabUser) -> Self {
+        Self {
+            login: user.username,
+            id: Some(user.id.to_string()),
+            name: user.name,
+            email: user.public_email,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::*;
+    use crate::forge::stub::{Response, StubServer};
+
+    fn merge_request(iid: u64) -> Value {
+        json!({
+            "iid": iid,
+            "title": format!("Merge request {}", iid),
+            "web_url": format!("https://gitlab.example.com/octo/app/-/merge_requests/{}", iid),
+            "state": "opened",
+            "source_branch": "feature",
+            "target_branch": "main",
+            "source_project_id": 1,
+            "target_project_id": 1,
+            "author": { "id": 1, "username": "alice", "name": "Alice" },
+        })
+    }
+
+    #[tokio::test]
+    async fn merge_requests_are_mapped_to_pull_requests() {
+        let mut draft = merge_request(2);
+        draft["draft"] = json!(true);
+        draft["source_project_id"] = json!(2);
+        draft["reviewers"] = json!([{ "id": 2, "username": "me" }]);
+        draft["assignees"] = json!([{ "id": 3, "username": "bob" }]);
+        draft["labels"] = json!([{ "name": "bug", "color": "#d73a4a" }]);
+        let server =
+            StubServer::start(move |_| Response::json(json!([merge_request(1), draft]))).await;
+        let gitlab = GitLab::new(Some(&server.url), None);
+
+        let page = gitlab.pull_requests("octo", "app", 10, None).await.unwrap();
+
+        assert_eq!(page.next, None);
+        assert_eq!(page.items.len(), 2);
+        assert!(!page.items[0].is_draft);
+        assert!(page.items[0].is_authored_by("alice"));
+        let pr = &page.items[1];
+        assert!(pr.is_draft);
+        assert!(pr.is_fork);
+        assert!(pr.is_review_requested("me"));
+        assert!(pr.is_assigned_to("bob"));
+        assert_eq!(pr.labels[0].color, "d73a4a");
+        assert_eq!(
+            server.requests(),
+            [
+                "/api/v4/projects/octo%2Fapp/merge_requests?state=opened&order_by=created_at&sort=desc&with_labels_details=true&per_page=100&page=1"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn pages_keep_the_same_size() {
+        let server = StubServer::start(|path| {
+            let page = if path.ends_with("&page=1") { 0 } else { 1 };
+            let merge_requests: Vec<Value> = (1..=MAX_PAGE_SIZE as u64)
+                .map(|i| merge_request(page * MAX_PAGE_SIZE as u64 + i))
+                .collect();
+            Response::json(json!(merge_requests)).header("x-next-page", ["2", "3"][page as usize])
+        })
+        .await;
+        let gitlab = GitLab::new(Some(&server.url), None);
+
+        let first = gitlab
+            .pull_requests("octo", "app", 150, None)
+            .await
+            .unwrap();
+        let second = gitlab
+            .pull_requests("octo", "app", 50, first.next.clone())
+            .await
+            .unwrap();
+
+        assert_eq!(first.items.len(), 100);
+        assert_eq!(first.next.as_deref(), Some("2"));
+        // The second page follows the first, only cut to what was asked
+        assert_eq!(second.items.len(), 50);
+        assert_eq!(second.items[0].number, 101);
+        assert!(
+            server
+                .requests()
+                .iter()
+                .all(|path| path.contains("per_page=100&"))
+        );
+    }
+
+    #[tokio::test]
+    async fn timeline_keeps_the_latest_notes() {
+        let server = StubServer::start(|path| {
+            let note = |n: u64| {
+                json!({
+                    "author": { "id": 1, "username": "alice" },
+                    "body": format!("Note {}", n),
+                    "created_at": "2025-01-01T00:00:00Z",
+                })
+            };
+            // Latest first, 700 notes in all
+            let page: u64 = path.rsplit_once("&page=").unwrap().1.parse().unwrap();
+            let notes: Vec<Value> = (0..MAX_PAGE_SIZE as u64)
+                .map(|i| note(700 - (page - 1) * MAX_PAGE_SIZE as u64 - i))
+                .collect();
+            Response::json(json!(notes)).header("x-next-page", &(page + 1).to_string())
+        })
+        .await;
+        let gitlab = GitLab::new(Some(&server.url), None);
+
+        let timeline = gitlab.timeline("octo", "app", 1).await.unwrap();
+
+        assert_eq!(timeline.len(), MAX_TIMELINE_ITEMS);
+        let body = |item: &TimelineItem| match &item.event {
+            TimelineEvent::Comment { body } => body.clone(),
+            event => panic!("unexpected {:?}", event),
+        };
+        // Oldest first
+        assert_eq!(body(&timeline[0]), "Note 201");
+        assert_eq!(body(timeline.last().unwrap()), "Note 700");
+        assert_eq!(server.requests().len(), 5);
+        assert!(server.requests()[0].contains("sort=desc"));
+    }
+
+    #[tokio::test]
+    async fn errors_keep_the_status() {
+        let server = StubServer::start(|_| Response {
+            status: 404,
+            headers: vec![],
+            body: json!({ "message": "404 Project Not Found" }).to_string(),
+        })
+        .await;
+        let gitlab = GitLab::new(Some(&server.url), Some("token".to_string()));
+
+        let err = gitlab
+            .pull_requests("octo", "gone", 10, None)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            Error::Status { status: 404, message } if message == "404 Project Not Found"
+        ));
+    }
+}
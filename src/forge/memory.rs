@@ -0,0 +1,481 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use super::{
+    Check, Error, Forge, Involved, Issue, MergeInfo, Notification, NotificationAction, Page,
+    PullRequest, PullRequestState, RepositoryOverview, Review, TimelineItem,
+};
+
+// Like on the forges, a page holds at most this many pull requests
+const MAX_PAGE_SIZE: usize = 100;
+
+/// In-memory forge, filled up front, to exercise the tui without a real forge behind it
+#[derive(Debug, Default)]
+pub struct MemoryForge {
+    data: Mutex<Data>,
+}
+
+#[derive(Debug, Default)]
+struct Data {
+    // Pull requests by `(owner, name)`, whatever their state
+    pulls: HashMap<(String, String), Vec<PullRequest>>,
+    // Issues by `(owner, name)`
+    issues: HashMap<(String, String), Vec<Issue>>,
+    overviews: HashMap<(String, String), RepositoryOverview>,
+    // Reviews and merge information by `(owner, name, number)`
+    reviews: HashMap<(String, String, u64), Vec<Review>>,
+    timelines: HashMap<(String, String, u64), Vec<TimelineItem>>,
+    details: HashMap<(String, String, u64), MergeInfo>,
+    // Checks by commit sha
+    checks: HashMap<String, Vec<Check>>,
+    teams: Vec<String>,
+    // Pull requests involving each login
+    involved: HashMap<String, Vec<Involved>>,
+    notifications: Vec<Notification>,
+    // Errors returned instead of the pull requests of a repository
+    errors: HashMap<(String, String), Error>,
+    // Errors returned instead of the closed pull requests, or the issues, only
+    closed_errors: HashMap<(String, String), Error>,
+    issue_errors: HashMap<(String, String), Error>,
+    teams_error: Option<Error>,
+}
+
+fn repo_key(owner: &str, name: &str) -> (String, String) {
+    (owner.to_string(), name.to_string())
+}
+
+fn pr_key(owner: &str, name: &str, number: u64) -> (String, String, u64) {
+    (owner.to_string(), name.to_string(), number)
+}
+
+fn not_found(what: &str) -> Error {
+    Error::Status {
+        status: 404,
+        message: format!("{} not found", what),
+    }
+}
+
+impl MemoryForge {
+    /// Adds the pull request to the repository it belongs to
+    pub fn add_pull_request(&self, pr: PullRequest) {
+        self.data
+            .lock()
+            .unwrap()
+            .pulls
+            .entry(repo_key(&pr.owner, &pr.repo))
+            .or_default()
+            .push(pr);
+    }
+
+    /// Adds the issue to the repository it belongs to
+    pub fn add_issue(&self, issue: Issue) {
+        self.data
+            .lock()
+            .unwrap()
+            .issues
+            .entry(repo_key(&issue.owner, &issue.repo))
+            .or_default()
+            .push(issue);
+    }
+
+    pub fn add_review(&self, owner: &str, name: &str, number: u64, review: Review) {
+        self.data
+            .lock()
+            .unwrap()
+            .reviews
+            .entry(pr_key(owner, name, number))
+            .or_default()
+            .push(review);
+    }
+
+    pub fn add_timeline_item(&self, owner: &str, name: &str, number: u64, item: TimelineItem) {
+        self.data
+            .lock()
+            .unwrap()
+            .timelines
+            .entry(pr_key(owner, name, number))
+            .or_default()
+            .push(item);
+    }
+
+    pub fn set_overview(&self, owner: &str, name: &str, overview: RepositoryOverview) {
+        self.data
+            .lock()
+            .unwrap()
+            .overviews
+            .insert(repo_key(owner, name), overview);
+    }
+
+    pub fn set_details(&self, owner: &str, name: &str, number: u64, details: MergeInfo) {
+        self.data
+            .lock()
+            .unwrap()
+            .details
+            .insert(pr_key(owner, name, number), details);
+    }
+
+    pub fn set_checks(&self, sha: &str, checks: Vec<Check>) {
+        self.data
+            .lock()
+            .unwrap()
+            .checks
+            .insert(sha.to_string(), checks);
+    }
+
+    pub fn set_teams(&self, teams: Vec<String>) {
+        self.data.lock().unwrap().teams = teams;
+    }
+
+    /// Makes the pull request show up in the search of the prs involving the user
+    pub fn add_involved(&self, login: &str, involved: Involved) {
+        self.data
+            .lock()
+            .unwrap()
+            .involved
+            .entry(login.to_string())
+            .or_default()
+            .push(involved);
+    }
+
+    pub fn add_notification(&self, notification: Notification) {
+        self.data.lock().unwrap().notifications.push(notification);
+    }
+
+    /// Notifications left in the inbox, along with their read state
+    pub fn inbox(&self) -> Vec<Notification> {
+        self.data.lock().unwrap().notifications.clone()
+    }
+
+    /// Makes every fetch of the pull requests of the repository fail with the given error
+    pub fn fail_repository(&self, owner: &str, name: &str, err: Error) {
+        self.data
+            .lock()
+            .unwrap()
+            .errors
+            .insert(repo_key(owner, name), err);
+    }
+
+    /// Makes the fetches of the closed pull requests of the repository fail with the given error
+    pub fn fail_closed_pull_requests(&self, owner: &str, name: &str, err: Error) {
+        self.data
+            .lock()
+            .unwrap()
+            .closed_errors
+            .insert(repo_key(owner, name), err);
+    }
+
+    /// Makes the fetches of the assigned issues of the repository fail with the given error
+    pub fn fail_assigned_issues(&self, owner: &str, name: &str, err: Error) {
+        self.data
+            .lock()
+            .unwrap()
+            .issue_errors
+            .insert(repo_key(owner, name), err);
+    }
+
+    /// Makes the listing of the teams fail with the given error
+    pub fn fail_teams(&self, err: Error) {
+        self.data.lock().unwrap().teams_error = Some(err);
+    }
+}
+
+#[async_trait]
+impl Forge for MemoryForge {
+    async fn pull_requests(
+        &self,
+        owner: &str,
+        name: &str,
+        first: usize,
+        after: Option<String>,
+    ) -> Result<Page<PullRequest>, Error> {
+        let data = self.data.lock().unwrap();
+        let key = repo_key(owner, name);
+        if let Some(err) = data.errors.get(&key) {
+            return Err(err.clone());
+        }
+
+        // The cursor is the index of the first pr of the page
+        let pulls: Vec<&PullRequest> = data
+            .pulls
+            .get(&key)
+            .into_iter()
+            .flatten()
+            .filter(|pr| pr.state == PullRequestState::Open)
+            .collect();
+        let start = after
+            .and_then(|a| a.parse().ok())
+            .unwrap_or(0)
+            .min(pulls.len());
+        let end = (start + first.min(MAX_PAGE_SIZE)).min(pulls.len());
+
+        Ok(Page {
+            items: pulls[start..end].iter().map(|pr| (*pr).clone()).collect(),
+            next: (end < pulls.len()).then(|| end.to_string()),
+        })
+    }
+
+    async fn closed_pull_requests(
+        &self,
+        owner: &str,
+        name: &str,
+        since: DateTime<Utc>,
+        max: usize,
+    ) -> Result<Vec<PullRequest>, Error> {
+        let data = self.data.lock().unwrap();
+        let key = repo_key(owner, name);
+        if let Some(err) = data.errors.get(&key).or(data.closed_errors.get(&key)) {
+            return Err(err.clone());
+        }
+
+        let mut closed: Vec<PullRequest> = data
+            .pulls
+            .get(&key)
+            .into_iter()
+            .flatten()
+            .filter(|pr| pr.state != PullRequestState::Open)
+            .filter(|pr| pr.closed_at.is_some_and(|at| at >= since))
+            .cloned()
+            .collect();
+        closed.sort_by_key(|pr| std::cmp::Reverse(pr.closed_at));
+        closed.truncate(max);
+
+        Ok(closed)
+    }
+
+    async fn assigned_issues(
+        &self,
+        owner: &str,
+        name: &str,
+        login: &str,
+    ) -> Result<Vec<Issue>, Error> {
+        let data = self.data.lock().unwrap();
+        let key = repo_key(owner, name);
+        if let Some(err) = data.issue_errors.get(&key) {
+            return Err(err.clone());
+        }
+
+        Ok(data
+            .issues
+            .get(&key)
+            .into_iter()
+            .flatten()
+            .filter(|issue| issue.assignees.iter().any(|a| a == login))
+            .cloned()
+            .collect())
+    }
+
+    async fn reviews(&self, owner: &str, name: &str, number: u64) -> Result<Vec<Review>, Error> {
+        Ok(self
+            .data
+            .lock()
+            .unwrap()
+            .reviews
+            .get(&pr_key(owner, name, number))
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    async fn timeline(
+        &self,
+        owner: &str,
+        name: &str,
+        number: u64,
+    ) -> Result<Vec<TimelineItem>, Error> {
+        Ok(self
+            .data
+            .lock()
+            .unwrap()
+            .timelines
+            .get(&pr_key(owner, name, number))
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    async fn overview(&self, owner: &str, name: &str) -> Result<RepositoryOverview, Error> {
+        self.data
+            .lock()
+            .unwrap()
+            .overviews
+            .get(&repo_key(owner, name))
+            .cloned()
+            .ok_or_else(|| not_found(&format!("{}/{}", owner, name)))
+    }
+
+    async fn details(&self, owner: &str, name: &str, number: u64) -> Result<MergeInfo, Error> {
+        self.data
+            .lock()
+            .unwrap()
+            .details
+            .get(&pr_key(owner, name, number))
+            .cloned()
+            .ok_or_else(|| not_found(&format!("{}/{}#{}", owner, name, number)))
+    }
+
+    async fn checks(&self, _owner: &str, _name: &str, sha: &str) -> Result<Vec<Check>, Error> {
+        Ok(self
+            .data
+            .lock()
+            .unwrap()
+            .checks
+            .get(sha)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    async fn teams(&self) -> Result<Vec<String>, Error> {
+        let data = self.data.lock().unwrap();
+        match &data.teams_error {
+            Some(err) => Err(err.clone()),
+            None => Ok(data.teams.clone()),
+        }
+    }
+
+    async fn involved(
+        &self,
+        repositories: &[(String, String)],
+        login: &str,
+    ) -> Result<Vec<Involved>, Error> {
+        Ok(self
+            .data
+            .lock()
+            .unwrap()
+            .involved
+            .get(login)
+            .into_iter()
+            .flatten()
+            .filter(|i| {
+                let pr = &i.pull_request;
+                repositories.contains(&repo_key(&pr.owner, &pr.repo))
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn notifications(
+        &self,
+        repositories: &[(String, String)],
+    ) -> Result<Vec<Notification>, Error> {
+        Ok(self
+            .data
+            .lock()
+            .unwrap()
+            .notifications
+            .iter()
+            .filter(|n| repositories.contains(&repo_key(&n.owner, &n.repo)))
+            .cloned()
+            .collect())
+    }
+
+    async fn update_notification(
+        &self,
+        thread_id: &str,
+        action: NotificationAction,
+    ) -> Result<(), Error> {
+        let mut data = self.data.lock().unwrap();
+        let index = data
+            .notifications
+            .iter()
+            .position(|n| n.thread_id == thread_id)
+            .ok_or_else(|| not_found(thread_id))?;
+
+        match action {
+            NotificationAction::MarkRead => data.notifications[index].unread = false,
+            NotificationAction::MarkDone | NotificationAction::Unsubscribe => {
+                data.notifications.remove(index);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pull_request(number: u64, state: PullRequestState) -> PullRequest {
+        PullRequest {
+            number,
+            owner: "octo".to_string(),
+            repo: "app".to_string(),
+            state,
+            closed_at: (state != PullRequestState::Open).then(Utc::now),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn pull_requests_are_paginated() {
+        let forge = MemoryForge::default();
+        for number in 1..=3 {
+            forge.add_pull_request(pull_request(number, PullRequestState::Open));
+        }
+        forge.add_pull_request(pull_request(4, PullRequestState::Merged));
+
+        let first = forge.pull_requests("octo", "app", 2, None).await.unwrap();
+        let second = forge
+            .pull_requests("octo", "app", 2, first.next.clone())
+            .await
+            .unwrap();
+
+        let numbers = |page: &Page<PullRequest>| -> Vec<u64> {
+            page.items.iter().map(|pr| pr.number).collect()
+        };
+        assert_eq!(numbers(&first), [1, 2]);
+        assert_eq!(numbers(&second), [3]);
+        assert_eq!(second.next, None);
+    }
+
+    #[tokio::test]
+    async fn failing_repository_returns_the_error() {
+        let forge = MemoryForge::default();
+        forge.add_pull_request(pull_request(1, PullRequestState::Merged));
+        forge.fail_repository("octo", "app", not_found("octo/app"));
+
+        let since = Utc::now() - chrono::Duration::days(1);
+        assert!(forge.pull_requests("octo", "app", 10, None).await.is_err());
+        assert!(
+            forge
+                .closed_pull_requests("octo", "app", since, 10)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn notifications_are_marked_read_then_done() {
+        let forge = MemoryForge::default();
+        forge.add_notification(Notification {
+            thread_id: "1".to_string(),
+            owner: "octo".to_string(),
+            repo: "app".to_string(),
+            title: String::new(),
+            kind: "PullRequest".to_string(),
+            number: Some(1),
+            url: String::new(),
+            reason: "mention".to_string(),
+            unread: true,
+            updated_at: Utc::now(),
+        });
+
+        forge
+            .update_notification("1", NotificationAction::MarkRead)
+            .await
+            .unwrap();
+        assert!(!forge.inbox()[0].unread);
+
+        forge
+            .update_notification("1", NotificationAction::MarkDone)
+            .await
+            .unwrap();
+        assert!(forge.inbox().is_empty());
+        assert!(
+            forge
+                .update_notification("1", NotificationAction::MarkDone)
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use reqwest::{
+    RequestBuilder, Response,
+    header::{HeaderMap, HeaderName, HeaderValue},
+};
+use serde::{Deserialize, de::DeserializeOwned};
+
+use super::Error;
+
+/// Minimal json client shared by the forges that don't have a dedicated crate
+#[derive(Debug)]
+pub(super) struct RestClient {
+    http: reqwest::Client,
+    // e.g. `https://gitlab.com/api/v4`
+    api_url: String,
+    // Header carrying the token, e.g. `PRIVATE-TOKEN`
+    auth: Option<(HeaderName, HeaderValue)>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ErrorMessage {
+    // Either a string or an object with the invalid fields
+    message: Option<serde_json::Value>,
+    error: Option<String>,
+}
+
+impl RestClient {
+    /// Client of the api at the given path of the host, which may include the scheme (e.g.
+    /// `http://localhost:8080`) and defaults to https
+    pub fn new(host: &str, api_path: &str, auth: Option<(HeaderName, String)>) -> Self {
+        let host = host.trim_end_matches('/');
+        let base_url = if host.contains("://") {
+            host.to_string()
+        } else {
+            format!("https://{}", host)
+        };
+
+        Self {
+            http: reqwest::Client::new(),
+            api_url: format!("{}{}", base_url, api_path),
+            // A token that is not a valid header would be rejected anyway
+            auth: auth.and_then(|(name, value)| Some((name, HeaderValue::from_str(&value).ok()?))),
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.api_url, path)
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<Response, Error> {
+        let request = match &self.auth {
+            Some((name, value)) => request.header(name, value),
+            None => request,
+        };
+        let response = request
+            .send()
+            .await
+            .map_err(|e| Error::Other(e.to_string()))?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+
+        let message = response
+            .json::<ErrorMessage>()
+            .await
+            .ok()
+            .and_then(|e| match e.message {
+                Some(serde_json::Value::String(message)) => Some(message),
+                Some(message) => Some(message.to_string()),
+                None => e.error,
+            })
+            .unwrap_or_else(|| status.to_string());
+
+        Err(Error::Status {
+            status: status.as_u16(),
+            message,
+        })
+    }
+
+    /// Fetches the path of the api, along with the headers of the response for the pagination
+    pub async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<(T, HeaderMap), Error> {
+        let response = self.send(self.http.get(self.url(path))).await?;
+        let headers = response.headers().clone();
+        let value = response
+            .json()
+            .await
+            .map_err(|e| Error::Other(format!("Invalid response: {}", e)))?;
+
+        Ok((value, headers))
+    }
+}
@@ -0,0 +1,1041 @@
+mod client;
+
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use octocrab::Page;
+use serde::Deserialize;
+use serde_json::json;
+
+pub use client::{Client, RateLimit};
+
+// Single query returning the open prs of a repository together with the data needed to classify
+// them (assignees, review requests and latest reviews) and the author names, this way a refresh
+// is one request per page instead of a request per pr.
+const OPEN_PULL_REQUESTS_QUERY: &str = r#"
+query OpenPullRequests($owner: String!, $name: String!, $first: Int!, $after: String) {
+  repository(owner: $owner, name: $name) {
+    pullRequests(states: OPEN, first: $first, after: $after, orderBy: {field: CREATED_AT, direction: DESC}) {
+      pageInfo { hasNextPage endCursor }
+      nodes { ...PullRequestFields }
+    }
+  }
+}
+"#;
+
+// Same as the open prs, for the merged and closed ones, the latest updated first
+const CLOSED_PULL_REQUESTS_QUERY: &str = r#"
+query ClosedPullRequests($owner: String!, $name: String!, $first: Int!, $after: String) {
+  repository(owner: $owner, name: $name) {
+    pullRequests(states: [CLOSED, MERGED], first: $first, after: $after, orderBy: {field: UPDATED_AT, direction: DESC}) {
+      pageInfo { hasNextPage endCursor }
+      nodes { ...PullRequestFields }
+    }
+  }
+}
+"#;
+
+// Same as the open prs, for the prs matching a search query
+const SEARCH_PULL_REQUESTS_QUERY: &str = r#"
+query SearchPullRequests($query: String!, $first: Int!) {
+  search(query: $query, type: ISSUE, first: $first) {
+    pageInfo { hasNextPage endCursor }
+    nodes { ...PullRequestFields }
+  }
+}
+"#;
+
+// Summary of a repository: counts, latest release and the checks of its default branch
+const REPOSITORY_OVERVIEW_QUERY: &str = r#"
+query RepositoryOverview($owner: String!, $name: String!) {
+  repository(owner: $owner, name: $name) {
+    description
+    url
+    defaultBranchRef {
+      name
+      target { ... on Commit { statusCheckRollup { state } } }
+    }
+    pullRequests(states: OPEN) { totalCount }
+    issues(states: OPEN) { totalCount }
+    latestRelease { name tagName publishedAt }
+  }
+}
+"#;
+
+// Conversation of a pr: comments, reviews, pushes and the events worth following
+const TIMELINE_QUERY: &str = r#"
+query Timeline($owner: String!, $name: String!, $number: Int!, $last: Int!, $before: String) {
+  repository(owner: $owner, name: $name) {
+    pullRequest(number: $number) {
+      timelineItems(last: $last, before: $before, itemTypes: [ISSUE_COMMENT, PULL_REQUEST_REVIEW, PULL_REQUEST_COMMIT, LABELED_EVENT, UNLABELED_EVENT, HEAD_REF_FORCE_PUSHED_EVENT, REVIEW_REQUESTED_EVENT, READY_FOR_REVIEW_EVENT]) {
+        pageInfo { hasNextPage endCursor hasPreviousPage startCursor }
+        nodes {
+          __typename
+          ... on IssueComment { author { login } body createdAt }
+          ... on PullRequestReview { author { login } state body submittedAt }
+          ... on PullRequestCommit { commit { abbreviatedOid messageHeadline committedDate author { name user { login } } } }
+          ... on LabeledEvent { actor { login } label { name } createdAt }
+          ... on UnlabeledEvent { actor { login } label { name } createdAt }
+          ... on HeadRefForcePushedEvent { actor { login } beforeCommit { abbreviatedOid } afterCommit { abbreviatedOid } createdAt }
+          ... on ReviewRequestedEvent {
+            actor { login }
+            requestedReviewer {
+              ... on User { login }
+              ... on Team { slug organization { login } }
+            }
+            createdAt
+          }
+          ... on ReadyForReviewEvent { actor { login } createdAt }
+        }
+      }
+    }
+  }
+}
+"#;
+
+// Fields of a pr needed to build a [`PullRequest`], shared by the queries above
+const PULL_REQUEST_FIELDS: &str = r#"
+fragment PullRequestFields on PullRequest {
+  number
+  title
+  url
+  body
+  isDraft
+  state
+  closedAt
+  updatedAt
+  mergedBy { login }
+  headRefOid
+  headRefName
+  baseRefName
+  isCrossRepository
+  labels(first: 20) { nodes { name color } }
+  milestone { title }
+  createdAt
+  additions
+  deletions
+  changedFiles
+  commits(last: 1) { nodes { commit { statusCheckRollup { state } } } }
+  baseRepository { name owner { login } }
+  author { login ... on User { databaseId name email } }
+  assignees(first: 25) { nodes { login } }
+  reviewRequests(first: 25) {
+    nodes {
+      requestedReviewer {
+        ... on User { login }
+        ... on Team { slug organization { login } }
+      }
+    }
+  }
+  latestReviews(first: 50) { nodes { author { login } state commit { oid } body submittedAt } }
+}
+"#;
+
+// Maximum page size allowed by the github graphql api
+pub const MAX_PAGE_SIZE: usize = 100;
+// Notifications are listed at most 50 at a time
+const NOTIFICATIONS_PAGE_SIZE: usize = 50;
+// Only the latest workflow runs are worth listing
+const WORKFLOW_RUNS_PAGE_SIZE: usize = 30;
+// Longer conversations are cut, the latest entries are kept
+const MAX_TIMELINE_ITEMS: usize = 500;
+
+#[derive(Debug)]
+pub enum Error {
+    Api(octocrab::Error),
+    GraphQL(String),
+    // The repository or pr doesn't exist, or can't be seen with the token
+    NotFound(String),
+    Deserialize(serde_json::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Api(octocrab::Error::GitHub { source, .. }) => write!(f, "{}", source.message),
+            Error::Api(err) => write!(f, "{}", err),
+            Error::GraphQL(message) | Error::NotFound(message) => write!(f, "{}", message),
+            Error::Deserialize(err) => write!(f, "Invalid response from github: {}", err),
+        }
+    }
+}
+
+impl From<octocrab::Error> for Error {
+    fn from(err: octocrab::Error) -> Self {
+        Self::Api(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Deserialize(err)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphQLResponse<T> {
+    data: Option<T>,
+    errors: Option<Vec<GraphQLError>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphQLError {
+    message: String,
+    // e.g. `NOT_FOUND` or `FORBIDDEN`
+    #[serde(rename = "type")]
+    kind: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RepositoryData {
+    repository: Option<Repository>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RepositoryOverviewData {
+    repository: Option<RepositoryOverview>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TimelineData {
+    repository: Option<TimelineRepository>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TimelineRepository {
+    pull_request: Option<TimelinePullRequest>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TimelinePullRequest {
+    timeline_items: Connection<TimelineItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchData {
+    search: Connection<PullRequest>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Repository {
+    pull_requests: Connection<PullRequest>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Connection<T> {
+    pub page_info: PageInfo,
+    pub nodes: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Nodes<T> {
+    pub nodes: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageInfo {
+    pub has_next_page: bool,
+    pub end_cursor: Option<String>,
+    // Only asked for by the queries paging backwards
+    #[serde(default)]
+    pub has_previous_page: bool,
+    pub start_cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PullRequest {
+    pub number: u64,
+    pub title: String,
+    pub url: String,
+    pub body: String,
+    pub is_draft: bool,
+    pub state: PullRequestState,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+    pub merged_by: Option<Actor>,
+    pub head_ref_oid: String,
+    pub head_ref_name: String,
+    pub base_ref_name: String,
+    pub is_cross_repository: bool,
+    pub labels: Option<Nodes<Label>>,
+    pub milestone: Option<Milestone>,
+    pub created_at: DateTime<Utc>,
+    pub additions: u64,
+    pub deletions: u64,
+    pub changed_files: u64,
+    // Only the head commit is requested
+    pub commits: Nodes<PullRequestCommit>,
+    pub base_repository: Option<RepositoryName>,
+    pub author: Option<Actor>,
+    pub assignees: Nodes<Actor>,
+    pub review_requests: Nodes<ReviewRequest>,
+    pub latest_reviews: Nodes<Review>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Label {
+    pub name: String,
+    // Hex color, without the leading `#`
+    pub color: String,
+}
+
+/// An issue as returned by the rest api, which lists the pull requests as issues too
+#[derive(Debug, Deserialize)]
+pub struct Issue {
+    pub number: u64,
+    pub title: String,
+    pub html_url: String,
+    pub body: Option<String>,
+    pub user: Option<Actor>,
+    #[serde(default)]
+    pub labels: Vec<Label>,
+    #[serde(default)]
+    pub assignees: Vec<Actor>,
+    pub milestone: Option<Milestone>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    // Only set when the issue is a pull request
+    pub pull_request: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Milestone {
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PullRequestState {
+    Open,
+    Closed,
+    Merged,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RepositoryName {
+    pub name: String,
+    pub owner: Organization,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Actor {
+    pub login: String,
+    // Only available when the actor is an user
+    pub database_id: Option<u64>,
+    pub name: Option<String>,
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewRequest {
+    // Empty when the reviewer is neither an user nor a team
+    pub requested_reviewer: Option<RequestedReviewer>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RequestedReviewer {
+    // Set when the reviewer is an user
+    pub login: Option<String>,
+    // Set when the reviewer is a team
+    pub slug: Option<String>,
+    pub organization: Option<Organization>,
+}
+
+// Owners and organizations only expose their login
+#[derive(Debug, Deserialize)]
+pub struct Organization {
+    pub login: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct UserTeam {
+    slug: String,
+    organization: Organization,
+}
+
+impl RequestedReviewer {
+    /// Returns the team as `org/team` when the reviewer is a team
+    pub fn team(&self) -> Option<String> {
+        match (&self.organization, &self.slug) {
+            (Some(org), Some(slug)) => Some(format!("{}/{}", org.login, slug)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Review {
+    pub author: Option<Actor>,
+    pub state: ReviewState,
+    // The commit the review was made on
+    pub commit: Option<Commit>,
+    pub body: String,
+    pub submitted_at: Option<DateTime<Utc>>,
+}
+
+/// A review as returned by the rest api
+#[derive(Debug, Deserialize)]
+pub struct PullRequestReview {
+    pub user: Option<Actor>,
+    pub state: ReviewState,
+    pub commit_id: Option<String>,
+    pub body: Option<String>,
+    pub submitted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ReviewState {
+    Pending,
+    Commented,
+    Approved,
+    ChangesRequested,
+    Dismissed,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Commit {
+    pub oid: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PullRequestCommit {
+    pub commit: CommitStatusRollup,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitStatusRollup {
+    // Empty when the commit has no checks nor statuses
+    pub status_check_rollup: Option<StatusCheckRollup>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StatusCheckRollup {
+    pub state: StatusState,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum StatusState {
+    Expected,
+    Error,
+    Failure,
+    Pending,
+    Success,
+}
+
+#[derive(Debug, Deserialize)]
+struct CheckRuns {
+    total_count: usize,
+    check_runs: Vec<CheckRun>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CheckRun {
+    pub name: String,
+    // `queued`, `in_progress` or `completed`
+    pub status: String,
+    // Only set once completed
+    pub conclusion: Option<String>,
+    pub html_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CombinedStatus {
+    total_count: usize,
+    statuses: Vec<CommitStatus>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CommitStatus {
+    pub context: String,
+    // `error`, `failure`, `pending` or `success`
+    pub state: String,
+    pub target_url: Option<String>,
+}
+
+/// General information about a repository
+#[derive(Debug, Deserialize)]
+pub struct RepositoryDetails {
+    pub default_branch: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct WorkflowRuns {
+    workflow_runs: Vec<WorkflowRun>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WorkflowRun {
+    pub id: u64,
+    // Name of the workflow
+    pub name: Option<String>,
+    pub run_number: u64,
+    // e.g. `push`, `pull_request`, `schedule`
+    pub event: String,
+    // `queued`, `in_progress` or `completed`
+    pub status: Option<String>,
+    // Only set once completed, e.g. `success`, `failure`, `cancelled`
+    pub conclusion: Option<String>,
+    pub head_branch: Option<String>,
+    pub html_url: String,
+    pub run_started_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WorkflowJobs {
+    jobs: Vec<WorkflowJob>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WorkflowJob {
+    pub id: u64,
+    pub name: String,
+    pub status: String,
+    pub conclusion: Option<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub html_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepositoryOverview {
+    pub description: Option<String>,
+    pub url: String,
+    // Missing for empty repositories
+    pub default_branch_ref: Option<BranchRef>,
+    pub pull_requests: TotalCount,
+    pub issues: TotalCount,
+    pub latest_release: Option<Release>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BranchRef {
+    pub name: String,
+    pub target: Option<BranchTarget>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BranchTarget {
+    pub status_check_rollup: Option<StatusCheckRollup>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TotalCount {
+    pub total_count: u64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Release {
+    pub name: Option<String>,
+    pub tag_name: String,
+    pub published_at: Option<DateTime<Utc>>,
+}
+
+/// An entry of the timeline of a pr, only the requested item types are expected
+#[derive(Debug, Deserialize)]
+#[serde(tag = "__typename", rename_all_fields = "camelCase")]
+pub enum TimelineItem {
+    IssueComment {
+        author: Option<Actor>,
+        body: String,
+        created_at: DateTime<Utc>,
+    },
+    PullRequestReview {
+        author: Option<Actor>,
+        state: ReviewState,
+        body: String,
+        submitted_at: Option<DateTime<Utc>>,
+    },
+    PullRequestCommit {
+        commit: TimelineCommit,
+    },
+    LabeledEvent {
+        actor: Option<Actor>,
+        label: LabelName,
+        created_at: DateTime<Utc>,
+    },
+    UnlabeledEvent {
+        actor: Option<Actor>,
+        label: LabelName,
+        created_at: DateTime<Utc>,
+    },
+    HeadRefForcePushedEvent {
+        actor: Option<Actor>,
+        // Missing when the commit doesn't exist anymore
+        before_commit: Option<AbbreviatedCommit>,
+        after_commit: Option<AbbreviatedCommit>,
+        created_at: DateTime<Utc>,
+    },
+    ReviewRequestedEvent {
+        actor: Option<Actor>,
+        requested_reviewer: Option<RequestedReviewer>,
+        created_at: DateTime<Utc>,
+    },
+    ReadyForReviewEvent {
+        actor: Option<Actor>,
+        created_at: DateTime<Utc>,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineCommit {
+    pub abbreviated_oid: String,
+    pub message_headline: String,
+    pub committed_date: DateTime<Utc>,
+    pub author: Option<GitActor>,
+}
+
+/// The author of a commit, an user only when the email matches one
+#[derive(Debug, Deserialize)]
+pub struct GitActor {
+    pub name: Option<String>,
+    pub user: Option<Actor>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AbbreviatedCommit {
+    pub abbreviated_oid: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LabelName {
+    pub name: String,
+}
+
+/// A notification thread of the authenticated user
+#[derive(Debug, Deserialize)]
+pub struct Notification {
+    pub id: String,
+    pub unread: bool,
+    // e.g. `review_requested`, `mention`, `ci_activity`
+    pub reason: String,
+    pub updated_at: DateTime<Utc>,
+    pub subject: NotificationSubject,
+    pub repository: NotificationRepository,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NotificationSubject {
+    pub title: String,
+    // Api url of the pr or issue, missing for subjects such as check suites
+    pub url: Option<String>,
+    // e.g. `PullRequest`, `Issue`, `CheckSuite`, `Release`
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NotificationRepository {
+    pub name: String,
+    pub owner: Organization,
+    pub html_url: String,
+}
+
+/// Mergeability of a pull request, only computed by the single pull request endpoint
+#[derive(Debug, Deserialize)]
+pub struct PullRequestDetails {
+    // `None` while github is still computing it
+    pub mergeable: Option<bool>,
+    pub rebaseable: Option<bool>,
+    pub mergeable_state: Option<String>,
+}
+
+impl Client {
+    /// Fetches a page of open pull requests of the given repository
+    pub async fn open_pull_requests(
+        &self,
+        owner: &str,
+        name: &str,
+        first: usize,
+        after: Option<String>,
+    ) -> Result<Connection<PullRequest>, Error> {
+        self.repository_pull_requests(OPEN_PULL_REQUESTS_QUERY, owner, name, first, after)
+            .await
+    }
+
+    /// Fetches a page of merged and closed pull requests of the given repository, the latest
+    /// updated first
+    pub async fn closed_pull_requests(
+        &self,
+        owner: &str,
+        name: &str,
+        first: usize,
+        after: Option<String>,
+    ) -> Result<Connection<PullRequest>, Error> {
+        self.repository_pull_requests(CLOSED_PULL_REQUESTS_QUERY, owner, name, first, after)
+            .await
+    }
+
+    async fn repository_pull_requests(
+        &self,
+        query: &str,
+        owner: &str,
+        name: &str,
+        first: usize,
+        after: Option<String>,
+    ) -> Result<Connection<PullRequest>, Error> {
+        let payload = json!({
+            "query": format!("{}{}", query, PULL_REQUEST_FIELDS),
+            "variables": {
+                "owner": owner,
+                "name": name,
+                "first": first.min(MAX_PAGE_SIZE),
+                "after": after,
+            },
+        });
+
+        let data: RepositoryData = self.query(&payload).await?;
+
+        data.repository
+            .map(|r| r.pull_requests)
+            .ok_or_else(|| Error::NotFound(format!("Could not resolve repository {owner}/{name}")))
+    }
+
+    /// Searches the pull requests matching the query, only the first page of results is returned
+    pub async fn search_pull_requests(
+        &self,
+        query: &str,
+        first: usize,
+    ) -> Result<Vec<PullRequest>, Error> {
+        let payload = json!({
+            "query": format!("{}{}", SEARCH_PULL_REQUESTS_QUERY, PULL_REQUEST_FIELDS),
+            "variables": {
+                "query": query,
+                "first": first.min(MAX_PAGE_SIZE),
+            },
+        });
+
+        let data: SearchData = self.query(&payload).await?;
+
+        Ok(data.search.nodes)
+    }
+
+    /// Fetches the details of a pull request
+    pub async fn pull_request_details(
+        &self,
+        owner: &str,
+        name: &str,
+        number: u64,
+    ) -> Result<PullRequestDetails, Error> {
+        self.get(&format!("/repos/{owner}/{name}/pulls/{number}"))
+            .await
+    }
+
+    /// Fetches the check runs of the given commit
+    pub async fn check_runs(
+        &self,
+        owner: &str,
+        name: &str,
+        sha: &str,
+    ) -> Result<Vec<CheckRun>, Error> {
+        let mut runs = vec![];
+        for page in 1.. {
+            let items: CheckRuns = self
+                .get(&format!(
+                    "/repos/{owner}/{name}/commits/{sha}/check-runs?per_page={MAX_PAGE_SIZE}&page={page}"
+                ))
+                .await?;
+
+            let last = items.check_runs.len() < MAX_PAGE_SIZE;
+            runs.extend(items.check_runs);
+            if last || runs.len() >= items.total_count {
+                break;
+            }
+        }
+
+        Ok(runs)
+    }
+
+    /// Fetches the commit statuses (the ones not reported as check runs) of the given commit
+    pub async fn commit_statuses(
+        &self,
+        owner: &str,
+        name: &str,
+        sha: &str,
+    ) -> Result<Vec<CommitStatus>, Error> {
+        let mut statuses = vec![];
+        for page in 1.. {
+            let combined: CombinedStatus = self
+                .get(&format!(
+                    "/repos/{owner}/{name}/commits/{sha}/status?per_page={MAX_PAGE_SIZE}&page={page}"
+                ))
+                .await?;
+
+            let last = combined.statuses.len() < MAX_PAGE_SIZE;
+            statuses.extend(combined.statuses);
+            if last || statuses.len() >= combined.total_count {
+                break;
+            }
+        }
+
+        Ok(statuses)
+    }
+
+    /// Fetches every review submitted on the pull request
+    pub async fn reviews(
+        &self,
+        owner: &str,
+        name: &str,
+        number: u64,
+    ) -> Result<Vec<PullRequestReview>, Error> {
+        self.get(&format!(
+            "/repos/{owner}/{name}/pulls/{number}/reviews?per_page={MAX_PAGE_SIZE}"
+        ))
+        .await
+    }
+
+    /// Fetches the open issues of the repository assigned to the user, leaving out the pull
+    /// requests
+    pub async fn assigned_issues(
+        &self,
+        owner: &str,
+        name: &str,
+        login: &str,
+    ) -> Result<Vec<Issue>, Error> {
+        // The pages are numbered, the last one is the first not to be full. Prs are only filtered
+        // out afterwards so that a page full of them doesn't look like the last one
+        let mut issues = vec![];
+        for page in 1.. {
+            let items: Vec<Issue> = self
+                .get(&format!(
+                    "/repos/{owner}/{name}/issues?state=open&assignee={login}&per_page={MAX_PAGE_SIZE}&page={page}"
+                ))
+                .await?;
+
+            let last = items.len() < MAX_PAGE_SIZE;
+            issues.extend(items.into_iter().filter(|i| i.pull_request.is_none()));
+            if last {
+                break;
+            }
+        }
+
+        Ok(issues)
+    }
+
+    /// Fetches the summary of the repository shown in its overview
+    pub async fn repository_overview(
+        &self,
+        owner: &str,
+        name: &str,
+    ) -> Result<RepositoryOverview, Error> {
+        let payload = json!({
+            "query": REPOSITORY_OVERVIEW_QUERY,
+            "variables": { "owner": owner, "name": name },
+        });
+
+        let data: RepositoryOverviewData = self.query(&payload).await?;
+        data.repository.ok_or_else(|| {
+            Error::NotFound(format!(
+                "Could not resolve to a repository {}/{}",
+                owner, name
+            ))
+        })
+    }
+
+    /// Fetches the timeline of the pr, oldest first. The pages are followed backwards from the
+    /// latest entry, up to [`MAX_TIMELINE_ITEMS`]
+    pub async fn timeline(
+        &self,
+        owner: &str,
+        name: &str,
+        number: u64,
+    ) -> Result<Vec<TimelineItem>, Error> {
+        let mut items = vec![];
+        let mut before = None;
+        loop {
+            let payload = json!({
+                "query": TIMELINE_QUERY,
+                "variables": {
+                    "owner": owner,
+                    "name": name,
+                    "number": number,
+                    "last": MAX_PAGE_SIZE.min(MAX_TIMELINE_ITEMS - items.len()),
+                    "before": before,
+                },
+            });
+
+            let data: TimelineData = self.query(&payload).await?;
+            let connection = data
+                .repository
+                .and_then(|r| r.pull_request)
+                .map(|pr| pr.timeline_items)
+                .ok_or_else(|| {
+                    Error::NotFound(format!(
+                        "Could not resolve pull request {owner}/{name}#{number}"
+                    ))
+                })?;
+
+            // Every page is older than the ones already fetched
+            items.splice(0..0, connection.nodes);
+            if !connection.page_info.has_previous_page || items.len() >= MAX_TIMELINE_ITEMS {
+                break;
+            }
+            before = connection.page_info.start_cursor;
+        }
+
+        Ok(items)
+    }
+
+    pub async fn repository(&self, owner: &str, name: &str) -> Result<RepositoryDetails, Error> {
+        self.get(&format!("/repos/{owner}/{name}")).await
+    }
+
+    /// Fetches the latest workflow runs of the repository, narrowed by the given filter
+    /// parameter, e.g. `branch=main` or `head_sha=...`
+    pub async fn workflow_runs(
+        &self,
+        owner: &str,
+        name: &str,
+        filter: &str,
+    ) -> Result<Vec<WorkflowRun>, Error> {
+        let runs: WorkflowRuns = self
+            .get(&format!(
+                "/repos/{owner}/{name}/actions/runs?{filter}&per_page={WORKFLOW_RUNS_PAGE_SIZE}"
+            ))
+            .await?;
+
+        Ok(runs.workflow_runs)
+    }
+
+    pub async fn workflow_jobs(
+        &self,
+        owner: &str,
+        name: &str,
+        run_id: u64,
+    ) -> Result<Vec<WorkflowJob>, Error> {
+        let jobs: WorkflowJobs = self
+            .get(&format!(
+                "/repos/{owner}/{name}/actions/runs/{run_id}/jobs?per_page={MAX_PAGE_SIZE}"
+            ))
+            .await?;
+
+        Ok(jobs.jobs)
+    }
+
+    /// Fetches the plain text log of the job
+    pub async fn job_log(&self, owner: &str, name: &str, job_id: u64) -> Result<String, Error> {
+        self.get_text(&format!("/repos/{owner}/{name}/actions/jobs/{job_id}/logs"))
+            .await
+    }
+
+    pub async fn rerun_failed_jobs(
+        &self,
+        owner: &str,
+        name: &str,
+        run_id: u64,
+    ) -> Result<(), Error> {
+        self.post_empty(&format!(
+            "/repos/{owner}/{name}/actions/runs/{run_id}/rerun-failed-jobs"
+        ))
+        .await
+    }
+
+    pub async fn cancel_run(&self, owner: &str, name: &str, run_id: u64) -> Result<(), Error> {
+        self.post_empty(&format!(
+            "/repos/{owner}/{name}/actions/runs/{run_id}/cancel"
+        ))
+        .await
+    }
+
+    /// Fetches the latest notifications of the authenticated user in the repository, the read
+    /// ones included as long as they aren't marked as done
+    pub async fn notifications(&self, owner: &str, name: &str) -> Result<Vec<Notification>, Error> {
+        self.get(&notifications_route(owner, name)).await
+    }
+
+    pub async fn mark_notification_read(&self, thread_id: &str) -> Result<(), Error> {
+        self.patch(&format!("/notifications/threads/{thread_id}"))
+            .await?;
+        // The lists might not be reported as modified, don't let them show the thread as unread
+        self.invalidate_notifications();
+        Ok(())
+    }
+
+    /// Marks the notification as done, removing it from the inbox
+    pub async fn mark_notification_done(&self, thread_id: &str) -> Result<(), Error> {
+        self.delete(&format!("/notifications/threads/{thread_id}"))
+            .await?;
+        self.invalidate_notifications();
+        Ok(())
+    }
+
+    // The repository of the thread isn't known, every list of notifications is forgotten
+    fn invalidate_notifications(&self) {
+        self.invalidate_matching(|route| {
+            route.starts_with("/repos/") && route.contains("/notifications?")
+        });
+    }
+
+    /// Stops the notifications of the thread, until the user takes part in it again
+    pub async fn unsubscribe(&self, thread_id: &str) -> Result<(), Error> {
+        self.delete(&format!("/notifications/threads/{thread_id}/subscription"))
+            .await
+    }
+
+    /// Fetches the teams the authenticated user belongs to, as `org/team`
+    pub async fn user_teams(&self) -> Result<Vec<String>, Error> {
+        let _permit = self.acquire(client::CORE_RESOURCE).await;
+
+        let octocrab = self.octocrab();
+        let page: Page<UserTeam> = octocrab
+            .get("/user/teams", Some(&[("per_page", MAX_PAGE_SIZE)]))
+            .await?;
+
+        Ok(octocrab
+            .all_pages(page)
+            .await?
+            .into_iter()
+            .map(|t| format!("{}/{}", t.organization.login, t.slug))
+            .collect())
+    }
+
+    async fn query<T>(&self, payload: &serde_json::Value) -> Result<T, Error>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let response: GraphQLResponse<T> = self.graphql(payload).await?;
+
+        match (response.data, response.errors) {
+            // Partial responses are fine as long as there is data
+            (Some(data), _) => Ok(data),
+            (None, Some(errors)) => {
+                let not_found = errors
+                    .iter()
+                    .any(|e| e.kind.as_deref() == Some("NOT_FOUND"));
+                let message = errors
+                    .into_iter()
+                    .map(|e| e.message)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                Err(if not_found {
+                    Error::NotFound(message)
+                } else {
+                    Error::GraphQL(message)
+                })
+            }
+            (None, None) => Err(Error::GraphQL("Empty response from github".to_string())),
+        }
+    }
+}
+
+fn notifications_route(owner: &str, name: &str) -> String {
+    format!("/repos/{owner}/{name}/notifications?all=true&per_page={NOTIFICATIONS_PAGE_SIZE}")
+}
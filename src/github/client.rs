@@ -0,0 +1,456 @@
+use std::{
+    collections::HashMap,
+    sync::{Mutex, RwLock},
+    time::Duration,
+};
+
+use http::{HeaderMap, HeaderValue, StatusCode, header};
+use octocrab::Octocrab;
+use serde::de::DeserializeOwned;
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+use super::Error;
+
+// When the remaining quota drops to this value requests wait for the reset
+const RATE_LIMIT_RESERVE: u64 = 50;
+// Never wait longer than a rate limit window, in case the reset we know about is wrong
+const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(60 * 60);
+// The routes of every pr and commit ever selected add up, the least recently used are dropped
+const MAX_CACHED_RESPONSES: usize = 1000;
+
+pub(super) const CORE_RESOURCE: &str = "core";
+pub(super) const GRAPHQL_RESOURCE: &str = "graphql";
+
+/// Github client that bounds the number of concurrent requests, caches the responses to send
+/// conditional requests and backs off when the rate limit is about to be exhausted.
+#[derive(Debug)]
+pub struct Client {
+    octocrab: Octocrab,
+    semaphore: Semaphore,
+    // Last response of each endpoint, by route
+    cache: Mutex<ResponseCache>,
+    // Rate limits by resource (e.g. `core`, `graphql`)
+    rate_limits: RwLock<HashMap<String, RateLimit>>,
+}
+
+#[derive(Debug, Clone)]
+struct CachedResponse {
+    etag: Option<HeaderValue>,
+    last_modified: Option<HeaderValue>,
+    body: String,
+    // Value of the cache clock when the response was last used
+    last_used: u64,
+}
+
+/// Responses by route, bounded to [`MAX_CACHED_RESPONSES`]
+#[derive(Debug, Default)]
+struct ResponseCache {
+    responses: HashMap<String, CachedResponse>,
+    // Ticks on every use of the cache
+    clock: u64,
+}
+
+impl ResponseCache {
+    fn get(&mut self, route: &str) -> Option<CachedResponse> {
+        self.clock += 1;
+        let response = self.responses.get_mut(route)?;
+        response.last_used = self.clock;
+        Some(response.clone())
+    }
+
+    fn insert(&mut self, route: &str, mut response: CachedResponse) {
+        self.clock += 1;
+        response.last_used = self.clock;
+
+        if !self.responses.contains_key(route)
+            && self.responses.len() >= MAX_CACHED_RESPONSES
+            && let Some(oldest) = self
+                .responses
+                .iter()
+                .min_by_key(|(_, r)| r.last_used)
+                .map(|(route, _)| route.clone())
+        {
+            self.responses.remove(&oldest);
+        }
+        self.responses.insert(route.to_string(), response);
+    }
+
+    fn remove_matching(&mut self, matches: impl Fn(&str) -> bool) {
+        self.responses.retain(|route, _| !matches(route));
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimit {
+    pub resource: String,
+    pub limit: u64,
+    pub remaining: u64,
+    // Unix timestamp, in seconds, at which the quota resets
+    pub reset: i64,
+}
+
+impl RateLimit {
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining <= RATE_LIMIT_RESERVE
+    }
+
+    fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+
+        Some(Self {
+            resource: header("x-ratelimit-resource")?.to_string(),
+            limit: header("x-ratelimit-limit")?.parse().ok()?,
+            remaining: header("x-ratelimit-remaining")?.parse().ok()?,
+            reset: header("x-ratelimit-reset")?.parse().ok()?,
+        })
+    }
+}
+
+impl Client {
+    pub fn new(octocrab: Octocrab, max_concurrent_requests: usize) -> Self {
+        Self {
+            octocrab,
+            semaphore: Semaphore::new(max_concurrent_requests.max(1)),
+            cache: Default::default(),
+            rate_limits: Default::default(),
+        }
+    }
+
+    /// Returns the rate limit with the least remaining requests
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        self.rate_limits
+            .read()
+            .unwrap()
+            .values()
+            .min_by_key(|r| r.remaining * 1000 / r.limit.max(1))
+            .cloned()
+    }
+
+    pub(super) fn octocrab(&self) -> &Octocrab {
+        &self.octocrab
+    }
+
+    /// Waits for the rate limit reset if the quota of the resource is about to be exhausted, then
+    /// for a free request slot
+    pub(super) async fn acquire(&self, resource: &str) -> SemaphorePermit<'_> {
+        // Wait without holding a slot, the requests to the other resources keep going meanwhile
+        let exhausted = self
+            .rate_limits
+            .read()
+            .unwrap()
+            .get(resource)
+            .filter(|r| r.is_exhausted())
+            .cloned();
+
+        if let Some(rate_limit) = exhausted {
+            let wait = (rate_limit.reset - chrono::Utc::now().timestamp()).max(0) as u64;
+            tokio::time::sleep(Duration::from_secs(wait).min(MAX_RATE_LIMIT_WAIT)).await;
+            // The quota is back, forget about the stale limit
+            self.rate_limits.write().unwrap().remove(resource);
+        }
+
+        self.semaphore
+            .acquire()
+            .await
+            .expect("the semaphore is never closed")
+    }
+
+    /// Sends a conditional `GET` to the route, a `304 Not Modified` does not count against the
+    /// rate limit and returns the previously cached response
+    pub(super) async fn get<T: DeserializeOwned>(&self, route: &str) -> Result<T, Error> {
+        let _permit = self.acquire(CORE_RESOURCE).await;
+
+        let cached = self.cache.lock().unwrap().get(route);
+        let mut headers = HeaderMap::new();
+        if let Some(cached) = &cached {
+            if let Some(etag) = &cached.etag {
+                headers.insert(header::IF_NONE_MATCH, etag.clone());
+            }
+            if let Some(last_modified) = &cached.last_modified {
+                headers.insert(header::IF_MODIFIED_SINCE, last_modified.clone());
+            }
+        }
+
+        let response = self
+            .octocrab
+            ._get_with_headers(route, Some(headers))
+            .await?;
+        self.update_rate_limit(response.headers());
+
+        if response.status() == StatusCode::NOT_MODIFIED
+            && let Some(cached) = cached
+        {
+            return Ok(serde_json::from_str(&cached.body)?);
+        }
+
+        let response = octocrab::map_github_error(response).await?;
+        let etag = response.headers().get(header::ETAG).cloned();
+        let last_modified = response.headers().get(header::LAST_MODIFIED).cloned();
+        let body = self.octocrab.body_to_string(response).await?;
+        let value = serde_json::from_str(&body)?;
+
+        if etag.is_some() || last_modified.is_some() {
+            self.cache.lock().unwrap().insert(
+                route,
+                CachedResponse {
+                    etag,
+                    last_modified,
+                    body,
+                    last_used: 0,
+                },
+            );
+        }
+
+        Ok(value)
+    }
+
+    /// Sends a graphql query, these can't be conditional but still count against the rate limit
+    pub(super) async fn graphql<T: DeserializeOwned>(
+        &self,
+        payload: &serde_json::Value,
+    ) -> Result<T, Error> {
+        self.post_to(GRAPHQL_RESOURCE, "/graphql", payload).await
+    }
+
+    async fn post_to<T: DeserializeOwned>(
+        &self,
+        resource: &str,
+        route: &str,
+        payload: &serde_json::Value,
+    ) -> Result<T, Error> {
+        let _permit = self.acquire(resource).await;
+
+        let response = self.octocrab._post(route, Some(payload)).await?;
+        self.update_rate_limit(response.headers());
+
+        let response = octocrab::map_github_error(response).await?;
+        let body = self.octocrab.body_to_string(response).await?;
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    /// Sends a `GET` to the route and returns the raw body, for the responses that aren't json
+    /// (e.g. logs). These aren't cached, the body being possibly large
+    pub(super) async fn get_text(&self, route: &str) -> Result<String, Error> {
+        let _permit = self.acquire(CORE_RESOURCE).await;
+
+        let response = self.octocrab._get(route).await?;
+        self.update_rate_limit(response.headers());
+
+        let response = octocrab::map_github_error(response).await?;
+        Ok(self.octocrab.body_to_string(response).await?)
+    }
+
+    /// Sends a `POST` without body to the route, discarding the response
+    pub(super) async fn post_empty(&self, route: &str) -> Result<(), Error> {
+        let _permit = self.acquire(CORE_RESOURCE).await;
+
+        let response = self.octocrab._post(route, None::<&()>).await?;
+        self.update_rate_limit(response.headers());
+        octocrab::map_github_error(response).await?;
+
+        Ok(())
+    }
+
+    /// Sends a `PATCH` without body to the route, discarding the response
+    pub(super) async fn patch(&self, route: &str) -> Result<(), Error> {
+        let _permit = self.acquire(CORE_RESOURCE).await;
+
+        let response = self.octocrab._patch(route, None::<&()>).await?;
+        self.update_rate_limit(response.headers());
+        octocrab::map_github_error(response).await?;
+
+        Ok(())
+    }
+
+    /// Sends a `DELETE` to the route, discarding the response
+    pub(super) async fn delete(&self, route: &str) -> Result<(), Error> {
+        let _permit = self.acquire(CORE_RESOURCE).await;
+
+        let response = self.octocrab._delete(route, None::<&()>).await?;
+        self.update_rate_limit(response.headers());
+        octocrab::map_github_error(response).await?;
+
+        Ok(())
+    }
+
+    /// Forgets the cached responses of the matching routes, so the next `GET` isn't answered
+    /// with them
+    pub(super) fn invalidate_matching(&self, matches: impl Fn(&str) -> bool) {
+        self.cache.lock().unwrap().remove_matching(matches);
+    }
+
+    fn update_rate_limit(&self, headers: &HeaderMap) {
+        if let Some(rate_limit) = RateLimit::from_headers(headers) {
+            self.rate_limits
+                .write()
+                .unwrap()
+                .insert(rate_limit.resource.clone(), rate_limit);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use serde_json::{Value, json};
+
+    use super::*;
+    use crate::forge::stub::{Response, StubServer};
+
+    fn response(body: &str) -> CachedResponse {
+        CachedResponse {
+            etag: Some(HeaderValue::from_static("\"etag\"")),
+            last_modified: None,
+            body: body.to_string(),
+            last_used: 0,
+        }
+    }
+
+    fn headers(values: &[(&'static str, &'static str)]) -> HeaderMap {
+        values
+            .iter()
+            .map(|(name, value)| {
+                (
+                    header::HeaderName::from_static(name),
+                    HeaderValue::from_static(value),
+                )
+            })
+            .collect()
+    }
+
+    fn client(url: &str) -> Client {
+        let octocrab = Octocrab::builder().base_uri(url).unwrap().build().unwrap();
+        Client::new(octocrab, 1)
+    }
+
+    #[test]
+    fn response_cache_drops_the_least_recently_used_response() {
+        let mut cache = ResponseCache::default();
+        for i in 0..MAX_CACHED_RESPONSES {
+            cache.insert(&format!("/route/{}", i), response("{}"));
+        }
+
+        // Used again, it is now more recent than the second one
+        assert!(cache.get("/route/0").is_some());
+        cache.insert("/route/new", response("{}"));
+
+        assert_eq!(cache.responses.len(), MAX_CACHED_RESPONSES);
+        assert!(cache.get("/route/0").is_some());
+        assert!(cache.get("/route/1").is_none());
+        assert!(cache.get("/route/new").is_some());
+    }
+
+    #[test]
+    fn response_cache_replaces_a_cached_route_without_dropping_any() {
+        let mut cache = ResponseCache::default();
+        for i in 0..MAX_CACHED_RESPONSES {
+            cache.insert(&format!("/route/{}", i), response("{}"));
+        }
+
+        cache.insert("/route/0", response("[]"));
+
+        assert_eq!(cache.responses.len(), MAX_CACHED_RESPONSES);
+        assert_eq!(cache.get("/route/0").unwrap().body, "[]");
+    }
+
+    #[test]
+    fn rate_limit_is_read_from_the_headers() {
+        let rate_limit = RateLimit::from_headers(&headers(&[
+            ("x-ratelimit-resource", "core"),
+            ("x-ratelimit-limit", "5000"),
+            ("x-ratelimit-remaining", "42"),
+            ("x-ratelimit-reset", "1700000000"),
+        ]));
+
+        assert_eq!(
+            rate_limit,
+            Some(RateLimit {
+                resource: "core".to_string(),
+                limit: 5000,
+                remaining: 42,
+                reset: 1700000000,
+            })
+        );
+        assert!(rate_limit.unwrap().is_exhausted());
+    }
+
+    #[test]
+    fn rate_limit_needs_every_header() {
+        let missing_reset = headers(&[
+            ("x-ratelimit-resource", "core"),
+            ("x-ratelimit-limit", "5000"),
+            ("x-ratelimit-remaining", "42"),
+        ]);
+        let invalid_remaining = headers(&[
+            ("x-ratelimit-resource", "core"),
+            ("x-ratelimit-limit", "5000"),
+            ("x-ratelimit-remaining", "many"),
+            ("x-ratelimit-reset", "1700000000"),
+        ]);
+
+        assert_eq!(RateLimit::from_headers(&missing_reset), None);
+        assert_eq!(RateLimit::from_headers(&invalid_remaining), None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn acquire_waits_for_the_reset_of_an_exhausted_rate_limit() {
+        let client = client("http://localhost");
+        let rate_limit = |resource: &str, remaining| RateLimit {
+            resource: resource.to_string(),
+            limit: 5000,
+            remaining,
+            reset: chrono::Utc::now().timestamp() + 120,
+        };
+        client.rate_limits.write().unwrap().extend([
+            (CORE_RESOURCE.to_string(), rate_limit(CORE_RESOURCE, 10)),
+            (
+                GRAPHQL_RESOURCE.to_string(),
+                rate_limit(GRAPHQL_RESOURCE, 4000),
+            ),
+        ]);
+
+        let start = tokio::time::Instant::now();
+        drop(client.acquire(GRAPHQL_RESOURCE).await);
+        assert_eq!(start.elapsed(), Duration::ZERO);
+
+        drop(client.acquire(CORE_RESOURCE).await);
+        assert!(start.elapsed() >= Duration::from_secs(119));
+        assert!(
+            !client
+                .rate_limits
+                .read()
+                .unwrap()
+                .contains_key(CORE_RESOURCE)
+        );
+    }
+
+    #[tokio::test]
+    async fn get_answers_a_not_modified_response_from_the_cache() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let requests = count.clone();
+        let server = StubServer::start(move |_| {
+            if requests.fetch_add(1, Ordering::SeqCst) == 0 {
+                Response::json(json!({ "name": "app" })).header("etag", "\"v1\"")
+            } else {
+                Response {
+                    status: 304,
+                    headers: vec![],
+                    body: String::new(),
+                }
+            }
+        })
+        .await;
+        let client = client(&server.url);
+
+        let fetched: Value = client.get("/repos/octo/app").await.unwrap();
+        let cached: Value = client.get("/repos/octo/app").await.unwrap();
+
+        assert_eq!(fetched, json!({ "name": "app" }));
+        assert_eq!(cached, fetched);
+        assert_eq!(server.requests(), ["/repos/octo/app", "/repos/octo/app"]);
+    }
+}
@@ -1,3 +1,5 @@
 pub mod cli;
 mod config;
+mod forge;
+mod github;
 mod tui;
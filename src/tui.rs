@@ -1,26 +1,82 @@
+mod markdown;
 mod pr;
 mod utils;
 
 use color_eyre::Result;
 use crossterm::event::{Event, EventStream, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
-use pr::PullRequestWidget;
+use pr::{PullRequestWidget, RepoId, RepositoryForge};
 use ratatui::{
     DefaultTerminal, Frame,
     layout::{Constraint, Layout},
     style::Stylize,
     text::Line,
 };
-use std::time::Duration;
+use std::{collections::HashMap, sync::Arc, time::Duration};
 use tokio_stream::StreamExt;
 
-use crate::config::Config;
+use crate::{
+    config::{Config, ForgeKind},
+    forge::{GitLab, Gitea, NotificationAction, RunAction},
+    github,
+};
+
+const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 4;
+
+pub async fn run(config: Config, offline: bool) -> Result<()> {
+    let max_concurrent_requests = config
+        .max_concurrent_requests
+        .unwrap_or(DEFAULT_MAX_CONCURRENT_REQUESTS);
+    let forges = build_forges(&config, max_concurrent_requests);
 
-pub async fn run(config: Config) -> Result<()> {
     let terminal = ratatui::init();
-    App::new(config).run(terminal).await?;
+    let mut app = App::new(config, forges, offline);
+    let result = app.run(terminal).await;
     ratatui::restore();
 
-    Ok(())
+    // Keep the details fetched since the last refresh for the next startup, failing to do so only
+    // costs a slower startup so it is reported once the terminal is restored
+    if let Err(err) = app.pull_requests.save_cache() {
+        eprintln!("Could not save the cache: {:#}", err);
+    }
+
+    result
+}
+
+// Builds the forge of every repository, repositories on the same forge share the same client
+fn build_forges(
+    config: &Config,
+    max_concurrent_requests: usize,
+) -> HashMap<RepoId, RepositoryForge> {
+    let github = RepositoryForge::new(Arc::new(github::Client::new(
+        octocrab::instance().as_ref().clone(),
+        max_concurrent_requests,
+    )));
+    let mut clients: HashMap<(ForgeKind, Option<String>, Option<String>), RepositoryForge> =
+        HashMap::new();
+
+    config
+        .repositories
+        .iter()
+        .map(|r| {
+            let forge = if r.forge == ForgeKind::Github {
+                github.clone()
+            } else {
+                clients
+                    .entry((r.forge, r.host.clone(), r.token.clone()))
+                    .or_insert_with(|| {
+                        let host = r.host.as_deref();
+                        let token = r.token.clone();
+                        RepositoryForge::new(match r.forge {
+                            ForgeKind::Github => unreachable!("github has a single client"),
+                            ForgeKind::Gitlab => Arc::new(GitLab::new(host, token)),
+                            ForgeKind::Gitea => Arc::new(Gitea::new(host, token)),
+                        })
+                    })
+                    .clone()
+            };
+            (RepoId::from(r), forge)
+        })
+        .collect()
 }
 
 #[derive(PartialEq, Eq)]
@@ -28,6 +84,8 @@ enum InputMode {
     Normal,
     Searching,
     Help,
+    ErrorLog,
+    WorkflowRuns,
 }
 
 pub struct App {
@@ -39,27 +97,30 @@ pub struct App {
 impl App {
     const FRAMES_PER_SECOND: f32 = 30.0;
 
-    pub fn new(config: Config) -> Self {
+    pub fn new(config: Config, forges: HashMap<RepoId, RepositoryForge>, offline: bool) -> Self {
         Self {
             should_quit: false,
-            pull_requests: PullRequestWidget::new(config),
+            pull_requests: PullRequestWidget::new(config, forges, offline),
             input_mode: InputMode::Normal,
         }
     }
 
-    pub async fn run(mut self, mut terminal: DefaultTerminal) -> Result<()> {
+    pub async fn run(&mut self, mut terminal: DefaultTerminal) -> Result<()> {
         self.pull_requests.run();
         let period = Duration::from_secs_f32(1.0 / Self::FRAMES_PER_SECOND);
         let mut interval = tokio::time::interval(period);
         let mut events = EventStream::new();
 
         let mut refresh_interval = tokio::time::interval(Duration::from_secs_f32(30_f32));
+        let mut retry_interval = tokio::time::interval(Duration::from_secs(1));
 
         while !self.should_quit {
             tokio::select! {
                 _ = interval.tick() => { terminal.draw(|frame| self.draw(frame))?; },
                 // Refresh pull requests on interval tick
                 _ = refresh_interval.tick() => { self.pull_requests.refresh_pull_requests() },
+                // Fetch again the repositories that failed once their backoff has elapsed
+                _ = retry_interval.tick() => { self.pull_requests.retry_failed() },
                 Some(Ok(event)) = events.next() => self.handle_event(&event),
             }
         }
@@ -81,13 +142,15 @@ impl App {
     }
 
     fn handle_event(&mut self, event: &Event) {
-        if let Event::Key(key_event) = event {
-            if key_event.kind == KeyEventKind::Press {
-                match self.input_mode {
-                    InputMode::Normal => self.handle_normal_input(*key_event),
-                    InputMode::Searching => self.handle_searching_input(*key_event, event),
-                    InputMode::Help => self.handle_help_input(*key_event),
-                }
+        if let Event::Key(key_event) = event
+            && key_event.kind == KeyEventKind::Press
+        {
+            match self.input_mode {
+                InputMode::Normal => self.handle_normal_input(*key_event),
+                InputMode::Searching => self.handle_searching_input(*key_event, event),
+                InputMode::Help => self.handle_help_input(*key_event),
+                InputMode::ErrorLog => self.handle_error_log_input(*key_event),
+                InputMode::WorkflowRuns => self.handle_workflow_runs_input(*key_event),
             }
         }
     }
@@ -99,7 +162,8 @@ impl App {
             KeyCode::Char('k') | KeyCode::Up => self.pull_requests.scroll_up(),
             KeyCode::Char('o') => self.pull_requests.open(),
             KeyCode::Char('r') => self.pull_requests.review(),
-            KeyCode::Char('f') => self.pull_requests.refresh_pull_requests(),
+            KeyCode::Char('f') => self.pull_requests.refetch_pull_requests(),
+            KeyCode::Char('s') => self.pull_requests.next_status_filter(),
             KeyCode::Char('n') => self.pull_requests.next_repository(),
             KeyCode::Char('p') => self.pull_requests.previous_repository(),
             KeyCode::Char('d') => {
@@ -117,6 +181,20 @@ impl App {
                 }
             }
             KeyCode::Tab => self.pull_requests.next_tab(),
+            KeyCode::Char('t') => self.pull_requests.next_details_tab(),
+            KeyCode::Char('J') => self.pull_requests.select_next_check(),
+            KeyCode::Char('K') => self.pull_requests.select_previous_check(),
+            KeyCode::Char('O') => self.pull_requests.open_selected_check(),
+            KeyCode::Char('m') => self
+                .pull_requests
+                .update_notification(NotificationAction::MarkRead),
+            KeyCode::Char('D') => self
+                .pull_requests
+                .update_notification(NotificationAction::MarkDone),
+            KeyCode::Char('U') => self
+                .pull_requests
+                .update_notification(NotificationAction::Unsubscribe),
+            KeyCode::Enter => self.pull_requests.go_to_notification(),
             KeyCode::Char('/') => {
                 self.pull_requests.toggle_search();
                 self.input_mode = InputMode::Searching;
@@ -125,6 +203,13 @@ impl App {
                 self.pull_requests.toggle_help();
                 self.input_mode = InputMode::Help;
             }
+            KeyCode::Char('e') => {
+                self.pull_requests.toggle_error_log();
+                self.input_mode = InputMode::ErrorLog;
+            }
+            KeyCode::Char('a') if self.pull_requests.open_workflow_runs() => {
+                self.input_mode = InputMode::WorkflowRuns;
+            }
             _ => {}
         }
     }
@@ -159,4 +244,54 @@ impl App {
             _ => {} // Ignore other keys
         }
     }
+
+    fn handle_error_log_input(&mut self, key_event: KeyEvent) {
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('e') => {
+                self.pull_requests.toggle_error_log();
+                self.input_mode = InputMode::Normal;
+            }
+            KeyCode::Char('j') | KeyCode::Down => self.pull_requests.scroll_error_log_down(),
+            KeyCode::Char('k') | KeyCode::Up => self.pull_requests.scroll_error_log_up(),
+            KeyCode::Char('c') => self.pull_requests.clear_error_log(),
+            _ => {}
+        }
+    }
+
+    fn handle_workflow_runs_input(&mut self, key_event: KeyEvent) {
+        // The search of the log takes every key until it is done
+        if self.pull_requests.workflow_runs_searching() {
+            self.pull_requests
+                .handle_workflow_log_search(key_event.code);
+            return;
+        }
+
+        let control = key_event.modifiers.contains(KeyModifiers::CONTROL);
+        match key_event.code {
+            // Going back from the runs closes them
+            KeyCode::Esc if !self.pull_requests.workflow_runs_back() => {
+                self.input_mode = InputMode::Normal;
+            }
+            KeyCode::Char('a') => {
+                self.pull_requests.close_workflow_runs();
+                self.input_mode = InputMode::Normal;
+            }
+            KeyCode::Char('j') | KeyCode::Down => self.pull_requests.select_next_workflow_item(),
+            KeyCode::Char('k') | KeyCode::Up => self.pull_requests.select_previous_workflow_item(),
+            KeyCode::Char('d') if control => self.pull_requests.page_workflow_log_down(),
+            KeyCode::Char('u') if control => self.pull_requests.page_workflow_log_up(),
+            KeyCode::Tab => self.pull_requests.toggle_workflow_runs_branch(),
+            KeyCode::Enter => self.pull_requests.enter_workflow_item(),
+            KeyCode::Char('o') => self.pull_requests.open_workflow_item(),
+            KeyCode::Char('f') => self.pull_requests.reload_workflow_runs(),
+            KeyCode::Char('R') => self
+                .pull_requests
+                .update_workflow_run(RunAction::RerunFailedJobs),
+            KeyCode::Char('C') => self.pull_requests.update_workflow_run(RunAction::Cancel),
+            KeyCode::Char('/') => self.pull_requests.start_workflow_log_search(),
+            KeyCode::Char('n') => self.pull_requests.next_workflow_log_match(),
+            KeyCode::Char('N') => self.pull_requests.previous_workflow_log_match(),
+            _ => {}
+        }
+    }
 }
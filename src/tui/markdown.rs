@@ -0,0 +1,490 @@
+use std::sync::LazyLock;
+
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use ratatui::{
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span, Text},
+};
+use syntect::{
+    easy::HighlightLines,
+    highlighting::{Theme, ThemeSet},
+    parsing::SyntaxSet,
+    util::LinesWithEndings,
+};
+
+// Loading the syntaxes is slow, do it once and only when a code block shows up
+static SYNTAXES: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+static THEME: LazyLock<Theme> = LazyLock::new(|| {
+    ThemeSet::load_defaults()
+        .themes
+        .remove("base16-ocean.dark")
+        .unwrap_or_default()
+});
+
+/// Renders the markdown of a pr or issue body into styled lines
+pub fn render(markdown: &str) -> Text<'static> {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_GFM;
+
+    let mut renderer = Renderer::default();
+    for event in Parser::new_ext(markdown, options) {
+        renderer.event(event);
+    }
+    renderer.flush();
+
+    // Every block leaves a blank line after it, the last one is not needed
+    while renderer.lines.last().is_some_and(|line| line.width() == 0) {
+        renderer.lines.pop();
+    }
+    Text::from(renderer.lines)
+}
+
+#[derive(Debug, Default)]
+struct Renderer {
+    lines: Vec<Line<'static>>,
+    // Spans of the line being built
+    current: Vec<Span<'static>>,
+    // Inline styles, e.g. emphasis inside a link
+    styles: Vec<Style>,
+    // Next number of every nested list, `None` for bullet lists
+    lists: Vec<Option<u64>>,
+    quote_depth: usize,
+    // Language and content of the code block being read
+    code_block: Option<(String, String)>,
+    // Raw html of the block being read
+    html_block: Option<String>,
+    // Cells of the table being read, by row
+    table: Option<Vec<Vec<String>>>,
+}
+
+impl Renderer {
+    fn event(&mut self, event: Event) {
+        // The content of code blocks, html blocks and tables is rendered once complete
+        if let Some((_, code)) = &mut self.code_block
+            && let Event::Text(text) = &event
+        {
+            code.push_str(text);
+            return;
+        }
+        if let Some(html) = &mut self.html_block
+            && let Event::Html(text) = &event
+        {
+            html.push_str(text);
+            return;
+        }
+        if let Some(rows) = &mut self.table
+            && let Event::Text(text) | Event::Code(text) = &event
+        {
+            if let Some(cell) = rows.last_mut().and_then(|row| row.last_mut()) {
+                cell.push_str(text);
+            }
+            return;
+        }
+
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) => self.push(text.to_string()),
+            Event::Code(code) => self.current.push(Span::styled(
+                code.to_string(),
+                Style::default().fg(Color::Yellow),
+            )),
+            // Pr templates are full of hints left as comments
+            Event::InlineHtml(html) if html.starts_with("<!--") => {}
+            Event::InlineHtml(html) => self.current.push(html.to_string().dark_gray()),
+            Event::Html(html) => self.render_html(&html),
+            Event::SoftBreak => self.push(" ".to_string()),
+            Event::HardBreak => self.flush(),
+            Event::Rule => {
+                self.flush();
+                self.lines.push(Line::from("─".repeat(40).dark_gray()));
+                self.lines.push(Line::default());
+            }
+            Event::TaskListMarker(checked) => self.current.push(if checked {
+                "[x] ".green()
+            } else {
+                "[ ] ".dark_gray()
+            }),
+            _ => {}
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Heading { level, .. } => {
+                self.flush();
+                let style = match level {
+                    HeadingLevel::H1 => Style::default()
+                        .fg(Color::Magenta)
+                        .add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+                    HeadingLevel::H2 => Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                    _ => Style::default().add_modifier(Modifier::BOLD),
+                };
+                self.styles.push(style);
+            }
+            Tag::BlockQuote(_) => {
+                self.flush();
+                self.quote_depth += 1;
+            }
+            Tag::CodeBlock(kind) => {
+                self.flush();
+                let language = match kind {
+                    // e.g. `rust` in ```rust,ignore
+                    CodeBlockKind::Fenced(info) => info
+                        .split([',', ' '])
+                        .next()
+                        .unwrap_or_default()
+                        .to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                self.code_block = Some((language, String::new()));
+            }
+            Tag::HtmlBlock => {
+                self.flush();
+                self.html_block = Some(String::new());
+            }
+            Tag::List(start) => {
+                self.flush();
+                self.lists.push(start);
+            }
+            Tag::Item => {
+                self.flush();
+                let indent = "  ".repeat(self.lists.len().saturating_sub(1));
+                let marker = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}{}. ", indent, *number - 1)
+                    }
+                    _ => format!("{}• ", indent),
+                };
+                self.current.push(marker.dark_gray());
+            }
+            Tag::Emphasis => self.push_style(Style::default().add_modifier(Modifier::ITALIC)),
+            Tag::Strong => self.push_style(Style::default().add_modifier(Modifier::BOLD)),
+            Tag::Strikethrough => {
+                self.push_style(Style::default().add_modifier(Modifier::CROSSED_OUT))
+            }
+            Tag::Link { .. } => self.push_style(
+                Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::UNDERLINED),
+            ),
+            Tag::Table(_) => {
+                self.flush();
+                self.table = Some(vec![]);
+            }
+            Tag::TableHead | Tag::TableRow => {
+                if let Some(rows) = &mut self.table {
+                    rows.push(vec![]);
+                }
+            }
+            Tag::TableCell => {
+                if let Some(row) = self.table.as_mut().and_then(|rows| rows.last_mut()) {
+                    row.push(String::new());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Heading(_) => {
+                self.flush();
+                self.styles.pop();
+                self.lines.push(Line::default());
+            }
+            TagEnd::Paragraph => {
+                self.flush();
+                // Paragraphs inside loose list items stay together
+                if self.lists.is_empty() {
+                    self.lines.push(Line::default());
+                }
+            }
+            TagEnd::BlockQuote(_) => {
+                self.flush();
+                self.quote_depth = self.quote_depth.saturating_sub(1);
+            }
+            TagEnd::CodeBlock => {
+                if let Some((language, code)) = self.code_block.take() {
+                    self.lines.extend(highlight(&language, &code));
+                    self.lines.push(Line::default());
+                }
+            }
+            TagEnd::HtmlBlock => {
+                if let Some(html) = self.html_block.take() {
+                    self.render_html(&html);
+                }
+            }
+            TagEnd::List(_) => {
+                self.flush();
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.lines.push(Line::default());
+                }
+            }
+            TagEnd::Item => self.flush(),
+            TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough | TagEnd::Link => {
+                self.styles.pop();
+            }
+            TagEnd::Table => {
+                if let Some(rows) = self.table.take() {
+                    self.lines.extend(table_lines(&rows));
+                    self.lines.push(Line::default());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn push_style(&mut self, style: Style) {
+        let current = self.styles.last().copied().unwrap_or_default();
+        self.styles.push(current.patch(style));
+    }
+
+    fn push(&mut self, text: String) {
+        let style = self.styles.last().copied().unwrap_or_default();
+        self.current.push(Span::styled(text, style));
+    }
+
+    /// Ends the line being built, if there is one
+    fn flush(&mut self) {
+        if self.current.is_empty() {
+            return;
+        }
+
+        let mut spans = vec![];
+        if self.quote_depth > 0 {
+            spans.push("│ ".repeat(self.quote_depth).dark_gray());
+        }
+        spans.append(&mut self.current);
+        self.lines.push(Line::from(spans));
+    }
+
+    // Html shows up as is, except for the comments which are hidden
+    fn render_html(&mut self, html: &str) {
+        let mut visible = String::new();
+        let mut rest = html;
+        while let Some(start) = rest.find("<!--") {
+            visible.push_str(&rest[..start]);
+            rest = match rest[start..].find("-->") {
+                Some(end) => &rest[start + end + 3..],
+                // Unterminated, hide everything after it
+                None => "",
+            };
+        }
+        visible.push_str(rest);
+
+        let lines: Vec<Line> = visible
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| Line::from(line.to_string().dark_gray()))
+            .collect();
+        if !lines.is_empty() {
+            self.lines.extend(lines);
+            self.lines.push(Line::default());
+        }
+    }
+}
+
+/// Highlights the code with the syntax of the language, plain when it is unknown
+fn highlight(language: &str, code: &str) -> Vec<Line<'static>> {
+    let Some(syntax) = (!language.is_empty())
+        .then(|| SYNTAXES.find_syntax_by_token(language))
+        .flatten()
+    else {
+        return code
+            .lines()
+            .map(|line| Line::from(format!("  {}", line).yellow()))
+            .collect();
+    };
+
+    let mut highlighter = HighlightLines::new(syntax, &THEME);
+    LinesWithEndings::from(code)
+        .map(|line| {
+            let mut spans = vec![Span::raw("  ")];
+            match highlighter.highlight_line(line, &SYNTAXES) {
+                Ok(regions) => spans.extend(regions.into_iter().map(|(style, text)| {
+                    let fg = style.foreground;
+                    Span::styled(
+                        text.trim_end_matches('\n').to_string(),
+                        Style::default().fg(Color::Rgb(fg.r, fg.g, fg.b)),
+                    )
+                })),
+                Err(_) => spans.push(Span::raw(line.trim_end_matches('\n').to_string())),
+            }
+            Line::from(spans)
+        })
+        .collect()
+}
+
+// Columns padded to their widest cell, the first row is the header
+fn table_lines(rows: &[Vec<String>]) -> Vec<Line<'static>> {
+    let columns = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|i| {
+            rows.iter()
+                .filter_map(|row| row.get(i))
+                .map(|cell| cell.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let mut lines = vec![];
+    for (i, row) in rows.iter().enumerate() {
+        let mut line = Line::default();
+        for (column, width) in widths.iter().enumerate() {
+            if column > 0 {
+                line.push_span(" │ ".dark_gray());
+            }
+            let cell = format!("{:<width$}", row.get(column).map_or("", |c| c.as_str()));
+            line.push_span(if i == 0 { cell.bold() } else { cell.into() });
+        }
+        lines.push(line);
+
+        if i == 0 {
+            let separator: Vec<String> = widths.iter().map(|width| "─".repeat(*width)).collect();
+            lines.push(Line::from(separator.join("─┼─").dark_gray()));
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::widgets::{Paragraph, Wrap};
+
+    use super::*;
+
+    fn lines(text: &Text) -> Vec<String> {
+        text.lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn headings_are_styled_by_level() {
+        let text = render("# Title\n## Section\n### Detail");
+
+        assert_eq!(lines(&text), ["Title", "", "Section", "", "Detail"]);
+        assert_eq!(text.lines[0].spans[0].style.fg, Some(Color::Magenta));
+        assert!(
+            text.lines[0].spans[0]
+                .style
+                .add_modifier
+                .contains(Modifier::UNDERLINED)
+        );
+        assert_eq!(text.lines[2].spans[0].style.fg, Some(Color::Cyan));
+        assert!(
+            text.lines[4].spans[0]
+                .style
+                .add_modifier
+                .contains(Modifier::BOLD)
+        );
+    }
+
+    #[test]
+    fn nested_and_ordered_lists_are_indented_and_numbered() {
+        let text = render("- one\n  - nested\n- two\n\n3. third\n4. fourth");
+
+        assert_eq!(
+            lines(&text),
+            ["• one", "  • nested", "• two", "", "3. third", "4. fourth"]
+        );
+    }
+
+    #[test]
+    fn task_lists_show_their_checkboxes() {
+        let text = render("- [x] done\n- [ ] todo");
+
+        assert_eq!(lines(&text), ["• [x] done", "• [ ] todo"]);
+        assert_eq!(text.lines[0].spans[1].style.fg, Some(Color::Green));
+        assert_eq!(text.lines[1].spans[1].style.fg, Some(Color::DarkGray));
+    }
+
+    #[test]
+    fn tables_are_padded_to_their_widest_cells() {
+        let text = render("| Name | State |\n|---|---|\n| build | `ok` |\n| lint-all | failed |");
+
+        assert_eq!(
+            lines(&text),
+            [
+                "Name     │ State ",
+                "─────────┼───────",
+                "build    │ ok    ",
+                "lint-all │ failed",
+            ]
+        );
+        assert!(
+            text.lines[0].spans[0]
+                .style
+                .add_modifier
+                .contains(Modifier::BOLD)
+        );
+    }
+
+    #[test]
+    fn table_lines_fill_the_missing_cells() {
+        let rows = vec![
+            vec!["a".to_string(), "b".to_string()],
+            vec!["long".to_string()],
+        ];
+
+        assert_eq!(
+            lines(&Text::from(table_lines(&rows))),
+            ["a    │ b", "─────┼──", "long │  "]
+        );
+    }
+
+    #[test]
+    fn code_blocks_are_highlighted_in_known_languages_only() {
+        let rust = render("```rust,ignore\nfn main() {}\n```");
+        let unknown = render("```nolang\nfn main() {}\n```");
+
+        assert_eq!(lines(&rust), ["  fn main() {}"]);
+        // Split into the tokens of the language, each with its color
+        assert!(rust.lines[0].spans.len() > 2);
+        assert!(
+            rust.lines[0]
+                .spans
+                .iter()
+                .all(|span| span.content.trim().is_empty()
+                    || matches!(span.style.fg, Some(Color::Rgb(..))))
+        );
+
+        assert_eq!(lines(&unknown), ["  fn main() {}"]);
+        assert_eq!(unknown.lines[0].spans.len(), 1);
+        assert_eq!(unknown.lines[0].spans[0].style.fg, Some(Color::Yellow));
+    }
+
+    #[test]
+    fn html_comments_are_hidden() {
+        let text = render(
+            "<!-- Describe your changes -->\nFixes the build <!-- hint --> for good\n\n<details>\n<!-- inside -->\n</details>",
+        );
+
+        assert_eq!(
+            lines(&text),
+            ["Fixes the build  for good", "", "<details>", "</details>"]
+        );
+    }
+
+    #[test]
+    fn unterminated_html_comments_hide_the_rest() {
+        let text = render("Summary\n\n<!-- Checklist\n- [ ] tests\n\nstill hidden");
+
+        assert_eq!(lines(&text), ["Summary"]);
+    }
+
+    #[test]
+    fn trailing_blank_lines_are_not_scrolled_to() {
+        let text = render("Body\n\n- item\n\n```\ncode\n```\n\n\n");
+        let paragraph = Paragraph::new(text.clone()).wrap(Wrap { trim: false });
+
+        assert_eq!(lines(&text), ["Body", "", "• item", "", "  code"]);
+        assert_eq!(paragraph.line_count(80), 5);
+    }
+}
@@ -1,17 +1,25 @@
+mod cache;
+mod error_log_state;
 mod pr_details_state;
 mod pr_list_state;
+mod retry;
+mod workflow_runs_state;
 
 use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    fmt,
+    hash::{Hash, Hasher},
+    path::PathBuf,
     process::Command,
     sync::{Arc, RwLock},
+    time::Duration,
 };
 
-use crossterm::event::Event;
-use octocrab::{
-    Page,
-    models::UserProfile,
-    params::{Direction, State},
-};
+use cache::Snapshot;
+use chrono::{DateTime, Utc};
+use crossterm::event::{Event, KeyCode};
+use error_log_state::ErrorLogState;
 use pr_details_state::PullRequestsDetailsState;
 use pr_list_state::PullRequestsListState;
 use ratatui::{
@@ -19,12 +27,18 @@ use ratatui::{
     layout::{Constraint, Layout, Position, Rect},
     style::{Color, Style, Stylize},
     text::Line,
-    widgets::{Block, Cell, Paragraph, Row, Table, Widget, Wrap},
+    widgets::{Block, Cell, Paragraph, Row, Table, Widget},
 };
-use tokio::task::JoinSet;
+use retry::RetryScheduler;
+use serde::{Deserialize, Serialize};
+use tokio::sync::OnceCell;
 use tui_input::{Input, backend::crossterm::EventHandler};
+use workflow_runs_state::{Entered, WorkflowRunsState};
 
-use crate::config::Config;
+use crate::{
+    config::{Config, Repository},
+    forge::{self, Forge},
+};
 
 use super::utils;
 
@@ -32,6 +46,30 @@ use super::utils;
 pub struct PullRequestWidget {
     config: Config,
     state: Arc<RwLock<AppState>>,
+    forges: HashMap<RepoId, RepositoryForge>,
+    // Names shared by several repositories, these are shown along with their owner
+    ambiguous_names: HashSet<String>,
+    // Only show the cached data, without calling github
+    offline: bool,
+    // Where the data is kept between runs, nothing is cached without it
+    cache_file: Option<PathBuf>,
+}
+
+/// Forge hosting some of the repositories
+#[derive(Debug, Clone)]
+pub struct RepositoryForge {
+    forge: Arc<dyn Forge>,
+    // Teams of the user on the forge, fetched once and shared by all the refreshes
+    teams: Arc<OnceCell<HashSet<String>>>,
+}
+
+impl RepositoryForge {
+    pub fn new(forge: Arc<dyn Forge>) -> Self {
+        Self {
+            forge,
+            teams: Default::default(),
+        }
+    }
 }
 
 #[derive(Debug, Default)]
@@ -39,11 +77,23 @@ struct AppState {
     active_panel: ActivePanel,
 
     review_prs: PullRequestsListState,
-    assignee_prs: PullRequestsListState,
+    authored_prs: PullRequestsListState,
+    assigned_prs: PullRequestsListState,
+    involved_prs: PullRequestsListState,
+    closed_prs: PullRequestsListState,
+    issues: PullRequestsListState,
+    notifications: PullRequestsListState,
 
     details: PullRequestsDetailsState,
 
-    loading_state: LoadingState,
+    repository_states: HashMap<RepoId, LoadingState>,
+    error_log: ErrorLogState,
+    workflow_runs: WorkflowRunsState,
+    // Failed repositories and when to fetch them again
+    retries: RetryScheduler,
+    loading_progress: LoadingProgress,
+    // Unix timestamp of the cached data being shown, until a refresh completes
+    stale_since: Option<i64>,
     show_help: bool,
 
     searching: bool,
@@ -51,62 +101,406 @@ struct AppState {
     cursor_position: Option<Position>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+/// A row of the panels
+// Most of the rows are prs, boxing them would only add an allocation per row
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+enum Item {
+    PullRequest(PullRequest),
+    Issue(Issue),
+    Notification(Notification),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 struct PullRequest {
     id: String,
     title: String,
     url: String,
-    repo: String,
+    repo: RepoId,
     body: String,
     author: String,
+    // The team (as `org/team`) through which our review was requested
+    requested_team: Option<String>,
+    // Our review status, only set for the prs in which we are involved as reviewers
+    review_status: Option<ReviewStatus>,
+    // Why the pr is listed, only set for the prs found by the search of the involved panel
+    involvements: Vec<Involvement>,
+    // Set once the pr is no longer open
+    closing: Option<Closing>,
     is_draft: bool,
-    mergeable: bool,
-    rebaseable: bool,
+    head_sha: String,
+    head_branch: String,
+    base_branch: String,
+    // Whether the head branch belongs to a fork of the repository
+    from_fork: bool,
+    labels: Vec<Label>,
+    // Requested and completed reviewers, with their latest state
+    reviewers: Vec<Reviewer>,
+    assignees: Vec<String>,
+    milestone: Option<String>,
+    created_at: Option<DateTime<Utc>>,
+    updated_at: Option<DateTime<Utc>>,
+    // Not every forge lists the size of the changes along with the prs
+    changes: Option<Changes>,
+    // Combined status of the checks of the head commit
+    checks: CheckStatus,
+}
+
+/// An issue assigned to us, these have no branches, checks nor merge information
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct Issue {
+    id: String,
+    title: String,
+    url: String,
+    repo: RepoId,
+    body: String,
+    author: String,
+    labels: Vec<Label>,
+    assignees: Vec<String>,
+    milestone: Option<String>,
+    created_at: Option<DateTime<Utc>>,
+    updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Label {
+    name: String,
+    // Hex color without the leading `#`, e.g. `d73a4a`
+    color: String,
+}
+
+/// An user or team (as `org/team`) reviewing the pr
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Reviewer {
+    login: String,
+    state: ReviewerState,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum ReviewerState {
+    // Pending, either never reviewed or requested again since
+    Requested,
+    Commented,
+    ChangesRequested,
+    Approved,
+    Dismissed,
+}
+
+/// A submitted review of the pr, shown in the Reviews tab
+#[derive(Debug, Clone, PartialEq)]
+struct Review {
+    author: String,
+    state: ReviewerState,
+    body: String,
+    submitted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct Changes {
+    additions: u64,
+    deletions: u64,
+    changed_files: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+enum Closing {
+    Merged { by: Option<String> },
+    Closed,
+}
+
+/// A thread of the inbox, only telling what happened. The pr or issue itself is in the other
+/// panels
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Notification {
+    thread_id: String,
+    // Notifications about a check suite or a release have no number
+    id: String,
+    title: String,
+    url: String,
+    repo: RepoId,
+    // e.g. `PullRequest`, `Issue`, `CheckSuite`
+    kind: String,
+    // e.g. `review_requested`, `mention`, `ci_activity`
+    reason: String,
+    unread: bool,
+    updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum Involvement {
+    Mentioned,
+    Commented,
+}
+
+/// Repository identified by its owner and name, as `owner/name` once serialized. Forges ignore
+/// the case of the names, and so does the comparison
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct RepoId {
+    owner: String,
+    name: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+enum CheckStatus {
+    Success,
+    Failure,
+    Pending,
+    #[default]
+    None,
+}
+
+/// A check run or commit status of the head commit of a pr
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Check {
+    name: String,
+    status: CheckStatus,
+    // Conclusion as reported by github, e.g. `success`, `cancelled`, `in_progress`
+    conclusion: String,
+    url: Option<String>,
+}
+
+/// Data fetched on demand, e.g. the checks of the selected pr
+#[derive(Debug, Default)]
+enum Fetch<T> {
+    #[default]
+    Loading,
+    Loaded(T),
+    Failed(String),
 }
 
+/// State of the local clone of a repository, from its `system_path`
 #[derive(Debug, Clone, PartialEq)]
+enum LocalState {
+    Loading,
+    // There is no `system_path` for the repository
+    Unconfigured,
+    Failed(String),
+    Status {
+        branch: String,
+        // Commits ahead and behind of the upstream, if there is one
+        ahead_behind: Option<(u64, u64)>,
+        changed_files: usize,
+    },
+}
+
+impl LocalState {
+    // Reads the output of `git status --porcelain=v2 --branch`
+    fn parse(output: &str) -> Self {
+        let mut branch = String::new();
+        let mut ahead_behind = None;
+        let mut changed_files = 0;
+
+        for line in output.lines() {
+            if let Some(head) = line.strip_prefix("# branch.head ") {
+                branch = head.to_string();
+            } else if let Some(ab) = line.strip_prefix("# branch.ab ") {
+                // e.g. `+1 -2`
+                let mut counts = ab
+                    .split_whitespace()
+                    .map(|count| count[1..].parse().unwrap_or(0));
+                ahead_behind = counts.next().zip(counts.next());
+            } else if !line.starts_with('#') {
+                changed_files += 1;
+            }
+        }
+
+        Self::Status {
+            branch,
+            ahead_behind,
+            changed_files,
+        }
+    }
+}
+
+/// What a pr looked like when its details were fetched, they are fetched again once it changes
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PrVersion {
+    updated_at: Option<DateTime<Utc>>,
+    head_sha: String,
+}
+
+/// Merge related information, only available through the details of a single pr
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct MergeInfo {
+    mergeable: Mergeability,
+    rebaseable: Mergeability,
+    // e.g. `clean`, `blocked`, `behind`, `dirty`
+    mergeable_state: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+enum Mergeability {
+    Yes,
+    No,
+    // Github computes it in the background, until then it is reported as `null`
+    #[default]
+    Computing,
+    // The forge doesn't tell, e.g. the rebaseability on gitea
+    Unknown,
+    // The details of the pr could not be fetched
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 struct Profile {
     id: String,
     login: String,
     name: String,
 }
 
+/// Status of the pr computed from our latest review
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum ReviewStatus {
+    Requested,
+    ReRequested,
+    Commented,
+    ChangesRequested,
+    Approved,
+    // Approved, but new commits have been pushed since
+    ApprovedStale,
+}
+
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd)]
 enum ActivePanel {
     #[default]
     PullRequestsToReview,
-    MyPullRequests,
+    Authored,
+    Assigned,
+    Involved,
+    RecentlyClosed,
+    Issues,
+    Notifications,
+}
+
+impl ActivePanel {
+    // Order in which the panels are shown and cycled through
+    const ALL: [ActivePanel; 7] = [
+        ActivePanel::PullRequestsToReview,
+        ActivePanel::Authored,
+        ActivePanel::Assigned,
+        ActivePanel::Involved,
+        ActivePanel::RecentlyClosed,
+        ActivePanel::Issues,
+        ActivePanel::Notifications,
+    ];
+
+    fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|p| *p == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            ActivePanel::PullRequestsToReview => "Review Requested",
+            ActivePanel::Authored => "Authored",
+            ActivePanel::Assigned => "Assigned",
+            ActivePanel::Involved => "Involved",
+            ActivePanel::RecentlyClosed => "Recently Closed",
+            ActivePanel::Issues => "Issues",
+            ActivePanel::Notifications => "Notifications",
+        }
+    }
 }
 
-#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 enum LoadingState {
-    #[default]
-    Idle,
     Loading,
     Loaded,
     Error(String),
 }
 
+/// Progress of the current refresh across all the configured repositories
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct LoadingProgress {
+    total_repositories: usize,
+    pending_repositories: usize,
+    fetched_pulls: usize,
+}
+
+/// The prs of a repository sorted into the panels they belong to
+#[derive(Debug, Default, PartialEq)]
+struct Classification {
+    review: Vec<PullRequest>,
+    authored: Vec<PullRequest>,
+    assigned: Vec<PullRequest>,
+    // Merged or closed prs we took part in
+    closed: Vec<PullRequest>,
+    // Open issues assigned to us
+    issues: Vec<Issue>,
+    // Profiles of the authors, to show them in the details
+    authors: Vec<Profile>,
+}
+
 const KEYBINDINGS: &[(&str, &str)] = &[
     ("↑↓, j/k", "Scroll List"),
     ("n", "Next repository"),
     ("p", "Previous repository"),
     ("Ctrl+d/u", "Scroll Details"),
+    ("t", "Switch Details tab"),
+    ("J/K", "Select check"),
+    ("O", "Open check in Browser"),
     ("TAB", "Switch Panel"),
     ("/", "Search"),
+    ("s", "Filter by review status"),
     ("f", "Refetch pulls"),
-    ("r", "Review PR"),
+    ("e", "Error log"),
+    ("a", "Workflow runs"),
+    ("m", "Mark notification read"),
+    ("D", "Mark notification done"),
+    ("U", "Unsubscribe from thread"),
+    ("Enter", "Go to notified PR"),
+    ("r", "Review PR / open repository locally"),
     ("o", "Open in Browser"),
     ("q", "Quit"),
 ];
 
 const DETAILS_SCROLL_INCREMENT: u16 = 3;
+const DEFAULT_MAX_PULL_REQUESTS: usize = 500;
+const DEFAULT_RECENTLY_CLOSED_DAYS: u32 = 7;
+// Attempts at fetching the pr details while github is still computing the mergeability
+const MERGE_INFO_ATTEMPTS: u32 = 5;
+const MERGE_INFO_RETRY_DELAY: Duration = Duration::from_secs(2);
 
 impl PullRequestWidget {
-    pub fn new(config: Config) -> Self {
+    pub fn new(config: Config, forges: HashMap<RepoId, RepositoryForge>, offline: bool) -> Self {
+        let cache_file = cache::cache_file(&config);
+        Self::with_cache_file(config, forges, offline, cache_file)
+    }
+
+    fn with_cache_file(
+        config: Config,
+        forges: HashMap<RepoId, RepositoryForge>,
+        offline: bool,
+        cache_file: Option<PathBuf>,
+    ) -> Self {
+        // Start from the data of the previous run while the refresh happens in the background
+        let mut state = AppState::default();
+        if let Some(snapshot) = cache_file.as_deref().and_then(Snapshot::load) {
+            let repositories = config.repositories.iter().map(RepoId::from).collect();
+            state.stale_since = Some(snapshot.saved_at);
+            snapshot.apply(&mut state, &repositories);
+            let pr = state.review_prs.find_selected().cloned();
+            let repository = state.review_prs.selected_repository().cloned();
+            state.details.set_item(pr);
+            state.details.set_repository(repository);
+        }
+
+        let mut names = HashSet::new();
+        let ambiguous_names = config
+            .repositories
+            .iter()
+            .filter(|r| !names.insert(&r.name))
+            .map(|r| r.name.clone())
+            .collect();
+
         Self {
             config,
-            state: Default::default(),
+            state: Arc::new(RwLock::new(state)),
+            forges,
+            ambiguous_names,
+            offline,
+            cache_file,
         }
     }
 
@@ -116,627 +510,3620 @@ impl PullRequestWidget {
 
     async fn fetch_pulls(
         app_state: Arc<RwLock<AppState>>,
-        username: Option<String>,
-        owner: String,
-        repo: String,
-    ) {
-        Self::set_loading_state(Arc::clone(&app_state), LoadingState::Loading);
-
-        let pulls = octocrab::instance()
-            .pulls(&owner, &repo)
-            .list()
-            .state(State::Open)
-            .direction(Direction::Descending)
-            .send()
-            .await;
-
-        match pulls {
-            Ok(page) => Self::on_load(app_state, username.as_ref(), &page, owner, repo).await,
-            Err(err) => Self::on_err(app_state, &err),
-        }
-    }
-
-    // On a load of prs received, pushes them in their corresponding map entry in the prs state
-    async fn on_load(
-        app_state: Arc<RwLock<AppState>>,
-        username: Option<&String>,
-        page: &Page<OctoPullRequest>,
-        owner: String,
-        repo: String,
+        forge: Arc<dyn Forge>,
+        config: Config,
+        repository: Repository,
+        teams: HashSet<String>,
+        cache_file: Option<PathBuf>,
     ) {
-        let mut prs_review = vec![];
-        let mut prs_assignee = vec![];
-        let mut reviews_set = JoinSet::new();
-        let mut author_set = JoinSet::new();
+        let Repository { owner, name, .. } = &repository;
+        let repo = RepoId::from(&repository);
+        let max_pull_requests = config
+            .max_pull_requests
+            .unwrap_or(DEFAULT_MAX_PULL_REQUESTS);
+        Self::set_loading_state(&app_state, &repo, LoadingState::Loading);
 
-        for pr in page.items.iter() {
-            // Transform the pr to our domain
-            let pr_to_push: PullRequest = pr.into();
+        // Walk through all the pages before touching the grouped view, this way the list is only
+        // replaced once with the complete set of prs
+        let mut pulls = vec![];
+        let mut after = None;
+        loop {
+            let page = forge
+                .pull_requests(owner, name, max_pull_requests - pulls.len(), after.take())
+                .await;
 
-            // Check if the author of this pr is already in cache or we need to fetch it
-            {
-                let state = app_state.read().unwrap();
-                //Add the author from the cached authors
-                if let Some(user) = &pr.user {
-                    // If the user is not in the cache request it's profile
-                    if !state.details.cached_authors.contains_key(&user.login) {
-                        let id = user.id;
-                        author_set.spawn(async move {
-                            let prof: Profile = octocrab::instance()
-                                .users_by_id(id)
-                                .profile()
-                                .await
-                                .unwrap()
-                                .into();
-                            prof
-                        });
-                    }
+            let page = match page {
+                Ok(page) => page,
+                Err(err) => {
+                    let snapshot = Self::on_err(&app_state, &repository, &err);
+                    return Self::save_snapshot(&app_state, snapshot, cache_file);
                 }
-            }
+            };
 
-            // If an username is set in the config, try and fetch reviews/assignees
-            if let Some(username) = username {
-                // Check if we are assignee
-                if let Some(assignees) = &pr.assignees {
-                    if assignees.iter().any(|e| e.login == *username) {
-                        prs_assignee.push(pr_to_push);
-                        // Would be very weird to be assignee and reviewer
-                        // as of now we're gonna skip if we are assignee maybe i'll come back to
-                        // this decision at some poit
-                        continue;
-                    }
-                }
+            Self::add_fetched_pulls(&app_state, page.items.len());
+            pulls.extend(page.items);
 
-                // Check if we are reviewers
-                if let Some(reviewers) = &pr.requested_reviewers {
-                    // If the reviewer is requested and has not yet been reviewed push the pr
-                    if reviewers.iter().any(|e| e.login == *username) {
-                        prs_review.push(pr_to_push);
-                        // Go to next iteration
-                        continue;
-                    }
-                }
+            if page.next.is_none() || pulls.len() >= max_pull_requests {
+                break;
+            }
+            after = page.next;
+        }
 
-                // Otherwise we might have reviewed already but the pr is still open
-                // For reference of the doc:
-                // Gets the users or teams whose review is requested for a pull request.
-                // Once a requested reviewer submits a review, they are no longer considered a requested reviewer.
-
-                // Therefore we are going to request another endpoint to make sure we are not
-                // reviewers of the pr (assuming if we have submited a review we are a reviewer)
-                let owner = owner.clone();
-                let repo = repo.clone();
-                let number = pr.number;
-                reviews_set.spawn(async move {
-                    (
-                        octocrab::instance()
-                            .pulls(owner, repo)
-                            .list_reviews(number)
-                            .send()
-                            .await,
-                        pr_to_push,
-                    )
-                });
+        let recently_closed_days = config
+            .recently_closed_days
+            .unwrap_or(DEFAULT_RECENTLY_CLOSED_DAYS);
+        let since = chrono::Utc::now() - chrono::Duration::days(recently_closed_days.into());
+        // The open prs are still worth showing without the closed ones, which are kept as they
+        // were until a refresh manages to list them
+        let closed_failed = match forge
+            .closed_pull_requests(owner, name, since, max_pull_requests)
+            .await
+        {
+            Ok(closed) => {
+                pulls.extend(closed);
+                false
             }
+            Err(err) => {
+                app_state.write().unwrap().error_log.push(
+                    &repo.to_string(),
+                    format!("Could not list the closed prs: {}", err),
+                );
+                true
+            }
+        };
+
+        // The username might not be the same on every forge
+        let username = repository.username.as_ref().or(config.username.as_ref());
+        let mut classification = classify(&pulls, username.map(String::as_str), &teams);
+        if closed_failed {
+            classification.closed =
+                Self::loaded_group(&app_state, &repo, |s| &s.closed_prs, Item::pull_request);
         }
 
-        for (reviews, pr) in reviews_set.join_all().await {
-            match reviews {
-                Ok(page) => {
-                    if page.items.iter().any(|r| {
-                        if let Some(u) = &r.user {
-                            if let Some(username) = username {
-                                return u.login == *username;
-                            }
-                        }
-                        false
-                    }) {
-                        // If found append the pr to the reviewwers
-                        prs_review.push(pr);
-                    }
+        // Same as the closed prs, the issues are kept as they were when they can't be listed
+        if let Some(username) = username {
+            match forge.assigned_issues(owner, name, username).await {
+                Ok(issues) => classification.issues = issues.iter().map(Into::into).collect(),
+                Err(err) => {
+                    app_state.write().unwrap().error_log.push(
+                        &repo.to_string(),
+                        format!("Could not list the assigned issues: {}", err),
+                    );
+                    classification.issues =
+                        Self::loaded_group(&app_state, &repo, |s| &s.issues, Item::issue);
                 }
-                // If error set it and return
-                Err(err) => return Self::on_err(app_state, &err),
             }
         }
 
-        let mut authors_to_add = vec![];
-        for author in author_set.join_all().await {
-            authors_to_add.push(author);
+        let snapshot = Self::on_load(&app_state, classification, repo);
+        Self::save_snapshot(&app_state, snapshot, cache_file)
+    }
+
+    // Prs of the repository currently listed in a panel
+    fn loaded_group<T: Clone>(
+        app_state: &Arc<RwLock<AppState>>,
+        repo: &RepoId,
+        panel: impl Fn(&AppState) -> &PullRequestsListState,
+        item: impl Fn(&Item) -> Option<&T>,
+    ) -> Vec<T> {
+        let state = app_state.read().unwrap();
+        panel(&state)
+            .grouped_prs
+            .get(repo)
+            .into_iter()
+            .flatten()
+            .filter_map(item)
+            .cloned()
+            .collect()
+    }
+
+    // Teams of the user on the forge, listed once and shared by all the refreshes. Not being able
+    // to list them (e.g. missing `read:org` scope) should not prevent the prs from loading, we
+    // just won't know about team review requests until a refresh manages to list them, failures
+    // are not kept
+    async fn forge_teams(
+        app_state: &Arc<RwLock<AppState>>,
+        forge: &RepositoryForge,
+        ignored_teams: &[String],
+    ) -> HashSet<String> {
+        let teams = forge
+            .teams
+            .get_or_try_init(|| Self::fetch_teams(forge.forge.as_ref(), ignored_teams))
+            .await;
+
+        match teams {
+            Ok(teams) => teams.clone(),
+            Err(err) => {
+                app_state
+                    .write()
+                    .unwrap()
+                    .error_log
+                    .push("teams", format!("Could not list the teams: {}", err));
+                HashSet::new()
+            }
         }
+    }
+
+    async fn fetch_teams(
+        forge: &dyn Forge,
+        ignored_teams: &[String],
+    ) -> Result<HashSet<String>, forge::Error> {
+        Ok(forge
+            .teams()
+            .await?
+            .into_iter()
+            .filter(|t| !ignored_teams.iter().any(|i| i.eq_ignore_ascii_case(t)))
+            .collect())
+    }
+
+    // On a load of prs received, pushes them in their corresponding map entry in the prs state.
+    // Returns the snapshot to cache when the refresh is complete
+    fn on_load(
+        app_state: &Arc<RwLock<AppState>>,
+        classification: Classification,
+        repo: RepoId,
+    ) -> Option<Snapshot> {
+        let Classification {
+            review,
+            authored,
+            assigned,
+            closed,
+            issues,
+            authors,
+        } = classification;
 
         let mut state = app_state.write().unwrap();
 
         // Push all the authors in the global author cache
-        authors_to_add.into_iter().for_each(|a| {
+        authors.into_iter().for_each(|a| {
             state.details.cached_authors.insert(a.login.clone(), a);
         });
 
-        // handle review prs
-        if !prs_review.is_empty() {
-            // Get  the review repo and clear previous entries
-            let review_repo = state
-                .review_prs
-                .grouped_prs
-                .entry(repo.clone())
-                .or_default();
+        state.review_prs.set_group(&repo, review);
+        state.authored_prs.set_group(&repo, authored);
+        state.assigned_prs.set_group(&repo, assigned);
+        state.closed_prs.set_group(&repo, closed);
+        state.issues.set_group(&repo, issues);
 
-            review_repo.clear();
-            review_repo.extend(prs_review);
-        } else {
-            let _ = state.review_prs.grouped_prs.remove(&repo);
-        }
-        // Update the view
-        state.review_prs.update_view();
+        state.retries.on_success(&repo);
+        state.repository_states.insert(repo, LoadingState::Loaded);
+        Self::finish_repository(&mut state)
+    }
 
-        // If the map is not empty, and theres not a previously selected state
-        if !state.review_prs.grouped_prs.is_empty()
-            && state.review_prs.table_state.selected().is_none()
-        {
-            state.review_prs.table_state.select(Some(0));
-        }
+    // Keeps the previously loaded prs of the repository, the error is logged and shown on it
+    // along with when the repository will be fetched again
+    fn on_err(
+        app_state: &Arc<RwLock<AppState>>,
+        repository: &Repository,
+        err: &forge::Error,
+    ) -> Option<Snapshot> {
+        let mut state = app_state.write().unwrap();
+        let repo = RepoId::from(repository);
+        let message = state.retries.on_failure(&repo, err).describe(err);
+        state.error_log.push(&repo.to_string(), message.clone());
+        state
+            .repository_states
+            .insert(repo, LoadingState::Error(message));
+        Self::finish_repository(&mut state)
+    }
 
-        if !prs_assignee.is_empty() {
-            // Now do the same for assigned
-            let assignee_repo = state.assignee_prs.grouped_prs.entry(repo).or_default();
-            assignee_repo.clear();
-            assignee_repo.extend(prs_assignee);
-        } else {
-            let _ = state.assignee_prs.grouped_prs.remove(&repo);
-        }
-        // Update the view
-        state.assignee_prs.update_view();
+    fn set_loading_state(app_state: &Arc<RwLock<AppState>>, repo: &RepoId, state: LoadingState) {
+        app_state
+            .write()
+            .unwrap()
+            .repository_states
+            .insert(repo.clone(), state);
+    }
 
-        // If the map is not empty, and theres not a previously selected state
-        if !state.assignee_prs.grouped_prs.is_empty()
-            && state.assignee_prs.table_state.selected().is_none()
-        {
-            state.assignee_prs.table_state.select(Some(0));
+    fn add_fetched_pulls(app_state: &Arc<RwLock<AppState>>, count: usize) {
+        app_state.write().unwrap().loading_progress.fetched_pulls += count;
+    }
+
+    // Marks a repository as done, the refresh is loaded once every repository has finished.
+    // Returns the snapshot to cache when it was the last one
+    fn finish_repository(state: &mut AppState) -> Option<Snapshot> {
+        let progress = &mut state.loading_progress;
+        progress.pending_repositories = progress.pending_repositories.saturating_sub(1);
+        if progress.pending_repositories > 0 {
+            return None;
         }
 
-        state.loading_state = LoadingState::Loaded;
+        // Only consider the data fresh if every repository could be fetched
+        if !state.has_errors() {
+            state.stale_since = None;
+        }
+        Some(Snapshot::from_state(state))
     }
 
-    fn on_err(app_state: Arc<RwLock<AppState>>, err: &octocrab::Error) {
-        let error_message = match err {
-            octocrab::Error::GitHub { source, .. } => source.message.clone(),
-            // Fallback to display
-            _ => format!("{}", err),
+    // Writes the snapshot in the background, the state must not be locked while on the disk
+    fn save_snapshot(
+        app_state: &Arc<RwLock<AppState>>,
+        snapshot: Option<Snapshot>,
+        cache_file: Option<PathBuf>,
+    ) {
+        let (Some(snapshot), Some(cache_file)) = (snapshot, cache_file) else {
+            return;
         };
 
-        Self::set_loading_state(app_state, LoadingState::Error(error_message));
-    }
-
-    fn set_loading_state(app_state: Arc<RwLock<AppState>>, state: LoadingState) {
-        app_state.write().unwrap().loading_state = state;
+        let app_state = app_state.clone();
+        tokio::spawn(async move {
+            let result = tokio::task::spawn_blocking(move || snapshot.save(&cache_file))
+                .await
+                .map_err(|err| err.to_string())
+                .and_then(|saved| saved.map_err(|err| format!("{:#}", err)));
+            // Failing to write the cache only means the next startup won't be instant
+            if let Err(err) = result {
+                app_state
+                    .write()
+                    .unwrap()
+                    .error_log
+                    .push("cache", format!("Could not save the cache: {}", err));
+            }
+        });
     }
 
-    fn get_active_prs_state_mut(state: &mut AppState) -> &mut PullRequestsListState {
-        match state.active_panel {
-            ActivePanel::PullRequestsToReview => &mut state.review_prs,
-            ActivePanel::MyPullRequests => &mut state.assignee_prs,
+    /// Shows the row in the details, fetching the merge information of a pr if it is not known
+    /// yet. Without a row the selected one is a group header, which shows an overview of its
+    /// repository
+    fn select_item(&self, state: &mut AppState, item: Option<Item>) {
+        if let Some(Item::PullRequest(pr)) = &item {
+            state.details.track_version(pr);
+            self.ensure_merge_info(state, pr);
+            self.ensure_tab(state, pr);
         }
-    }
-}
 
-// Eventful functions
-impl PullRequestWidget {
-    pub fn scroll_down(&self) {
-        let mut state = self.state.write().unwrap();
-        let prs_state = Self::get_active_prs_state_mut(&mut state);
-        prs_state.scroll_down();
+        let repository = match item {
+            Some(_) => None,
+            None => state.active_prs_state().selected_repository().cloned(),
+        };
+        if let Some(repository) = &repository {
+            self.ensure_overview(state, repository);
+        }
 
-        // If a pr is selected make it available in the details
-        let pr = prs_state.find_selected().cloned();
-        state.details.set_pull_request(pr);
+        state.details.set_item(item);
+        state.details.set_repository(repository);
     }
 
-    pub fn scroll_up(&self) {
-        let mut state = self.state.write().unwrap();
-        let prs_state = Self::get_active_prs_state_mut(&mut state);
-        prs_state.scroll_up();
-        let pr = prs_state.find_selected().cloned();
-        state.details.set_pull_request(pr);
+    fn forge_of(&self, repo: &RepoId) -> Option<Arc<dyn Forge>> {
+        self.forges.get(repo).map(|f| f.forge.clone())
     }
 
-    pub fn jump_up(&self) {
-        let mut state = self.state.write().unwrap();
-        let prs_state = Self::get_active_prs_state_mut(&mut state);
-        prs_state.jump_up();
-        let pr = prs_state.find_selected().cloned();
-        state.details.set_pull_request(pr);
+    fn config_repository(&self, repo: &RepoId) -> Option<&Repository> {
+        self.config
+            .repositories
+            .iter()
+            .find(|r| RepoId::from(*r) == *repo)
     }
 
-    pub fn jump_down(&self) {
-        let mut state = self.state.write().unwrap();
-        let prs_state = Self::get_active_prs_state_mut(&mut state);
-        prs_state.jump_down();
-        let pr = prs_state.find_selected().cloned();
-        state.details.set_pull_request(pr);
+    /// Fetches the overview of the repository and the state of its local clone, unless known
+    fn ensure_overview(&self, state: &mut AppState, repo: &RepoId) {
+        // A stale overview is still shown while it is fetched again
+        let stale = state.details.stale_overviews.remove(repo);
+        if !self.offline
+            && (stale || !state.details.overviews.contains_key(repo))
+            && let Some(forge) = self.forges.get(repo)
+        {
+            // `None` marks the overview as loading
+            state.details.overviews.entry(repo.clone()).or_insert(None);
+            tokio::spawn(Self::fetch_overview(
+                self.state.clone(),
+                forge.forge.clone(),
+                repo.clone(),
+            ));
+        }
+
+        if !state.details.local_states.contains_key(repo) {
+            match self
+                .config_repository(repo)
+                .and_then(|r| r.system_path.clone())
+            {
+                Some(path) => {
+                    state
+                        .details
+                        .local_states
+                        .insert(repo.clone(), LocalState::Loading);
+                    tokio::spawn(Self::fetch_local_state(
+                        self.state.clone(),
+                        repo.clone(),
+                        path,
+                    ));
+                }
+                None => {
+                    state
+                        .details
+                        .local_states
+                        .insert(repo.clone(), LocalState::Unconfigured);
+                }
+            }
+        }
     }
 
-    pub fn next_repository(&self) {
-        let mut state = self.state.write().unwrap();
-        let prs_state = Self::get_active_prs_state_mut(&mut state);
+    async fn fetch_overview(app_state: Arc<RwLock<AppState>>, forge: Arc<dyn Forge>, repo: RepoId) {
+        let overview = forge.overview(&repo.owner, &repo.name).await;
 
-        prs_state.next_repository();
-        let pr = prs_state.find_selected().cloned();
-        state.details.set_pull_request(pr);
+        let mut state = app_state.write().unwrap();
+        match overview {
+            Ok(overview) => {
+                state.details.overviews.insert(repo, Some(overview));
+            }
+            Err(err) => {
+                state.error_log.push(&repo.to_string(), err.to_string());
+                // Forget about it so selecting the header again retries
+                state.details.overviews.remove(&repo);
+            }
+        }
     }
 
-    pub fn previous_repository(&self) {
-        let mut state = self.state.write().unwrap();
-        let prs_state = Self::get_active_prs_state_mut(&mut state);
+    async fn fetch_local_state(app_state: Arc<RwLock<AppState>>, repo: RepoId, path: String) {
+        let output = tokio::process::Command::new("git")
+            .arg("-C")
+            .arg(&path)
+            .args(["status", "--porcelain=v2", "--branch"])
+            .output()
+            .await;
 
-        prs_state.previous_repository();
-        let pr = prs_state.find_selected().cloned();
-        state.details.set_pull_request(pr);
-    }
+        let local_state = match output {
+            Ok(output) if output.status.success() => {
+                LocalState::parse(&String::from_utf8_lossy(&output.stdout))
+            }
+            // e.g. the path doesn't exist or is not a git repository
+            Ok(output) => LocalState::Failed(
+                String::from_utf8_lossy(&output.stderr)
+                    .lines()
+                    .next()
+                    .unwrap_or("git status failed")
+                    .to_string(),
+            ),
+            Err(err) => LocalState::Failed(err.to_string()),
+        };
 
-    pub fn scroll_details_down(&self) {
-        let mut state = self.state.write().unwrap();
-        if state.details.pr_details.is_some() {
-            let scroll = state
-                .details
-                .body_scroll
-                .saturating_add(DETAILS_SCROLL_INCREMENT);
+        app_state
+            .write()
+            .unwrap()
+            .details
+            .local_states
+            .insert(repo, local_state);
+    }
 
-            state.details.body_scroll = scroll;
-            state.details.scrollbar_state = state.details.scrollbar_state.position(scroll as usize);
+    // Fetches what the active details tab shows, if it is not known yet
+    fn ensure_tab(&self, state: &mut AppState, pr: &PullRequest) {
+        if state.details.is_checks_tab() {
+            self.ensure_checks(state, pr);
+        } else if state.details.is_reviews_tab() {
+            self.ensure_reviews(state, pr);
+        } else if state.details.is_timeline_tab() {
+            self.ensure_timeline(state, pr);
         }
     }
 
-    pub fn scroll_details_up(&self) {
-        let mut state = self.state.write().unwrap();
-        if state.details.pr_details.is_some() {
-            let scroll = state
+    fn ensure_timeline(&self, state: &mut AppState, pr: &PullRequest) {
+        if !self.offline
+            && !state.details.timelines.contains_key(&pr.url)
+            && let Some(forge) = self.forge_of(&pr.repo)
+        {
+            state
                 .details
-                .body_scroll
-                .saturating_sub(DETAILS_SCROLL_INCREMENT);
-
-            state.details.body_scroll = scroll;
-            state.details.scrollbar_state = state.details.scrollbar_state.position(scroll as usize);
+                .timelines
+                .insert(pr.url.clone(), Fetch::Loading);
+            tokio::spawn(Self::fetch_timeline(self.state.clone(), forge, pr.clone()));
         }
     }
 
-    pub fn next_tab(&self) {
-        let mut state = self.state.write().unwrap();
-        let prs_state = match state.active_panel {
-            ActivePanel::PullRequestsToReview => {
-                state.active_panel = ActivePanel::MyPullRequests;
-                &mut state.assignee_prs
-            }
-            ActivePanel::MyPullRequests => {
-                state.active_panel = ActivePanel::PullRequestsToReview;
-                &mut state.review_prs
-            }
+    async fn fetch_timeline(
+        app_state: Arc<RwLock<AppState>>,
+        forge: Arc<dyn Forge>,
+        pr: PullRequest,
+    ) {
+        let timeline = match pr.id.parse() {
+            Ok(number) => forge.timeline(&pr.repo.owner, &pr.repo.name, number).await,
+            Err(_) => Err(forge::Error::Other(format!("Invalid pr number {}", pr.id))),
         };
 
-        let pr = prs_state.find_selected().cloned();
-        state.details.pr_details = pr;
-    }
+        // Rendered before locking the state, the markdown of the comments takes a while
+        let timeline = timeline.map(|timeline| PullRequestsDetailsState::timeline_lines(&timeline));
 
-    pub fn open(&self) {
-        let state = self.state.read().unwrap();
-        let prs_state = match state.active_panel {
-            ActivePanel::PullRequestsToReview => &state.review_prs,
-            ActivePanel::MyPullRequests => &state.assignee_prs,
+        let mut state = app_state.write().unwrap();
+        let timeline = match timeline {
+            Ok(text) => Fetch::Loaded(text),
+            // Shown as failed until the next refresh fetches it again
+            Err(err) => {
+                state.error_log.push(
+                    &pr.repo.to_string(),
+                    format!("Timeline of #{}: {}", pr.id, err),
+                );
+                Fetch::Failed(err.to_string())
+            }
         };
+        state.details.timelines.insert(pr.url, timeline);
+    }
 
-        if let Some(pr) = prs_state.find_selected() {
-            open::that(pr.url.clone()).unwrap();
+    fn ensure_reviews(&self, state: &mut AppState, pr: &PullRequest) {
+        if !self.offline
+            && !state.details.reviews.contains_key(&pr.url)
+            && let Some(forge) = self.forge_of(&pr.repo)
+        {
+            state.details.reviews.insert(pr.url.clone(), Fetch::Loading);
+            tokio::spawn(Self::fetch_reviews(self.state.clone(), forge, pr.clone()));
         }
     }
 
-    pub fn review(&self) {
-        let state = self.state.read().unwrap();
+    async fn fetch_reviews(
+        app_state: Arc<RwLock<AppState>>,
+        forge: Arc<dyn Forge>,
+        pr: PullRequest,
+    ) {
+        let reviews = match pr.id.parse() {
+            Ok(number) => forge.reviews(&pr.repo.owner, &pr.repo.name, number).await,
+            Err(_) => Err(forge::Error::Other(format!("Invalid pr number {}", pr.id))),
+        };
 
-        // Only available with reviewable prs
-        if let ActivePanel::PullRequestsToReview = state.active_panel {
-            if let Some(pr) = state.review_prs.find_selected() {
-                // TODO: handle missing paths or config repo
-                if let Some(config_repo) =
-                    self.config.repositories.iter().find(|r| r.name == pr.repo)
-                {
-                    let cmd = self.config.command.clone().unwrap_or_else(|| {
-                        std::env::var("TERMINAL").unwrap_or_else(|_| "ghostty".to_string())
-                    });
-
-                    if let Some(path) = &config_repo.system_path {
-                        let args = self.config.command_args.clone();
-                        let path = path.clone();
-                        std::thread::spawn(move || {
-                            // First change to the target directory
-                            std::env::set_current_dir(&path).unwrap_or_else(|e| {
-                                eprintln!("Failed to change directory: {}", e);
-                            });
-
-                            let mut cmd = Command::new(cmd);
-                            for arg in args.iter() {
-                                cmd.arg(arg);
-                            }
-                            cmd.output()
-                        });
-                    }
-                }
+        // Rendered before locking the state, the markdown of the bodies takes a while
+        let reviews = reviews.map(|reviews| {
+            let reviews: Vec<Review> = reviews
+                .iter()
+                // Pending reviews are drafts only their author can see
+                .filter(|r| r.state != forge::ReviewState::Pending)
+                .map(Review::from)
+                .collect();
+            PullRequestsDetailsState::review_lines(&reviews)
+        });
+
+        let mut state = app_state.write().unwrap();
+        let reviews = match reviews {
+            Ok(text) => Fetch::Loaded(text),
+            // Shown as failed until the next refresh fetches them again
+            Err(err) => {
+                state.error_log.push(
+                    &pr.repo.to_string(),
+                    format!("Reviews of #{}: {}", pr.id, err),
+                );
+                Fetch::Failed(err.to_string())
             }
-        }
+        };
+        state.details.reviews.insert(pr.url, reviews);
     }
 
-    pub fn toggle_help(&self) {
+    fn ensure_checks(&self, state: &mut AppState, pr: &PullRequest) {
+        if !self.offline
+            && !state.details.checks.contains_key(&pr.url)
+            && let Some(forge) = self.forge_of(&pr.repo)
+        {
+            state.details.checks.insert(pr.url.clone(), Fetch::Loading);
+            tokio::spawn(Self::fetch_checks(self.state.clone(), forge, pr.clone()));
+        }
+    }
+
+    async fn fetch_checks(
+        app_state: Arc<RwLock<AppState>>,
+        forge: Arc<dyn Forge>,
+        pr: PullRequest,
+    ) {
+        let checks = forge
+            .checks(&pr.repo.owner, &pr.repo.name, &pr.head_sha)
+            .await;
+
+        let mut state = app_state.write().unwrap();
+        let checks = match checks {
+            Ok(checks) => Fetch::Loaded(checks.iter().map(Check::from).collect()),
+            // Shown as failed until the next refresh fetches them again
+            Err(err) => {
+                state.error_log.push(
+                    &pr.repo.to_string(),
+                    format!("Checks of #{}: {}", pr.id, err),
+                );
+                Fetch::Failed(err.to_string())
+            }
+        };
+        state.details.checks.insert(pr.url, checks);
+    }
+
+    fn ensure_merge_info(&self, state: &mut AppState, pr: &PullRequest) {
+        // There is nothing left to merge once the pr is closed. The notifications only tell what
+        // happened, the pr itself is in the other panels
+        if !self.offline
+            && pr.closing.is_none()
+            && !state.details.merge_info.contains_key(&pr.url)
+            && let Some(forge) = self.forge_of(&pr.repo)
+        {
+            // Mark it as computing right away so we don't spawn the same fetch twice
+            state
+                .details
+                .merge_info
+                .insert(pr.url.clone(), MergeInfo::default());
+            tokio::spawn(Self::fetch_merge_info(
+                self.state.clone(),
+                forge,
+                pr.clone(),
+            ));
+        }
+    }
+
+    async fn fetch_merge_info(
+        app_state: Arc<RwLock<AppState>>,
+        forge: Arc<dyn Forge>,
+        pr: PullRequest,
+    ) {
+        let number = match pr.id.parse() {
+            Ok(number) => number,
+            Err(_) => {
+                let err = format!("Invalid pull request number `{}`", pr.id);
+                return Self::on_merge_info_err(&app_state, &pr, err);
+            }
+        };
+
+        for attempt in 1..=MERGE_INFO_ATTEMPTS {
+            match forge.details(&pr.repo.owner, &pr.repo.name, number).await {
+                Ok(details) => {
+                    let computed = details.mergeable.is_some();
+                    app_state
+                        .write()
+                        .unwrap()
+                        .details
+                        .merge_info
+                        .insert(pr.url.clone(), details.into());
+
+                    if computed {
+                        return;
+                    }
+                }
+                Err(err) => return Self::on_merge_info_err(&app_state, &pr, err.to_string()),
+            }
+
+            if attempt < MERGE_INFO_ATTEMPTS {
+                tokio::time::sleep(MERGE_INFO_RETRY_DELAY * attempt).await;
+            }
+        }
+
+        // The forge never finished computing it, forget about it so selecting the pr again retries
+        app_state
+            .write()
+            .unwrap()
+            .details
+            .merge_info
+            .remove(&pr.url);
+    }
+
+    // Shows the merge information as failed until the next refresh, which fetches it again
+    fn on_merge_info_err(app_state: &Arc<RwLock<AppState>>, pr: &PullRequest, err: String) {
+        let mut state = app_state.write().unwrap();
+        state.error_log.push(
+            &pr.repo.to_string(),
+            format!("Merge information of #{}: {}", pr.id, err),
+        );
+        state
+            .details
+            .merge_info
+            .insert(pr.url.clone(), MergeInfo::failed());
+    }
+
+    fn get_active_prs_state_mut(state: &mut AppState) -> &mut PullRequestsListState {
+        match state.active_panel {
+            ActivePanel::PullRequestsToReview => &mut state.review_prs,
+            ActivePanel::Authored => &mut state.authored_prs,
+            ActivePanel::Assigned => &mut state.assigned_prs,
+            ActivePanel::Involved => &mut state.involved_prs,
+            ActivePanel::RecentlyClosed => &mut state.closed_prs,
+            ActivePanel::Issues => &mut state.issues,
+            ActivePanel::Notifications => &mut state.notifications,
+        }
+    }
+}
+
+/// Sorts the prs into the panels depending on how the user is involved in them
+fn classify(
+    pulls: &[forge::PullRequest],
+    username: Option<&str>,
+    teams: &HashSet<String>,
+) -> Classification {
+    let mut classification = Classification::default();
+
+    for pr in pulls.iter() {
+        // Transform the pr to our domain
+        let pr_to_push: PullRequest = pr.into();
+
+        // The author profile comes along with the pr, keep it for the details
+        if let Some(author) = &pr.author
+            && author.id.is_some()
+        {
+            classification.authors.push(Profile::from(author));
+        }
+
+        // If an username is set in the config, classify the pr
+        if let Some(username) = username {
+            // Closed prs only matter if we took part in them
+            if pr.state != forge::PullRequestState::Open {
+                if pr.is_authored_by(username)
+                    || pr.is_assigned_to(username)
+                    || pr.is_review_requested(username)
+                    || pr.requested_team(teams).is_some()
+                    || pr.latest_review_by(username).is_some()
+                {
+                    classification.closed.push(pr_to_push);
+                }
+                continue;
+            }
+
+            // A pr can be in several panels, e.g. we can be both an assignee and a reviewer
+            if pr.is_authored_by(username) {
+                classification.authored.push(pr_to_push.clone());
+            }
+            if pr.is_assigned_to(username) {
+                classification.assigned.push(pr_to_push.clone());
+            }
+
+            // Review requested to one of our teams (e.g. through CODEOWNERS)
+            let requested_team = if pr.is_review_requested(username) {
+                None
+            } else {
+                pr.requested_team(teams)
+            };
+
+            if let Some(status) = ReviewStatus::compute(pr, username, requested_team.is_some()) {
+                classification.review.push(PullRequest {
+                    requested_team,
+                    review_status: Some(status),
+                    ..pr_to_push
+                });
+            }
+        }
+    }
+
+    classification
+}
+
+// Eventful functions
+impl PullRequestWidget {
+    pub fn scroll_down(&self) {
+        let mut state = self.state.write().unwrap();
+        let prs_state = Self::get_active_prs_state_mut(&mut state);
+        prs_state.scroll_down();
+
+        // If a pr is selected make it available in the details
+        let pr = prs_state.find_selected().cloned();
+        self.select_item(&mut state, pr);
+    }
+
+    pub fn scroll_up(&self) {
+        let mut state = self.state.write().unwrap();
+        let prs_state = Self::get_active_prs_state_mut(&mut state);
+        prs_state.scroll_up();
+        let pr = prs_state.find_selected().cloned();
+        self.select_item(&mut state, pr);
+    }
+
+    pub fn jump_up(&self) {
+        let mut state = self.state.write().unwrap();
+        let prs_state = Self::get_active_prs_state_mut(&mut state);
+        prs_state.jump_up();
+        let pr = prs_state.find_selected().cloned();
+        self.select_item(&mut state, pr);
+    }
+
+    pub fn jump_down(&self) {
+        let mut state = self.state.write().unwrap();
+        let prs_state = Self::get_active_prs_state_mut(&mut state);
+        prs_state.jump_down();
+        let pr = prs_state.find_selected().cloned();
+        self.select_item(&mut state, pr);
+    }
+
+    pub fn next_repository(&self) {
+        let mut state = self.state.write().unwrap();
+        let prs_state = Self::get_active_prs_state_mut(&mut state);
+
+        prs_state.next_repository();
+        let pr = prs_state.find_selected().cloned();
+        self.select_item(&mut state, pr);
+    }
+
+    pub fn previous_repository(&self) {
+        let mut state = self.state.write().unwrap();
+        let prs_state = Self::get_active_prs_state_mut(&mut state);
+
+        prs_state.previous_repository();
+        let pr = prs_state.find_selected().cloned();
+        self.select_item(&mut state, pr);
+    }
+
+    pub fn scroll_details_down(&self) {
+        let mut state = self.state.write().unwrap();
+        if state.details.pr_details.is_some() {
+            let scroll = state
+                .details
+                .body_scroll
+                .saturating_add(DETAILS_SCROLL_INCREMENT);
+
+            state.details.body_scroll = scroll;
+            state.details.scrollbar_state = state.details.scrollbar_state.position(scroll as usize);
+        }
+    }
+
+    pub fn scroll_details_up(&self) {
+        let mut state = self.state.write().unwrap();
+        if state.details.pr_details.is_some() {
+            let scroll = state
+                .details
+                .body_scroll
+                .saturating_sub(DETAILS_SCROLL_INCREMENT);
+
+            state.details.body_scroll = scroll;
+            state.details.scrollbar_state = state.details.scrollbar_state.position(scroll as usize);
+        }
+    }
+
+    pub fn next_tab(&self) {
+        let mut state = self.state.write().unwrap();
+        state.active_panel = state.active_panel.next();
+        let prs_state = Self::get_active_prs_state_mut(&mut state);
+
+        let pr = prs_state.find_selected().cloned();
+        self.select_item(&mut state, pr);
+    }
+
+    pub fn next_details_tab(&self) {
+        let mut state = self.state.write().unwrap();
+        state.details.next_tab();
+
+        if let Some(Item::PullRequest(pr)) = state.details.pr_details.clone() {
+            self.ensure_tab(&mut state, &pr);
+        }
+    }
+
+    pub fn select_next_check(&self) {
+        self.state.write().unwrap().details.select_next_check();
+    }
+
+    pub fn select_previous_check(&self) {
+        self.state.write().unwrap().details.select_previous_check();
+    }
+
+    pub fn open_selected_check(&self) {
+        let url = {
+            let state = self.state.read().unwrap();
+            state.details.selected_check().and_then(|c| c.url.clone())
+        };
+        if let Some(url) = url {
+            self.open_url(&url);
+        }
+    }
+
+    pub fn open(&self) {
+        let url = {
+            let state = self.state.read().unwrap();
+            let prs_state = state.active_prs_state();
+            match prs_state.find_selected() {
+                Some(item) => Some(item.url().to_string()),
+                None => prs_state
+                    .selected_repository()
+                    .and_then(|repo| state.details.overviews.get(repo))
+                    .and_then(|overview| Some(overview.as_ref()?.url.clone())),
+            }
+        };
+        if let Some(url) = url {
+            self.open_url(&url);
+        }
+    }
+
+    // Opening fails when there is nothing to open it with, e.g. over ssh
+    fn open_url(&self, url: &str) {
+        if let Err(err) = open::that(url) {
+            self.state
+                .write()
+                .unwrap()
+                .error_log
+                .push("open", format!("Could not open {}: {}", url, err));
+        }
+    }
+
+    /// Launches the configured command in the local path of the repository, either the one of the
+    /// selected reviewable pr or the one of the selected group header
+    pub fn review(&self) {
+        let state = self.state.read().unwrap();
+
+        let repo = match state.active_prs_state().selected_repository() {
+            Some(repo) => Some(repo),
+            // Only available with reviewable prs
+            None if state.active_panel == ActivePanel::PullRequestsToReview => {
+                state.review_prs.find_selected().map(Item::repo)
+            }
+            None => None,
+        };
+
+        // TODO: handle missing paths or config repo
+        if let Some(config_repo) = repo.and_then(|repo| self.config_repository(repo)) {
+            let cmd = self.config.command.clone().unwrap_or_else(|| {
+                std::env::var("TERMINAL").unwrap_or_else(|_| "ghostty".to_string())
+            });
+
+            if let Some(path) = &config_repo.system_path {
+                let args = self.config.command_args.clone();
+                let path = path.clone();
+                let app_state = self.state.clone();
+                std::thread::spawn(move || {
+                    // Run in the target directory, without moving the whole process there
+                    let output = Command::new(&cmd).args(&args).current_dir(&path).output();
+                    if let Err(err) = output {
+                        app_state.write().unwrap().error_log.push(
+                            "command",
+                            format!("Could not run {} in {}: {}", cmd, path, err),
+                        );
+                    }
+                });
+            }
+        }
+    }
+
+    pub fn toggle_help(&self) {
         let mut state = self.state.write().unwrap();
         state.show_help = !state.show_help
     }
 
-    pub fn help_open(&self) -> bool {
-        self.state.read().unwrap().show_help
+    pub fn help_open(&self) -> bool {
+        self.state.read().unwrap().show_help
+    }
+
+    pub fn toggle_error_log(&self) {
+        let mut state = self.state.write().unwrap();
+        state.error_log.show = !state.error_log.show
+    }
+
+    pub fn scroll_error_log_down(&self) {
+        self.state.write().unwrap().error_log.scroll_down();
+    }
+
+    pub fn scroll_error_log_up(&self) {
+        self.state.write().unwrap().error_log.scroll_up();
+    }
+
+    pub fn clear_error_log(&self) {
+        self.state.write().unwrap().error_log.clear();
+    }
+
+    pub fn searching(&self) -> bool {
+        self.state.read().unwrap().searching
+    }
+
+    pub fn cursor_position(&self) -> Option<Position> {
+        self.state.read().unwrap().cursor_position
+    }
+
+    pub fn toggle_search(&self) {
+        let mut state = self.state.write().unwrap();
+        state.searching = !state.searching
+    }
+
+    /// Calls the github api again and updates the prs, skipping the repositories that are
+    /// waiting for a retry or that failed in a way retrying can't fix
+    pub fn refresh_pull_requests(&self) {
+        if self.offline {
+            return;
+        }
+
+        let repositories: Vec<&Repository> = {
+            let mut state = self.state.write().unwrap();
+            let repositories: Vec<&Repository> = self
+                .config
+                .repositories
+                .iter()
+                .filter(|r| state.retries.allows(&RepoId::from(*r)))
+                .collect();
+            // Reset the progress for this refresh
+            state.loading_progress = LoadingProgress {
+                total_repositories: repositories.len(),
+                pending_repositories: repositories.len(),
+                fetched_pulls: 0,
+            };
+
+            // Only the details of the prs that changed since they were fetched are fetched again,
+            // the overviews are fetched again once shown
+            state.invalidate_details();
+            state.details.local_states.clear();
+            if let Some(Item::PullRequest(pr)) = state.details.pr_details.clone() {
+                self.ensure_merge_info(&mut state, &pr);
+                self.ensure_tab(&mut state, &pr);
+            }
+            if let Some(repo) = state.details.repository.clone() {
+                self.ensure_overview(&mut state, &repo);
+            }
+
+            repositories
+        };
+
+        self.spawn_fetches(&repositories);
+        self.spawn_involved(&repositories);
+        self.spawn_notifications(&repositories);
+    }
+
+    /// Refreshes every repository right away, including the ones that were failing
+    pub fn refetch_pull_requests(&self) {
+        self.state.write().unwrap().retries.reset();
+        self.refresh_pull_requests();
+    }
+
+    /// Fetches again the failed repositories whose retry is due
+    pub fn retry_failed(&self) {
+        if self.offline {
+            return;
+        }
+
+        let repositories: Vec<&Repository> = {
+            let mut state = self.state.write().unwrap();
+            let due = state.retries.take_due();
+            if due.is_empty() {
+                return;
+            }
+            let repositories: Vec<&Repository> = self
+                .config
+                .repositories
+                .iter()
+                .filter(|r| due.contains(&RepoId::from(*r)))
+                .collect();
+
+            // Retries join the refresh in progress, if any
+            let progress = &mut state.loading_progress;
+            if progress.pending_repositories == 0 {
+                *progress = LoadingProgress::default();
+            }
+            progress.total_repositories += repositories.len();
+            progress.pending_repositories += repositories.len();
+
+            repositories
+        };
+
+        self.spawn_fetches(&repositories);
+    }
+
+    // Searches the prs involving the user, once per forge and user since a search can cover
+    // several repositories
+    fn spawn_involved(&self, repositories: &[&Repository]) {
+        let mut searches: Vec<(Arc<dyn Forge>, String, Vec<Repository>)> = vec![];
+        for repository in repositories {
+            // Without an username there is no one to search for
+            let (Some(forge), Some(username)) = (
+                self.forges.get(&RepoId::from(*repository)),
+                repository
+                    .username
+                    .as_ref()
+                    .or(self.config.username.as_ref()),
+            ) else {
+                continue;
+            };
+
+            match searches
+                .iter_mut()
+                .find(|(f, u, _)| Arc::ptr_eq(f, &forge.forge) && u == username)
+            {
+                Some((_, _, repositories)) => repositories.push((*repository).clone()),
+                None => searches.push((
+                    forge.forge.clone(),
+                    username.clone(),
+                    vec![(*repository).clone()],
+                )),
+            }
+        }
+
+        for (forge, username, repositories) in searches {
+            tokio::spawn(Self::fetch_involved(
+                self.state.clone(),
+                forge,
+                username,
+                repositories,
+            ));
+        }
+    }
+
+    async fn fetch_involved(
+        app_state: Arc<RwLock<AppState>>,
+        forge: Arc<dyn Forge>,
+        username: String,
+        repositories: Vec<Repository>,
+    ) {
+        let keys: Vec<(String, String)> = repositories
+            .iter()
+            .map(|r| (r.owner.clone(), r.name.clone()))
+            .collect();
+        let involved = match forge.involved(&keys, &username).await {
+            Ok(involved) => involved,
+            Err(err) => {
+                let mut state = app_state.write().unwrap();
+                state.error_log.push("involved", err.to_string());
+                return;
+            }
+        };
+
+        let mut grouped: HashMap<RepoId, Vec<PullRequest>> = HashMap::new();
+        for involved in involved {
+            let pr = PullRequest {
+                involvements: involved.involvements.into_iter().map(Into::into).collect(),
+                ..PullRequest::from(&involved.pull_request)
+            };
+            grouped.entry(pr.repo.clone()).or_default().push(pr);
+        }
+
+        // Repositories without results are cleared, the prs might not involve us anymore
+        let mut state = app_state.write().unwrap();
+        for repository in repositories.iter() {
+            let repo = RepoId::from(repository);
+            let prs = grouped.remove(&repo).unwrap_or_default();
+            state.involved_prs.set_group(&repo, prs);
+        }
+    }
+
+    // Fetches the notifications once per forge, the inbox covering every repository of the user
+    fn spawn_notifications(&self, repositories: &[&Repository]) {
+        let mut inboxes: Vec<(Arc<dyn Forge>, Vec<Repository>)> = vec![];
+        for repository in repositories {
+            let Some(forge) = self.forges.get(&RepoId::from(*repository)) else {
+                continue;
+            };
+
+            match inboxes
+                .iter_mut()
+                .find(|(f, _)| Arc::ptr_eq(f, &forge.forge))
+            {
+                Some((_, repositories)) => repositories.push((*repository).clone()),
+                None => inboxes.push((forge.forge.clone(), vec![(*repository).clone()])),
+            }
+        }
+
+        for (forge, repositories) in inboxes {
+            tokio::spawn(Self::fetch_notifications(
+                self.state.clone(),
+                forge,
+                repositories,
+            ));
+        }
+    }
+
+    async fn fetch_notifications(
+        app_state: Arc<RwLock<AppState>>,
+        forge: Arc<dyn Forge>,
+        repositories: Vec<Repository>,
+    ) {
+        let keys: Vec<(String, String)> = repositories
+            .iter()
+            .map(|r| (r.owner.clone(), r.name.clone()))
+            .collect();
+        let notifications = match forge.notifications(&keys).await {
+            Ok(notifications) => notifications,
+            Err(err) => {
+                let mut state = app_state.write().unwrap();
+                state.error_log.push("notifications", err.to_string());
+                return;
+            }
+        };
+
+        let mut grouped: HashMap<RepoId, Vec<Notification>> = HashMap::new();
+        for notification in notifications.iter() {
+            let notification = Notification::from(notification);
+            grouped
+                .entry(notification.repo.clone())
+                .or_default()
+                .push(notification);
+        }
+
+        let mut state = app_state.write().unwrap();
+        for repository in repositories.iter() {
+            let repo = RepoId::from(repository);
+            let notifications = grouped.remove(&repo).unwrap_or_default();
+            state.notifications.set_group(&repo, notifications);
+        }
+    }
+
+    /// Acts on the selected notification, the inbox is updated right away without waiting for
+    /// the forge
+    pub fn update_notification(&self, action: forge::NotificationAction) {
+        if self.offline {
+            return;
+        }
+
+        let mut state = self.state.write().unwrap();
+        let Some(Item::Notification(notification)) = state.active_prs_state().find_selected()
+        else {
+            return;
+        };
+        let Some(forge) = self.forge_of(&notification.repo) else {
+            return;
+        };
+
+        let thread_id = notification.thread_id.clone();
+        state.notifications.update_groups(|items| match action {
+            forge::NotificationAction::MarkRead => items
+                .iter_mut()
+                .filter_map(|item| match item {
+                    Item::Notification(notification) => Some(notification),
+                    _ => None,
+                })
+                .filter(|n| n.thread_id == thread_id)
+                .for_each(|n| n.unread = false),
+            forge::NotificationAction::MarkDone | forge::NotificationAction::Unsubscribe => items
+                .retain(|item| !matches!(item, Item::Notification(n) if n.thread_id == thread_id)),
+        });
+        let selected = state.notifications.find_selected().cloned();
+        self.select_item(&mut state, selected);
+
+        let app_state = self.state.clone();
+        tokio::spawn(async move {
+            if let Err(err) = forge.update_notification(&thread_id, action).await {
+                // The next refresh brings the notification back as it is on the forge
+                let mut state = app_state.write().unwrap();
+                state.error_log.push("notifications", err.to_string());
+            }
+        });
+    }
+
+    /// Selects the pr of the selected notification in the first panel listing it
+    pub fn go_to_notification(&self) {
+        let mut state = self.state.write().unwrap();
+        let Some(Item::Notification(notification)) = state.active_prs_state().find_selected()
+        else {
+            return;
+        };
+        let url = notification.url.clone();
+
+        for panel in ActivePanel::ALL {
+            if panel == ActivePanel::Notifications {
+                continue;
+            }
+            state.active_panel = panel;
+            let prs_state = Self::get_active_prs_state_mut(&mut state);
+            if prs_state.select_url(&url) {
+                let pr = prs_state.find_selected().cloned();
+                self.select_item(&mut state, pr);
+                return;
+            }
+        }
+
+        // Not listed anywhere else, stay on the notification
+        state.active_panel = ActivePanel::Notifications;
+    }
+
+    /// Shows the workflow runs of the repository of the selected pr, starting with the ones of
+    /// the pr itself. Returns whether they are shown
+    pub fn open_workflow_runs(&self) -> bool {
+        if self.offline {
+            return false;
+        }
+
+        let mut state = self.state.write().unwrap();
+        let Some(item) = state.active_prs_state().find_selected().cloned() else {
+            return false;
+        };
+        if !self.forges.contains_key(item.repo()) {
+            return false;
+        }
+
+        let pull_request = item
+            .pull_request()
+            .filter(|pr| !pr.head_sha.is_empty())
+            .map(|pr| (pr.id.clone(), pr.head_sha.clone()));
+        state.workflow_runs.open(item.repo().clone(), pull_request);
+        self.spawn_workflow_runs(&state.workflow_runs);
+
+        true
+    }
+
+    pub fn close_workflow_runs(&self) {
+        self.state.write().unwrap().workflow_runs.close();
+    }
+
+    /// Goes back to the previous view of the workflow runs, returns whether they are still shown
+    pub fn workflow_runs_back(&self) -> bool {
+        self.state.write().unwrap().workflow_runs.back()
+    }
+
+    pub fn select_next_workflow_item(&self) {
+        self.state.write().unwrap().workflow_runs.select_next();
+    }
+
+    pub fn select_previous_workflow_item(&self) {
+        self.state.write().unwrap().workflow_runs.select_previous();
+    }
+
+    pub fn page_workflow_log_down(&self) {
+        self.state.write().unwrap().workflow_runs.page_down();
+    }
+
+    pub fn page_workflow_log_up(&self) {
+        self.state.write().unwrap().workflow_runs.page_up();
+    }
+
+    pub fn toggle_workflow_runs_branch(&self) {
+        let mut state = self.state.write().unwrap();
+        if state.workflow_runs.toggle_branch() {
+            self.spawn_workflow_runs(&state.workflow_runs);
+        }
+    }
+
+    pub fn reload_workflow_runs(&self) {
+        let mut state = self.state.write().unwrap();
+        state.workflow_runs.reload_runs();
+        self.spawn_workflow_runs(&state.workflow_runs);
+    }
+
+    /// Lists the jobs of the selected run, or shows the log of the selected job
+    pub fn enter_workflow_item(&self) {
+        let mut state = self.state.write().unwrap();
+        let Some(repo) = state.workflow_runs.repo.clone() else {
+            return;
+        };
+        let (Some(entered), Some(forge)) = (
+            state.workflow_runs.enter(),
+            self.forges.get(&repo).map(|f| f.forge.clone()),
+        ) else {
+            return;
+        };
+
+        let app_state = self.state.clone();
+        tokio::spawn(async move {
+            let RepoId { owner, name } = &repo;
+            match entered {
+                Entered::Run(run_id) => {
+                    let jobs = forge.workflow_jobs(owner, name, run_id).await;
+                    let mut state = app_state.write().unwrap();
+                    state.workflow_runs.set_jobs(run_id, jobs);
+                }
+                Entered::Job(job_id) => {
+                    let log = forge.job_log(owner, name, job_id).await;
+                    let mut state = app_state.write().unwrap();
+                    state.workflow_runs.set_log(job_id, log);
+                }
+            }
+        });
+    }
+
+    /// Opens the selected job, or else the selected run, in the browser
+    pub fn open_workflow_item(&self) {
+        let url = {
+            let state = self.state.read().unwrap();
+            let runs = &state.workflow_runs;
+            runs.selected_job()
+                .and_then(|job| job.url.clone())
+                .or_else(|| runs.selected_run().map(|run| run.url.clone()))
+        };
+        if let Some(url) = url {
+            self.open_url(&url);
+        }
+    }
+
+    /// Re-runs or cancels the selected run, the runs are fetched again once the forge accepted
+    pub fn update_workflow_run(&self, action: forge::RunAction) {
+        let mut state = self.state.write().unwrap();
+        let runs = &mut state.workflow_runs;
+        let (Some(repo), Some(run)) = (runs.repo.clone(), runs.selected_run().cloned()) else {
+            return;
+        };
+        let Some(forge) = self.forges.get(&repo).map(|f| f.forge.clone()) else {
+            return;
+        };
+        let verb = match action {
+            forge::RunAction::RerunFailedJobs => "Re-running the failed jobs of",
+            forge::RunAction::Cancel => "Cancelling",
+        };
+        runs.set_notice(format!("{} {} #{}...", verb, run.name, run.number));
+
+        let app_state = self.state.clone();
+        tokio::spawn(async move {
+            let RepoId { owner, name } = &repo;
+            let result = forge.update_workflow_run(owner, name, run.id, action).await;
+
+            let filter = {
+                let mut state = app_state.write().unwrap();
+                let runs = &mut state.workflow_runs;
+                match result {
+                    Ok(()) => runs.set_notice(format!("{} {} #{}", verb, run.name, run.number)),
+                    Err(err) => {
+                        runs.set_notice(err.to_string());
+                        return;
+                    }
+                }
+                runs.filter()
+            };
+            // Show the new state of the run
+            let fetched = forge.workflow_runs(owner, name, &filter).await;
+            let mut state = app_state.write().unwrap();
+            state.workflow_runs.set_runs(&repo, &filter, fetched);
+        });
+    }
+
+    pub fn workflow_runs_searching(&self) -> bool {
+        self.state.read().unwrap().workflow_runs.searching
+    }
+
+    pub fn start_workflow_log_search(&self) {
+        self.state.write().unwrap().workflow_runs.start_search();
+    }
+
+    pub fn handle_workflow_log_search(&self, key: KeyCode) {
+        let mut state = self.state.write().unwrap();
+        let runs = &mut state.workflow_runs;
+        match key {
+            KeyCode::Esc => runs.clear_search(),
+            KeyCode::Enter => runs.end_search(),
+            KeyCode::Backspace => runs.pop_search(),
+            KeyCode::Char(c) => runs.push_search(c),
+            _ => {}
+        }
+    }
+
+    pub fn next_workflow_log_match(&self) {
+        self.state.write().unwrap().workflow_runs.next_match();
+    }
+
+    pub fn previous_workflow_log_match(&self) {
+        self.state.write().unwrap().workflow_runs.previous_match();
+    }
+
+    fn spawn_workflow_runs(&self, runs: &WorkflowRunsState) {
+        let Some(repo) = runs.repo.clone() else {
+            return;
+        };
+        let Some(forge) = self.forges.get(&repo).map(|f| f.forge.clone()) else {
+            return;
+        };
+        let filter = runs.filter();
+
+        let app_state = self.state.clone();
+        tokio::spawn(async move {
+            let fetched = forge.workflow_runs(&repo.owner, &repo.name, &filter).await;
+            let mut state = app_state.write().unwrap();
+            state.workflow_runs.set_runs(&repo, &filter, fetched);
+        });
+    }
+
+    // Fetches the repositories forge by forge, the teams of the user are listed once per forge
+    // before fetching its repositories
+    fn spawn_fetches(&self, repositories: &[&Repository]) {
+        let mut fetches: Vec<(RepositoryForge, Vec<Repository>)> = vec![];
+        for repository in repositories {
+            let Some(forge) = self.forges.get(&RepoId::from(*repository)) else {
+                let snapshot = Self::finish_repository(&mut self.state.write().unwrap());
+                Self::save_snapshot(&self.state, snapshot, self.cache_file.clone());
+                continue;
+            };
+
+            match fetches
+                .iter_mut()
+                .find(|(f, _)| Arc::ptr_eq(&f.forge, &forge.forge))
+            {
+                Some((_, repositories)) => repositories.push((*repository).clone()),
+                None => fetches.push((forge.clone(), vec![(*repository).clone()])),
+            }
+        }
+
+        for (forge, repositories) in fetches {
+            let app_state = self.state.clone();
+            let config = self.config.clone();
+            let cache_file = self.cache_file.clone();
+            tokio::spawn(async move {
+                let teams = Self::forge_teams(&app_state, &forge, &config.ignored_teams).await;
+                for repository in repositories {
+                    tokio::spawn(Self::fetch_pulls(
+                        app_state.clone(),
+                        forge.forge.clone(),
+                        config.clone(),
+                        repository,
+                        teams.clone(),
+                        cache_file.clone(),
+                    ));
+                }
+            });
+        }
+    }
+
+    /// Cycles through the review status filters of the review requested panel
+    pub fn next_status_filter(&self) {
+        let mut state = self.state.write().unwrap();
+        if state.active_panel == ActivePanel::PullRequestsToReview {
+            let filter = match state.review_prs.status_filter() {
+                None => Some(ReviewStatus::Requested),
+                Some(ReviewStatus::Requested) => Some(ReviewStatus::ReRequested),
+                Some(ReviewStatus::ReRequested) => Some(ReviewStatus::Commented),
+                Some(ReviewStatus::Commented) => Some(ReviewStatus::ChangesRequested),
+                Some(ReviewStatus::ChangesRequested) => Some(ReviewStatus::Approved),
+                Some(ReviewStatus::Approved) => Some(ReviewStatus::ApprovedStale),
+                Some(ReviewStatus::ApprovedStale) => None,
+            };
+            state.review_prs.set_status_filter(filter);
+
+            let pr = state.review_prs.find_selected().cloned();
+            self.select_item(&mut state, pr);
+        }
+    }
+
+    /// Persists the current data so the next run starts with it
+    pub fn save_cache(&self) -> color_eyre::Result<()> {
+        // The cache is all there is when offline, keep its original timestamp
+        let Some(cache_file) = self.cache_file.as_deref().filter(|_| !self.offline) else {
+            return Ok(());
+        };
+
+        // Don't keep the state locked while writing
+        let snapshot = Snapshot::from_state(&self.state.read().unwrap());
+        snapshot.save(cache_file)
+    }
+
+    pub fn clear_search(&self) {
+        let mut state = self.state.write().unwrap();
+        state.search.reset();
+        state
+            .lists_mut()
+            .into_iter()
+            .for_each(|list| list.clear_filter_query());
+    }
+
+    pub fn handle_search_input(&self, event: &Event) {
+        let mut state = self.state.write().unwrap();
+        state.search.handle_event(event);
+
+        let value = state.search.value().to_string();
+
+        // We search in all of the lists
+        state
+            .lists_mut()
+            .into_iter()
+            .for_each(|list| list.set_filter_query(Some(value.clone())));
+    }
+}
+
+impl AppState {
+    fn active_prs_state(&self) -> &PullRequestsListState {
+        match self.active_panel {
+            ActivePanel::PullRequestsToReview => &self.review_prs,
+            ActivePanel::Authored => &self.authored_prs,
+            ActivePanel::Assigned => &self.assigned_prs,
+            ActivePanel::Involved => &self.involved_prs,
+            ActivePanel::RecentlyClosed => &self.closed_prs,
+            ActivePanel::Issues => &self.issues,
+            ActivePanel::Notifications => &self.notifications,
+        }
+    }
+
+    fn lists(&self) -> [&PullRequestsListState; 7] {
+        [
+            &self.review_prs,
+            &self.authored_prs,
+            &self.assigned_prs,
+            &self.involved_prs,
+            &self.closed_prs,
+            &self.issues,
+            &self.notifications,
+        ]
+    }
+
+    // Forgets the details fetched for the prs that changed since, or that are no longer listed
+    fn invalidate_details(&mut self) {
+        let listed: HashMap<String, PrVersion> = self
+            .lists()
+            .into_iter()
+            .flat_map(|list| list.grouped_prs.values().flatten())
+            .filter_map(Item::pull_request)
+            .map(|pr| (pr.url.clone(), PrVersion::from(pr)))
+            .collect();
+        self.details.retain_versions(&listed);
+    }
+
+    fn lists_mut(&mut self) -> [&mut PullRequestsListState; 7] {
+        [
+            &mut self.review_prs,
+            &mut self.authored_prs,
+            &mut self.assigned_prs,
+            &mut self.involved_prs,
+            &mut self.closed_prs,
+            &mut self.issues,
+            &mut self.notifications,
+        ]
+    }
+
+    fn has_errors(&self) -> bool {
+        self.repository_states
+            .values()
+            .any(|s| matches!(s, LoadingState::Error(_)))
+    }
+}
+
+impl Widget for &PullRequestWidget {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        // 1. Calculate Layout
+        let (prs_area, details_area, footer_area) = self.calculate_main_layout(area);
+
+        // 2. Acquire Lock
+        let mut state = self.state.write().unwrap();
+
+        // 3. Render Footer
+        self.render_footer(&mut state, footer_area, buf);
+
+        // 4. Render Main Panels using state
+        self.render_pr_list_panel(&mut state, prs_area, buf);
+        state.details.render(details_area, buf);
+
+        // 5. Render Popups if needed
+        if state.show_help {
+            self.render_help_popup(area, buf); // area is the full screen for centering
+        }
+        if state.error_log.show {
+            state.error_log.render(area, buf); // area is the full screen for centering
+        }
+        if state.workflow_runs.show {
+            state.workflow_runs.render(area, buf);
+        }
+    }
+}
+
+// Render related functions
+impl PullRequestWidget {
+    // --- Helper render functions ---
+    fn calculate_main_layout(&self, area: Rect) -> (Rect, Rect, Rect) {
+        let base_layout =
+            Layout::vertical([Constraint::Percentage(95), Constraint::Min(3)]).split(area);
+        let prs_layout =
+            Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)])
+                .split(base_layout[0]);
+        (prs_layout[0], prs_layout[1], base_layout[1])
+    }
+
+    fn render_pr_list_panel(&self, state: &mut AppState, area: Rect, buf: &mut Buffer) {
+        // Build title line based on state.active_panel
+        let mut title_line = Line::from("📋 ");
+        for (i, panel) in ActivePanel::ALL.iter().enumerate() {
+            if i > 0 {
+                title_line.push_span(" - ");
+            }
+            if *panel == state.active_panel {
+                title_line.push_span(panel.title().bold());
+            } else {
+                title_line.push_span(panel.title().dark_gray());
+            }
+        }
+        title_line.push_span(" ");
+        if state.active_panel == ActivePanel::PullRequestsToReview
+            && let Some(status) = state.review_prs.status_filter()
+        {
+            title_line
+                .push_span(format!("[{} {}] ", status.glyph(), status.label()).fg(status.color()));
+        }
+
+        let mut prs_block = utils::block_with_title(title_line);
+
+        // If we're not searching we are focused on the panel block
+        if !state.searching {
+            prs_block = prs_block.border_style(Style::default().fg(Color::Green));
+        }
+
+        let prs_state = match state.active_panel {
+            ActivePanel::PullRequestsToReview => &mut state.review_prs,
+            ActivePanel::Authored => &mut state.authored_prs,
+            ActivePanel::Assigned => &mut state.assigned_prs,
+            ActivePanel::Involved => &mut state.involved_prs,
+            ActivePanel::RecentlyClosed => &mut state.closed_prs,
+            ActivePanel::Issues => &mut state.issues,
+            ActivePanel::Notifications => &mut state.notifications,
+        };
+        prs_state.render_table(
+            prs_block,
+            &state.repository_states,
+            &self.ambiguous_names,
+            area,
+            buf,
+        );
+    }
+
+    fn render_footer(&self, state: &mut AppState, area: Rect, buf: &mut Buffer) {
+        // Create the block with common styling first
+        let mut bottom_box = Block::default()
+            .borders(ratatui::widgets::Borders::ALL)
+            .border_type(ratatui::widgets::BorderType::Rounded);
+
+        // Calculate inner area *before* setting title and potentially border style
+        let bottom_inner = bottom_box.inner(area);
+
+        // Determine title, content, and apply conditional styling
+        if state.searching || !state.search.value().is_empty() {
+            bottom_box = bottom_box
+                .title("Search")
+                .border_style(Style::default().fg(Color::Green)); // Green border when searching
+
+            // Render the block first to draw borders
+            bottom_box.render(area, buf);
+
+            // Calculate scroll for the search input
+            let width = bottom_inner.width.max(1) as usize; // Ensure width is at least 1
+            let scroll = state.search.visual_scroll(width);
+            let input_paragraph = Paragraph::new(state.search.value());
+
+            // Render the search text itself inside the inner area
+            input_paragraph.render(bottom_inner, buf);
+
+            // Calculate and store cursor position if searching is active
+            if state.searching {
+                let cursor_x_offset = (state.search.visual_cursor().max(scroll) - scroll) as u16;
+                // Store the calculated absolute position
+                state.cursor_position = Some(Position {
+                    x: bottom_inner.x + cursor_x_offset, // Absolute X position
+                    y: bottom_inner.y,                   // Absolute Y position
+                });
+            } else {
+                // Not actively searching, but might have text, don't show cursor
+                state.cursor_position = None;
+            }
+        } else {
+            // Not searching: Render help text
+            bottom_box = bottom_box.title("Help"); // Default title
+
+            let bottom_inner_parts = Layout::horizontal([
+                Constraint::Fill(1),
+                Constraint::Length(30),
+                Constraint::Length(32),
+            ])
+            .split(bottom_inner);
+
+            // Render the block first
+            bottom_box.render(area, buf);
+
+            let progress = state.loading_progress;
+            let failed_repositories = state
+                .repository_states
+                .values()
+                .filter(|s| matches!(s, LoadingState::Error(_)))
+                .count();
+            let loading_state = if progress.pending_repositories > 0 {
+                format!(
+                    "Loading {}/{} repos • {} PRs ",
+                    progress.total_repositories - progress.pending_repositories,
+                    progress.total_repositories,
+                    progress.fetched_pulls
+                )
+                .yellow()
+                .into_right_aligned_line()
+            } else if self.offline {
+                format!(
+                    "Offline • cached {} ",
+                    Self::format_time(state.stale_since.unwrap_or_default())
+                )
+                .yellow()
+                .into_right_aligned_line()
+            } else if failed_repositories > 0 {
+                format!("{} repos failed ✗ (e) ", failed_repositories)
+                    .red()
+                    .into_right_aligned_line()
+            } else if let Some(stale_since) = state.stale_since {
+                format!("Stale since {} ", Self::format_time(stale_since))
+                    .yellow()
+                    .into_right_aligned_line()
+            } else {
+                "Loaded ✔  ".green().into_right_aligned_line()
+            };
+
+            let help_line = Line::from(
+                "Scroll: ↑↓,j/k • Switch: TAB • Review: r • Keybindings: ? • Quit: q".green(),
+            );
+
+            // Render help text inside the inner area
+            help_line.render(bottom_inner_parts[0], buf);
+            // Show the forge closest to its rate limit
+            let rate_limit = self
+                .forges
+                .values()
+                .filter_map(|f| f.forge.rate_limit())
+                .min_by_key(|r| r.remaining * 1000 / r.limit.max(1));
+            if let Some(rate_limit) = rate_limit {
+                Self::rate_limit_line(&rate_limit).render(bottom_inner_parts[1], buf);
+            }
+            loading_state.render(bottom_inner_parts[2], buf);
+
+            // No cursor when showing help
+            state.cursor_position = None;
+        }
+    }
+
+    fn rate_limit_line(rate_limit: &forge::RateLimit) -> Line<'static> {
+        let reset = Self::format_time(rate_limit.reset);
+
+        if rate_limit.is_exhausted() {
+            format!("Rate limited until {} ", reset)
+                .red()
+                .into_right_aligned_line()
+        } else {
+            format!(
+                "API {}/{} • resets {} ",
+                rate_limit.remaining, rate_limit.limit, reset
+            )
+            .dark_gray()
+            .into_right_aligned_line()
+        }
+    }
+
+    // Formats an unix timestamp as a local `HH:MM`
+    fn format_time(timestamp: i64) -> String {
+        chrono::DateTime::from_timestamp(timestamp, 0)
+            .map(|time| {
+                time.with_timezone(&chrono::Local)
+                    .format("%H:%M")
+                    .to_string()
+            })
+            .unwrap_or_default()
+    }
+
+    fn render_help_popup(&self, screen_area: Rect, buf: &mut Buffer) {
+        let rows = KEYBINDINGS.iter().map(|(key, action)| {
+            Row::new(vec![
+                Cell::from(key.to_string()).style(Style::default().fg(Color::Cyan)), // Theming
+                Cell::from(action.to_string()).style(Style::default().fg(Color::Green)), // Theming
+            ])
+        });
+
+        // Use the full screen_area for centering, tall enough to show all the keybindings
+        let area = utils::centered_rect(screen_area, 30, 20, 35, KEYBINDINGS.len() as u16 + 2);
+        let popup_block = utils::block_with_title(" Keybindings ")
+            .title_bottom(" Esc to close ")
+            .borders(ratatui::widgets::Borders::ALL)
+            .border_style(Style::default().fg(Color::LightCyan)); // Theming
+
+        let help_table = Table::new(rows, [Constraint::Length(10), Constraint::Min(15)])
+            .block(popup_block)
+            .column_spacing(2);
+
+        ratatui::widgets::Clear.render(area, buf);
+        ratatui::prelude::Widget::render(help_table, area, buf);
+    }
+}
+
+impl ReviewStatus {
+    /// Computes our review status on the pr, `None` when we're not involved as reviewer
+    fn compute(pr: &forge::PullRequest, login: &str, team_requested: bool) -> Option<Self> {
+        let requested = team_requested || pr.is_review_requested(login);
+        let latest_review = pr.latest_review_by(login);
+
+        match latest_review {
+            // We already reviewed and we've been asked again
+            Some(_) if requested => Some(Self::ReRequested),
+            None if requested => Some(Self::Requested),
+            None => None,
+            Some(review) => Some(match review.state {
+                forge::ReviewState::Approved => {
+                    let reviewed_head = review.commit.as_ref() == Some(&pr.head_sha);
+                    if reviewed_head {
+                        Self::Approved
+                    } else {
+                        Self::ApprovedStale
+                    }
+                }
+                forge::ReviewState::ChangesRequested => Self::ChangesRequested,
+                // Our review no longer counts, it is up to us to review again
+                forge::ReviewState::Dismissed => Self::Requested,
+                _ => Self::Commented,
+            }),
+        }
+    }
+
+    fn glyph(&self) -> &'static str {
+        match self {
+            Self::Requested => "●",
+            Self::ReRequested => "↻",
+            Self::Commented => "○",
+            Self::ChangesRequested => "✗",
+            Self::Approved => "✓",
+            Self::ApprovedStale => "◐",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Requested => "Requested",
+            Self::ReRequested => "Re-requested",
+            Self::Commented => "Commented",
+            Self::ChangesRequested => "Changes requested",
+            Self::Approved => "Approved",
+            Self::ApprovedStale => "Approved, new commits",
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            Self::Requested | Self::ReRequested => Color::Yellow,
+            Self::Commented => Color::Blue,
+            Self::ChangesRequested => Color::Red,
+            Self::Approved => Color::Green,
+            Self::ApprovedStale => Color::LightMagenta,
+        }
+    }
+}
+
+impl From<&forge::PullRequest> for PullRequest {
+    fn from(pr: &forge::PullRequest) -> Self {
+        Self {
+            id: pr.number.to_string(),
+            title: pr.title.clone(),
+            url: pr.url.clone(),
+            repo: RepoId::new(&pr.owner, &pr.repo),
+            body: pr.body.clone(),
+            is_draft: pr.is_draft,
+            author: pr
+                .author
+                .as_ref()
+                .map(|a| {
+                    if let Some(email) = a.email.as_ref().filter(|e| !e.is_empty()) {
+                        format!("{} - {}", a.login, email)
+                    } else {
+                        a.login.clone()
+                    }
+                })
+                .unwrap_or_default(),
+            requested_team: None,
+            review_status: None,
+            involvements: vec![],
+            closing: match pr.state {
+                forge::PullRequestState::Open => None,
+                forge::PullRequestState::Merged => Some(Closing::Merged {
+                    by: pr.merged_by.clone(),
+                }),
+                forge::PullRequestState::Closed => Some(Closing::Closed),
+            },
+            head_sha: pr.head_sha.clone(),
+            head_branch: pr.head_branch.clone(),
+            base_branch: pr.base_branch.clone(),
+            from_fork: pr.is_fork,
+            labels: pr
+                .labels
+                .iter()
+                .map(|l| Label {
+                    name: l.name.clone(),
+                    color: l.color.clone(),
+                })
+                .collect(),
+            reviewers: Reviewer::from_pull_request(pr),
+            assignees: pr.assignees.clone(),
+            milestone: pr.milestone.clone(),
+            created_at: pr.created_at,
+            updated_at: pr.updated_at,
+            changes: pr
+                .additions
+                .zip(pr.deletions)
+                .map(|(additions, deletions)| Changes {
+                    additions,
+                    deletions,
+                    changed_files: pr.changed_files,
+                }),
+            checks: pr.checks.into(),
+        }
+    }
+}
+
+impl Label {
+    // Labels with an invalid color are shown in gray
+    fn rgb(&self) -> (u8, u8, u8) {
+        match u32::from_str_radix(&self.color, 16) {
+            Ok(rgb) if self.color.len() == 6 => ((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8),
+            _ => (128, 128, 128),
+        }
+    }
+}
+
+impl Reviewer {
+    // A review requested again takes precedence over the previous review
+    fn from_pull_request(pr: &forge::PullRequest) -> Vec<Self> {
+        let mut reviewers: Vec<Self> = pr
+            .latest_reviews
+            .iter()
+            .filter(|r| r.state != forge::ReviewState::Pending)
+            .map(|r| Self {
+                login: r.author.clone(),
+                state: r.state.into(),
+            })
+            .collect();
+
+        for login in pr.requested_reviewers.iter().chain(&pr.requested_teams) {
+            match reviewers.iter_mut().find(|r| &r.login == login) {
+                Some(reviewer) => reviewer.state = ReviewerState::Requested,
+                None => reviewers.push(Self {
+                    login: login.clone(),
+                    state: ReviewerState::Requested,
+                }),
+            }
+        }
+
+        reviewers
+    }
+}
+
+impl From<forge::ReviewState> for ReviewerState {
+    fn from(state: forge::ReviewState) -> Self {
+        match state {
+            forge::ReviewState::Approved => Self::Approved,
+            forge::ReviewState::ChangesRequested => Self::ChangesRequested,
+            forge::ReviewState::Dismissed => Self::Dismissed,
+            forge::ReviewState::Commented | forge::ReviewState::Pending => Self::Commented,
+        }
+    }
+}
+
+impl From<&forge::Review> for Review {
+    fn from(review: &forge::Review) -> Self {
+        Self {
+            author: review.author.clone(),
+            state: review.state.into(),
+            body: review.body.clone(),
+            submitted_at: review.submitted_at,
+        }
+    }
+}
+
+impl ReviewerState {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Requested => "requested",
+            Self::Commented => "commented",
+            Self::ChangesRequested => "requested changes",
+            Self::Approved => "approved",
+            Self::Dismissed => "dismissed",
+        }
+    }
+
+    fn glyph(&self) -> &'static str {
+        match self {
+            Self::Requested => "●",
+            Self::Commented => "○",
+            Self::ChangesRequested => "✗",
+            Self::Approved => "✓",
+            Self::Dismissed => "–",
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            Self::Requested => Color::Yellow,
+            Self::Commented => Color::Blue,
+            Self::ChangesRequested => Color::Red,
+            Self::Approved => Color::Green,
+            Self::Dismissed => Color::DarkGray,
+        }
+    }
+}
+
+impl Closing {
+    fn label(&self) -> String {
+        match self {
+            Self::Merged { by: Some(by) } => format!("merged by @{}", by),
+            Self::Merged { by: None } => "merged".to_string(),
+            Self::Closed => "closed".to_string(),
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            Self::Merged { .. } => Color::Magenta,
+            Self::Closed => Color::Red,
+        }
+    }
+}
+
+impl Involvement {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Mentioned => "mentioned",
+            Self::Commented => "commented",
+        }
+    }
+}
+
+impl From<forge::Involvement> for Involvement {
+    fn from(involvement: forge::Involvement) -> Self {
+        match involvement {
+            forge::Involvement::Mentioned => Self::Mentioned,
+            forge::Involvement::Commented => Self::Commented,
+        }
+    }
+}
+
+impl From<Option<forge::CheckState>> for CheckStatus {
+    fn from(state: Option<forge::CheckState>) -> Self {
+        match state {
+            Some(forge::CheckState::Success) => Self::Success,
+            Some(forge::CheckState::Failure) => Self::Failure,
+            Some(forge::CheckState::Pending) => Self::Pending,
+            None => Self::None,
+        }
+    }
+}
+
+impl From<&forge::Check> for Check {
+    fn from(check: &forge::Check) -> Self {
+        Self {
+            name: check.name.clone(),
+            status: Some(check.state).into(),
+            conclusion: check.conclusion.clone(),
+            url: check.url.clone(),
+        }
+    }
+}
+
+impl CheckStatus {
+    fn glyph(&self) -> &'static str {
+        match self {
+            Self::Success | Self::Failure => "■",
+            Self::Pending => "□",
+            Self::None => "",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Success => "Success",
+            Self::Failure => "Failure",
+            Self::Pending => "Pending",
+            Self::None => "None",
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            Self::Success => Color::Green,
+            Self::Failure => Color::Red,
+            Self::Pending => Color::Yellow,
+            Self::None => Color::DarkGray,
+        }
+    }
+}
+
+impl From<forge::MergeInfo> for MergeInfo {
+    fn from(details: forge::MergeInfo) -> Self {
+        Self {
+            mergeable: details.mergeable.into(),
+            // Once the mergeability is known a missing rebaseability won't show up anymore
+            rebaseable: match (details.mergeable, details.rebaseable) {
+                (Some(_), None) => Mergeability::Unknown,
+                (_, rebaseable) => rebaseable.into(),
+            },
+            mergeable_state: details.mergeable_state,
+        }
+    }
+}
+
+impl MergeInfo {
+    fn failed() -> Self {
+        Self {
+            mergeable: Mergeability::Failed,
+            rebaseable: Mergeability::Failed,
+            mergeable_state: None,
+        }
+    }
+}
+
+impl From<Option<bool>> for Mergeability {
+    fn from(value: Option<bool>) -> Self {
+        match value {
+            Some(true) => Self::Yes,
+            Some(false) => Self::No,
+            None => Self::Computing,
+        }
+    }
+}
+
+impl RepoId {
+    pub fn new(owner: &str, name: &str) -> Self {
+        Self {
+            owner: owner.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    /// The name alone, unless other repositories have the same name
+    fn label(&self, ambiguous_names: &HashSet<String>) -> String {
+        if ambiguous_names.contains(&self.name) {
+            self.to_string()
+        } else {
+            self.name.clone()
+        }
+    }
+}
+
+// The api answers with the actual case of the names, which isn't always the one of the config
+impl PartialEq for RepoId {
+    fn eq(&self, other: &Self) -> bool {
+        self.owner.eq_ignore_ascii_case(&other.owner) && self.name.eq_ignore_ascii_case(&other.name)
+    }
+}
+
+impl Eq for RepoId {}
+
+impl Hash for RepoId {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for part in [&self.owner, &self.name] {
+            part.bytes()
+                .for_each(|b| state.write_u8(b.to_ascii_lowercase()));
+            // Keeps `ab/c` apart from `a/bc`
+            state.write_u8(0xff);
+        }
+    }
+}
+
+impl PartialOrd for RepoId {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for RepoId {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let cmp = |a: &str, b: &str| {
+            let a = a.bytes().map(|c| c.to_ascii_lowercase());
+            a.cmp(b.bytes().map(|c| c.to_ascii_lowercase()))
+        };
+        cmp(&self.owner, &other.owner).then_with(|| cmp(&self.name, &other.name))
+    }
+}
+
+impl fmt::Display for RepoId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.owner, self.name)
+    }
+}
+
+impl From<&PullRequest> for PrVersion {
+    fn from(pr: &PullRequest) -> Self {
+        Self {
+            updated_at: pr.updated_at,
+            head_sha: pr.head_sha.clone(),
+        }
+    }
+}
+
+impl From<&Repository> for RepoId {
+    fn from(repository: &Repository) -> Self {
+        Self::new(&repository.owner, &repository.name)
+    }
+}
+
+impl From<RepoId> for String {
+    fn from(repo: RepoId) -> Self {
+        repo.to_string()
+    }
+}
+
+impl TryFrom<String> for RepoId {
+    type Error = String;
+
+    // Gitlab owners may contain slashes (subgroups), the name never does
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value
+            .rsplit_once('/')
+            .map(|(owner, name)| Self::new(owner, name))
+            .ok_or_else(|| format!("Invalid repository {}, expected owner/name", value))
+    }
+}
+
+impl From<&forge::Issue> for Issue {
+    fn from(issue: &forge::Issue) -> Self {
+        Self {
+            id: issue.number.to_string(),
+            title: issue.title.clone(),
+            url: issue.url.clone(),
+            repo: RepoId::new(&issue.owner, &issue.repo),
+            body: issue.body.clone(),
+            author: issue
+                .author
+                .as_ref()
+                .map(|a| a.login.clone())
+                .unwrap_or_default(),
+            labels: issue
+                .labels
+                .iter()
+                .map(|l| Label {
+                    name: l.name.clone(),
+                    color: l.color.clone(),
+                })
+                .collect(),
+            assignees: issue.assignees.clone(),
+            milestone: issue.milestone.clone(),
+            created_at: issue.created_at,
+            updated_at: issue.updated_at,
+        }
+    }
+}
+
+impl Item {
+    fn id(&self) -> &str {
+        match self {
+            Self::PullRequest(pr) => &pr.id,
+            Self::Issue(issue) => &issue.id,
+            Self::Notification(notification) => &notification.id,
+        }
+    }
+
+    fn title(&self) -> &str {
+        match self {
+            Self::PullRequest(pr) => &pr.title,
+            Self::Issue(issue) => &issue.title,
+            Self::Notification(notification) => &notification.title,
+        }
+    }
+
+    fn url(&self) -> &str {
+        match self {
+            Self::PullRequest(pr) => &pr.url,
+            Self::Issue(issue) => &issue.url,
+            Self::Notification(notification) => &notification.url,
+        }
+    }
+
+    fn repo(&self) -> &RepoId {
+        match self {
+            Self::PullRequest(pr) => &pr.repo,
+            Self::Issue(issue) => &issue.repo,
+            Self::Notification(notification) => &notification.repo,
+        }
+    }
+
+    fn body(&self) -> &str {
+        match self {
+            Self::PullRequest(pr) => &pr.body,
+            Self::Issue(issue) => &issue.body,
+            Self::Notification(_) => "",
+        }
+    }
+
+    fn author(&self) -> &str {
+        match self {
+            Self::PullRequest(pr) => &pr.author,
+            Self::Issue(issue) => &issue.author,
+            Self::Notification(_) => "",
+        }
+    }
+
+    fn labels(&self) -> &[Label] {
+        match self {
+            Self::PullRequest(pr) => &pr.labels,
+            Self::Issue(issue) => &issue.labels,
+            Self::Notification(_) => &[],
+        }
+    }
+
+    fn assignees(&self) -> &[String] {
+        match self {
+            Self::PullRequest(pr) => &pr.assignees,
+            Self::Issue(issue) => &issue.assignees,
+            Self::Notification(_) => &[],
+        }
+    }
+
+    fn milestone(&self) -> Option<&String> {
+        match self {
+            Self::PullRequest(pr) => pr.milestone.as_ref(),
+            Self::Issue(issue) => issue.milestone.as_ref(),
+            Self::Notification(_) => None,
+        }
+    }
+
+    fn created_at(&self) -> Option<&DateTime<Utc>> {
+        match self {
+            Self::PullRequest(pr) => pr.created_at.as_ref(),
+            Self::Issue(issue) => issue.created_at.as_ref(),
+            Self::Notification(_) => None,
+        }
+    }
+
+    fn updated_at(&self) -> Option<&DateTime<Utc>> {
+        match self {
+            Self::PullRequest(pr) => pr.updated_at.as_ref(),
+            Self::Issue(issue) => issue.updated_at.as_ref(),
+            Self::Notification(notification) => Some(&notification.updated_at),
+        }
+    }
+
+    fn pull_request(&self) -> Option<&PullRequest> {
+        match self {
+            Self::PullRequest(pr) => Some(pr),
+            _ => None,
+        }
+    }
+
+    fn issue(&self) -> Option<&Issue> {
+        match self {
+            Self::Issue(issue) => Some(issue),
+            _ => None,
+        }
+    }
+}
+
+impl From<PullRequest> for Item {
+    fn from(pr: PullRequest) -> Self {
+        Self::PullRequest(pr)
+    }
+}
+
+impl From<Issue> for Item {
+    fn from(issue: Issue) -> Self {
+        Self::Issue(issue)
+    }
+}
+
+impl From<Notification> for Item {
+    fn from(notification: Notification) -> Self {
+        Self::Notification(notification)
+    }
+}
+
+impl From<&forge::Notification> for Notification {
+    fn from(notification: &forge::Notification) -> Self {
+        Self {
+            thread_id: notification.thread_id.clone(),
+            id: notification
+                .number
+                .map(|n| n.to_string())
+                .unwrap_or_default(),
+            title: notification.title.clone(),
+            url: notification.url.clone(),
+            repo: RepoId::new(&notification.owner, &notification.repo),
+            kind: notification.kind.clone(),
+            reason: notification.reason.clone(),
+            unread: notification.unread,
+            updated_at: notification.updated_at,
+        }
+    }
+}
+
+impl From<&forge::User> for Profile {
+    fn from(user: &forge::User) -> Self {
+        Self {
+            id: user.id.clone().unwrap_or_default(),
+            login: user.login.clone(),
+            name: user.name.clone().unwrap_or_default(),
+        }
+    }
+}
+
+impl From<&PullRequest> for Row<'_> {
+    fn from(pr: &PullRequest) -> Self {
+        let pr = pr.clone();
+        Row::new(vec![pr.id, pr.title, pr.repo.to_string()])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+    use crate::{config::ForgeKind, forge::MemoryForge};
+
+    fn config(username: &str) -> Config {
+        Config {
+            api_key: None,
+            username: Some(username.to_string()),
+            command: None,
+            command_args: vec![],
+            max_pull_requests: None,
+            recently_closed_days: None,
+            max_concurrent_requests: None,
+            ignored_teams: vec![],
+            repositories: vec![repository()],
+        }
+    }
+
+    fn repository() -> Repository {
+        Repository {
+            owner: "octo".to_string(),
+            name: "app".to_string(),
+            system_path: None,
+            forge: ForgeKind::Github,
+            host: None,
+            token: None,
+            username: None,
+        }
+    }
+
+    fn pull_request(number: u64, author: &str) -> forge::PullRequest {
+        forge::PullRequest {
+            number,
+            title: format!("Pr {}", number),
+            owner: "octo".to_string(),
+            repo: "app".to_string(),
+            head_sha: format!("sha{}", number),
+            author: Some(forge::User {
+                login: author.to_string(),
+                id: Some(format!("id-{}", author)),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn review(author: &str, state: forge::ReviewState, commit: &str) -> forge::Review {
+        forge::Review {
+            author: author.to_string(),
+            state,
+            commit: Some(commit.to_string()),
+            body: String::new(),
+            submitted_at: None,
+        }
+    }
+
+    fn ids(prs: &[PullRequest]) -> Vec<&str> {
+        prs.iter().map(|pr| pr.id.as_str()).collect()
+    }
+
+    fn listed_ids(items: &[Item]) -> Vec<&str> {
+        items.iter().map(Item::id).collect()
+    }
+
+    fn app_state() -> Arc<RwLock<AppState>> {
+        Arc::new(RwLock::new(AppState::default()))
+    }
+
+    async fn fetch(forge: MemoryForge, config: Config, app_state: &Arc<RwLock<AppState>>) {
+        PullRequestWidget::fetch_pulls(
+            app_state.clone(),
+            Arc::new(forge),
+            config,
+            repository(),
+            HashSet::new(),
+            None,
+        )
+        .await;
+    }
+
+    #[test]
+    fn classify_sorts_the_prs_into_the_panels() {
+        let authored = pull_request(1, "me");
+        let assigned = forge::PullRequest {
+            assignees: vec!["me".to_string()],
+            ..pull_request(2, "other")
+        };
+        let requested = forge::PullRequest {
+            requested_reviewers: vec!["me".to_string()],
+            ..pull_request(3, "other")
+        };
+        let team_requested = forge::PullRequest {
+            requested_teams: vec!["octo/core".to_string()],
+            ..pull_request(4, "other")
+        };
+        let unrelated = pull_request(5, "other");
+        let teams = HashSet::from(["octo/core".to_string()]);
+
+        let classification = classify(
+            &[authored, assigned, requested, team_requested, unrelated],
+            Some("me"),
+            &teams,
+        );
+
+        assert_eq!(ids(&classification.authored), ["1"]);
+        assert_eq!(ids(&classification.assigned), ["2"]);
+        assert_eq!(ids(&classification.review), ["3", "4"]);
+        assert_eq!(
+            classification.review[1].requested_team.as_deref(),
+            Some("octo/core")
+        );
+        assert!(classification.closed.is_empty());
+        assert_eq!(classification.authors.len(), 5);
+    }
+
+    #[test]
+    fn classify_lists_a_pr_in_every_panel_it_belongs_to() {
+        let self_assigned = forge::PullRequest {
+            assignees: vec!["me".to_string()],
+            ..pull_request(1, "me")
+        };
+        let assigned_reviewer = forge::PullRequest {
+            assignees: vec!["me".to_string()],
+            requested_reviewers: vec!["me".to_string()],
+            ..pull_request(2, "other")
+        };
+
+        let classification = classify(
+            &[self_assigned, assigned_reviewer],
+            Some("me"),
+            &HashSet::new(),
+        );
+
+        assert_eq!(ids(&classification.authored), ["1"]);
+        // Being an assignee doesn't hide the review request
+        assert_eq!(ids(&classification.assigned), ["1", "2"]);
+        assert_eq!(ids(&classification.review), ["2"]);
+    }
+
+    #[test]
+    fn authored_and_assigned_panels_follow_the_review_requests() {
+        let widget = refresh_widget(MemoryForge::default(), &[]);
+
+        let titles: Vec<&str> = (0..3)
+            .map(|_| {
+                widget.next_tab();
+                widget.state.read().unwrap().active_panel.title()
+            })
+            .collect();
+
+        assert_eq!(titles, ["Authored", "Assigned", "Involved"]);
+    }
+
+    #[tokio::test]
+    async fn fetch_involved_lists_the_prs_of_the_configured_repositories() {
+        let forge = MemoryForge::default();
+        forge.add_involved(
+            "me",
+            forge::Involved {
+                pull_request: pull_request(1, "other"),
+                involvements: vec![forge::Involvement::Mentioned, forge::Involvement::Commented],
+            },
+        );
+        forge.add_involved(
+            "me",
+            forge::Involved {
+                pull_request: forge::PullRequest {
+                    repo: "elsewhere".to_string(),
+                    ..pull_request(2, "other")
+                },
+                involvements: vec![forge::Involvement::Mentioned],
+            },
+        );
+        let app_state = app_state();
+        let app = RepoId::new("octo", "app");
+        let api = Repository {
+            name: "api".to_string(),
+            ..repository()
+        };
+        app_state.write().unwrap().involved_prs.set_group(
+            &RepoId::from(&api),
+            vec![PullRequest::from(&pull_request(3, "other"))],
+        );
+
+        PullRequestWidget::fetch_involved(
+            app_state.clone(),
+            Arc::new(forge),
+            "me".to_string(),
+            vec![repository(), api],
+        )
+        .await;
+
+        let state = app_state.read().unwrap();
+        // The prs that no longer involve us are cleared
+        assert_eq!(state.involved_prs.grouped_prs.len(), 1);
+        let [Item::PullRequest(pr)] = state.involved_prs.grouped_prs[&app].as_slice() else {
+            panic!("The pr is not listed");
+        };
+        assert_eq!(pr.id, "1");
+        assert_eq!(
+            pr.involvements,
+            [Involvement::Mentioned, Involvement::Commented]
+        );
+    }
+
+    #[test]
+    fn reviewers_keep_their_latest_state_unless_requested_again() {
+        let pr = forge::PullRequest {
+            latest_reviews: vec![
+                review("bob", forge::ReviewState::Approved, "sha1"),
+                review("carol", forge::ReviewState::ChangesRequested, "sha1"),
+                review("dave", forge::ReviewState::Pending, "sha1"),
+            ],
+            requested_reviewers: vec!["carol".to_string()],
+            requested_teams: vec!["octo/core".to_string()],
+            ..pull_request(1, "other")
+        };
+
+        let reviewers: Vec<(String, ReviewerState)> = PullRequest::from(&pr)
+            .reviewers
+            .into_iter()
+            .map(|r| (r.login, r.state))
+            .collect();
+
+        assert_eq!(
+            reviewers,
+            [
+                ("bob".to_string(), ReviewerState::Approved),
+                ("carol".to_string(), ReviewerState::Requested),
+                ("octo/core".to_string(), ReviewerState::Requested),
+            ]
+        );
+    }
+
+    #[test]
+    fn label_colors_are_read_from_their_hex_code() {
+        let label = |color: &str| Label {
+            name: "bug".to_string(),
+            color: color.to_string(),
+        };
+
+        assert_eq!(label("d73a4a").rgb(), (0xd7, 0x3a, 0x4a));
+        assert_eq!(label("fff").rgb(), (128, 128, 128));
+        assert_eq!(label("zzzzzz").rgb(), (128, 128, 128));
+    }
+
+    #[test]
+    fn details_show_the_branches_reviewers_and_changes() {
+        let pr = forge::PullRequest {
+            head_branch: "fix".to_string(),
+            base_branch: "main".to_string(),
+            is_fork: true,
+            labels: vec![forge::Label {
+                name: "bug".to_string(),
+                color: "d73a4a".to_string(),
+            }],
+            milestone: Some("v1.0".to_string()),
+            assignees: vec!["me".to_string()],
+            requested_reviewers: vec!["bob".to_string()],
+            additions: Some(10),
+            deletions: Some(2),
+            changed_files: Some(3),
+            ..pull_request(1, "other")
+        };
+        let mut state = AppState::default();
+        state.details.set_item(Some(PullRequest::from(&pr).into()));
+        let area = Rect::new(0, 0, 80, 20);
+        let mut buf = Buffer::empty(area);
+
+        state.details.render(area, &mut buf);
+
+        let lines: Vec<String> = (0..area.height)
+            .map(|y| (0..area.width).map(|x| buf[(x, y)].symbol()).collect())
+            .collect();
+        for expected in [
+            "Labels:  bug",
+            "Branch: fix → main (fork)",
+            "Reviewers: ● @bob",
+            "Assignees: @me",
+            "Milestone: v1.0",
+            "Changes: +10 -2 in 3 files",
+        ] {
+            assert!(
+                lines.iter().any(|line| line.contains(expected)),
+                "{} not in {:#?}",
+                expected,
+                lines
+            );
+        }
+    }
+
+    #[test]
+    fn classify_computes_the_review_status() {
+        let approved = forge::PullRequest {
+            latest_reviews: vec![review("me", forge::ReviewState::Approved, "sha1")],
+            ..pull_request(1, "other")
+        };
+        let approved_stale = forge::PullRequest {
+            latest_reviews: vec![review("me", forge::ReviewState::Approved, "old")],
+            ..pull_request(2, "other")
+        };
+        let re_requested = forge::PullRequest {
+            requested_reviewers: vec!["me".to_string()],
+            latest_reviews: vec![review("me", forge::ReviewState::Commented, "old")],
+            ..pull_request(3, "other")
+        };
+
+        let classification = classify(
+            &[approved, approved_stale, re_requested],
+            Some("me"),
+            &HashSet::new(),
+        );
+
+        let statuses: Vec<_> = classification
+            .review
+            .iter()
+            .map(|pr| pr.review_status)
+            .collect();
+        assert_eq!(
+            statuses,
+            [
+                Some(ReviewStatus::Approved),
+                Some(ReviewStatus::ApprovedStale),
+                Some(ReviewStatus::ReRequested),
+            ]
+        );
     }
 
-    pub fn searching(&self) -> bool {
-        self.state.read().unwrap().searching
+    #[test]
+    fn local_state_is_parsed_from_the_git_status() {
+        let output = "# branch.oid 1234\n# branch.head main\n# branch.upstream origin/main\n# branch.ab +2 -1\n1 .M N... 100644 100644 100644 1234 1234 src/main.rs\n? notes.txt\n";
+
+        assert_eq!(
+            LocalState::parse(output),
+            LocalState::Status {
+                branch: "main".to_string(),
+                ahead_behind: Some((2, 1)),
+                changed_files: 2,
+            }
+        );
     }
 
-    pub fn cursor_position(&self) -> Option<Position> {
-        self.state.read().unwrap().cursor_position
+    #[test]
+    fn local_state_without_upstream_nor_changes() {
+        let output = "# branch.oid 1234\n# branch.head (detached)\n";
+
+        assert_eq!(
+            LocalState::parse(output),
+            LocalState::Status {
+                branch: "(detached)".to_string(),
+                ahead_behind: None,
+                changed_files: 0,
+            }
+        );
     }
 
-    pub fn toggle_search(&self) {
-        let mut state = self.state.write().unwrap();
-        state.searching = !state.searching
+    #[test]
+    fn review_status_follows_our_latest_review() {
+        let status = |requested: bool, team_requested: bool, reviews: Vec<forge::Review>| {
+            let pr = forge::PullRequest {
+                requested_reviewers: if requested {
+                    vec!["me".to_string()]
+                } else {
+                    vec![]
+                },
+                latest_reviews: reviews,
+                ..pull_request(1, "other")
+            };
+            ReviewStatus::compute(&pr, "me", team_requested)
+        };
+        let reviewed = |state| vec![review("me", state, "sha1")];
+
+        assert_eq!(status(false, false, vec![]), None);
+        assert_eq!(status(true, false, vec![]), Some(ReviewStatus::Requested));
+        assert_eq!(status(false, true, vec![]), Some(ReviewStatus::Requested));
+        assert_eq!(
+            status(true, false, reviewed(forge::ReviewState::Approved)),
+            Some(ReviewStatus::ReRequested)
+        );
+        assert_eq!(
+            status(false, false, reviewed(forge::ReviewState::Commented)),
+            Some(ReviewStatus::Commented)
+        );
+        assert_eq!(
+            status(false, false, reviewed(forge::ReviewState::ChangesRequested)),
+            Some(ReviewStatus::ChangesRequested)
+        );
+        assert_eq!(
+            status(false, false, reviewed(forge::ReviewState::Dismissed)),
+            Some(ReviewStatus::Requested)
+        );
+        // Drafts are only seen by their author, they don't count as a review
+        assert_eq!(
+            status(false, false, reviewed(forge::ReviewState::Pending)),
+            None
+        );
+        // Someone else's review is not ours
+        assert_eq!(
+            status(
+                false,
+                false,
+                vec![review("other", forge::ReviewState::Approved, "sha1")]
+            ),
+            None
+        );
     }
 
-    /// Calls the github api again and updates the prs
-    pub fn refresh_pull_requests(&self) {
-        self.config.repositories.iter().for_each(|r| {
-            let state = self.state.clone(); // clone the widget to pass to the background task
-            let username = self.config.username.clone();
-            let owner = r.owner.clone();
-            let repo = r.name.clone();
-            tokio::spawn(Self::fetch_pulls(state, username, owner, repo));
-        });
+    #[test]
+    fn classify_keeps_only_the_closed_prs_we_took_part_in() {
+        let merged = forge::PullRequest {
+            state: forge::PullRequestState::Merged,
+            ..pull_request(1, "me")
+        };
+        let closed = forge::PullRequest {
+            state: forge::PullRequestState::Closed,
+            ..pull_request(2, "other")
+        };
+
+        let classification = classify(&[merged, closed], Some("me"), &HashSet::new());
+
+        assert_eq!(ids(&classification.closed), ["1"]);
+        assert!(classification.authored.is_empty());
     }
 
-    pub fn clear_search(&self) {
-        let mut state = self.state.write().unwrap();
-        state.search.reset();
-        state.review_prs.clear_filter_query();
-        state.assignee_prs.clear_filter_query();
+    #[test]
+    fn classify_without_username_only_keeps_the_authors() {
+        let classification = classify(&[pull_request(1, "me")], None, &HashSet::new());
+
+        assert_eq!(
+            classification,
+            Classification {
+                authors: vec![Profile::from(&forge::User {
+                    login: "me".to_string(),
+                    id: Some("id-me".to_string()),
+                    ..Default::default()
+                })],
+                ..Default::default()
+            }
+        );
     }
 
-    pub fn handle_search_input(&self, event: &Event) {
-        let mut state = self.state.write().unwrap();
-        state.search.handle_event(event);
+    #[tokio::test]
+    async fn fetch_pulls_loads_every_panel_of_the_repository() {
+        let forge = MemoryForge::default();
+        forge.add_pull_request(pull_request(1, "me"));
+        forge.add_pull_request(forge::PullRequest {
+            requested_reviewers: vec!["me".to_string()],
+            ..pull_request(2, "other")
+        });
+        forge.add_pull_request(forge::PullRequest {
+            state: forge::PullRequestState::Merged,
+            closed_at: Some(Utc::now()),
+            ..pull_request(3, "me")
+        });
+        forge.add_issue(forge::Issue {
+            number: 4,
+            owner: "octo".to_string(),
+            repo: "app".to_string(),
+            assignees: vec!["me".to_string()],
+            ..Default::default()
+        });
+        let app_state = app_state();
 
-        let value = state.search.value().to_string();
+        fetch(forge, config("me"), &app_state).await;
 
-        // We search in BOTH of the lists
-        state.review_prs.set_filter_query(Some(value.clone()));
-        state.assignee_prs.set_filter_query(Some(value));
+        let state = app_state.read().unwrap();
+        let repo = RepoId::new("octo", "app");
+        assert_eq!(listed_ids(&state.authored_prs.grouped_prs[&repo]), ["1"]);
+        assert_eq!(listed_ids(&state.review_prs.grouped_prs[&repo]), ["2"]);
+        assert_eq!(listed_ids(&state.closed_prs.grouped_prs[&repo]), ["3"]);
+        assert_eq!(listed_ids(&state.issues.grouped_prs[&repo]), ["4"]);
+        assert_eq!(state.repository_states[&repo], LoadingState::Loaded);
+        assert_eq!(state.loading_progress.fetched_pulls, 2);
+        assert!(state.details.cached_authors.contains_key("me"));
     }
-}
 
-impl Widget for &PullRequestWidget {
-    fn render(self, area: Rect, buf: &mut Buffer) {
-        // 1. Calculate Layout
-        let (prs_area, details_area, footer_area) = self.calculate_main_layout(area);
+    fn text_of(fetch: &Fetch<ratatui::text::Text<'static>>) -> String {
+        let Fetch::Loaded(text) = fetch else {
+            panic!("not loaded: {:?}", fetch);
+        };
+        text.lines
+            .iter()
+            .map(|line| line.to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
 
-        // 2. Acquire Lock
-        let mut state = self.state.write().unwrap();
+    #[tokio::test]
+    async fn fetch_reviews_renders_the_submitted_reviews() {
+        let forge = MemoryForge::default();
+        forge.add_review(
+            "octo",
+            "app",
+            1,
+            forge::Review {
+                body: "Looks **good**".to_string(),
+                ..review("alice", forge::ReviewState::Approved, "sha1")
+            },
+        );
+        forge.add_review(
+            "octo",
+            "app",
+            1,
+            review("me", forge::ReviewState::Pending, "sha1"),
+        );
+        let app_state = app_state();
+        let pr: PullRequest = (&pull_request(1, "me")).into();
 
-        // 3. Render Footer
-        self.render_footer(&mut state, footer_area, buf);
+        PullRequestWidget::fetch_reviews(app_state.clone(), Arc::new(forge), pr.clone()).await;
 
-        // 4. Render Main Panels using state
-        self.render_pr_list_panel(&mut state, prs_area, buf);
-        state.details.render(details_area, buf);
+        let state = app_state.read().unwrap();
+        let text = text_of(&state.details.reviews[&pr.url]);
+        assert!(text.contains("@alice"));
+        assert!(text.contains("Looks good"));
+        // Pending reviews are drafts
+        assert!(!text.contains("@me"));
+    }
 
-        // 5. Render Popups if needed
-        if state.show_help {
-            self.render_help_popup(area, buf); // area is the full screen for centering
+    fn merge_details(mergeable: Option<bool>, state: Option<&str>) -> forge::MergeInfo {
+        forge::MergeInfo {
+            mergeable,
+            rebaseable: None,
+            mergeable_state: state.map(ToString::to_string),
         }
-        if let LoadingState::Error(ref msg) = state.loading_state {
-            self.render_error_popup(msg, area, buf); // area is the full screen for centering
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn fetch_merge_info_retries_while_it_is_computed() {
+        let forge = Arc::new(MemoryForge::default());
+        forge.set_details("octo", "app", 1, merge_details(None, None));
+        let app_state = app_state();
+        let pr: PullRequest = (&pull_request(1, "me")).into();
+
+        let fetch = tokio::spawn(PullRequestWidget::fetch_merge_info(
+            app_state.clone(),
+            forge.clone(),
+            pr.clone(),
+        ));
+        while !app_state
+            .read()
+            .unwrap()
+            .details
+            .merge_info
+            .contains_key(&pr.url)
+        {
+            tokio::task::yield_now().await;
         }
+        assert_eq!(
+            app_state.read().unwrap().details.merge_info[&pr.url],
+            MergeInfo {
+                mergeable: Mergeability::Computing,
+                rebaseable: Mergeability::Computing,
+                mergeable_state: None,
+            }
+        );
+
+        forge.set_details("octo", "app", 1, merge_details(Some(false), Some("dirty")));
+        fetch.await.unwrap();
+
+        // The rebaseability is not computed along, it is just not known
+        assert_eq!(
+            app_state.read().unwrap().details.merge_info[&pr.url],
+            MergeInfo {
+                mergeable: Mergeability::No,
+                rebaseable: Mergeability::Unknown,
+                mergeable_state: Some("dirty".to_string()),
+            }
+        );
     }
-}
 
-// Render related functions
-impl PullRequestWidget {
-    // --- Helper render functions ---
-    fn calculate_main_layout(&self, area: Rect) -> (Rect, Rect, Rect) {
-        let base_layout =
-            Layout::vertical([Constraint::Percentage(95), Constraint::Min(3)]).split(area);
-        let prs_layout =
-            Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)])
-                .split(base_layout[0]);
-        (prs_layout[0], prs_layout[1], base_layout[1])
+    #[tokio::test(start_paused = true)]
+    async fn fetch_merge_info_gives_up_when_it_is_never_computed() {
+        let forge = MemoryForge::default();
+        forge.set_details("octo", "app", 1, merge_details(None, None));
+        let app_state = app_state();
+        let pr: PullRequest = (&pull_request(1, "me")).into();
+
+        PullRequestWidget::fetch_merge_info(app_state.clone(), Arc::new(forge), pr.clone()).await;
+
+        // Forgotten, so that selecting the pr again fetches it
+        assert!(app_state.read().unwrap().details.merge_info.is_empty());
     }
 
-    fn render_pr_list_panel(&self, state: &mut AppState, area: Rect, buf: &mut Buffer) {
-        // Build title line based on state.active_panel
-        let review_requested = if state.active_panel == ActivePanel::PullRequestsToReview {
-            "Review Requested".bold()
-        } else {
-            "Review Requested".dark_gray()
+    #[tokio::test]
+    async fn fetch_merge_info_shows_the_failures() {
+        let app_state = app_state();
+        let pr: PullRequest = (&pull_request(1, "me")).into();
+
+        PullRequestWidget::fetch_merge_info(
+            app_state.clone(),
+            Arc::new(MemoryForge::default()),
+            pr.clone(),
+        )
+        .await;
+
+        let state = app_state.read().unwrap();
+        assert_eq!(state.details.merge_info[&pr.url], MergeInfo::failed());
+        assert_eq!(state.error_log.messages().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn fetch_overview_keeps_it_or_forgets_it_on_failure() {
+        let forge = Arc::new(MemoryForge::default());
+        let overview = forge::RepositoryOverview {
+            description: Some("An app".to_string()),
+            url: "https://github.com/octo/app".to_string(),
+            default_branch: Some("main".to_string()),
+            open_pull_requests: Some(3),
+            open_issues: None,
+            latest_release: None,
+            default_branch_checks: None,
         };
-        let my_prs = if state.active_panel == ActivePanel::MyPullRequests {
-            "My Pull Requests ".bold()
-        } else {
-            "My Pull Requests ".dark_gray()
+        forge.set_overview("octo", "app", overview.clone());
+        let app_state = app_state();
+        let app = RepoId::new("octo", "app");
+        let api = RepoId::new("octo", "api");
+        app_state
+            .write()
+            .unwrap()
+            .details
+            .overviews
+            .insert(api.clone(), None);
+
+        PullRequestWidget::fetch_overview(app_state.clone(), forge.clone(), app.clone()).await;
+        PullRequestWidget::fetch_overview(app_state.clone(), forge, api.clone()).await;
+
+        let state = app_state.read().unwrap();
+        assert_eq!(state.details.overviews[&app], Some(overview));
+        assert!(!state.details.overviews.contains_key(&api));
+        assert_eq!(state.error_log.messages().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn fetch_checks_lists_the_checks_of_the_head_commit() {
+        let forge = MemoryForge::default();
+        let check = |name: &str, state, conclusion: &str| forge::Check {
+            name: name.to_string(),
+            state,
+            conclusion: conclusion.to_string(),
+            url: Some(format!("https://ci.example.com/{}", name)),
         };
-        let title_line = Line::from(vec!["📋 ".into(), review_requested, " - ".into(), my_prs]);
+        forge.set_checks(
+            "sha1",
+            vec![
+                check("build", forge::CheckState::Success, "success"),
+                check("lint", forge::CheckState::Failure, "cancelled"),
+            ],
+        );
+        forge.set_checks(
+            "old",
+            vec![check("build", forge::CheckState::Pending, "queued")],
+        );
+        let app_state = app_state();
+        let pr: PullRequest = (&pull_request(1, "me")).into();
 
-        let mut prs_block = utils::block_with_title(title_line);
+        PullRequestWidget::fetch_checks(app_state.clone(), Arc::new(forge), pr.clone()).await;
 
-        // If we're not searching we are focused on the panel block
-        if !state.searching {
-            prs_block = prs_block.border_style(Style::default().fg(Color::Green));
+        let state = app_state.read().unwrap();
+        let Fetch::Loaded(checks) = &state.details.checks[&pr.url] else {
+            panic!("The checks are not loaded");
+        };
+        let statuses: Vec<_> = checks
+            .iter()
+            .map(|c| (c.name.as_str(), c.status, c.conclusion.as_str()))
+            .collect();
+        assert_eq!(
+            statuses,
+            [
+                ("build", CheckStatus::Success, "success"),
+                ("lint", CheckStatus::Failure, "cancelled"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn fetch_timeline_renders_the_conversation() {
+        let forge = MemoryForge::default();
+        forge.add_timeline_item(
+            "octo",
+            "app",
+            1,
+            forge::TimelineItem {
+                author: Some("alice".to_string()),
+                created_at: None,
+                event: forge::TimelineEvent::Comment {
+                    body: "Could you *rebase*?".to_string(),
+                },
+            },
+        );
+        forge.add_timeline_item(
+            "octo",
+            "app",
+            1,
+            forge::TimelineItem {
+                author: Some("me".to_string()),
+                created_at: None,
+                event: forge::TimelineEvent::Labeled {
+                    label: "bug".to_string(),
+                },
+            },
+        );
+        let app_state = app_state();
+        let pr: PullRequest = (&pull_request(1, "me")).into();
+
+        PullRequestWidget::fetch_timeline(app_state.clone(), Arc::new(forge), pr.clone()).await;
+
+        let state = app_state.read().unwrap();
+        let text = text_of(&state.details.timelines[&pr.url]);
+        assert_eq!(
+            text,
+            "● @alice commented\nCould you rebase?\n\n+ @me added the label bug"
+        );
+    }
+
+    #[tokio::test]
+    async fn fetch_reviews_fails_on_an_invalid_number() {
+        let app_state = app_state();
+        let pr = PullRequest {
+            id: "abc".to_string(),
+            url: "https://github.com/octo/app/pull/abc".to_string(),
+            repo: RepoId::new("octo", "app"),
+            ..Default::default()
+        };
+
+        PullRequestWidget::fetch_reviews(
+            app_state.clone(),
+            Arc::new(MemoryForge::default()),
+            pr.clone(),
+        )
+        .await;
+
+        let state = app_state.read().unwrap();
+        assert!(matches!(state.details.reviews[&pr.url], Fetch::Failed(_)));
+        assert_eq!(
+            state.error_log.messages(),
+            ["Reviews of #abc: Invalid pr number abc"]
+        );
+    }
+
+    #[test]
+    fn repo_id_ignores_the_case() {
+        let configured = RepoId::new("octo", "app");
+        let fetched = RepoId::new("Octo", "App");
+
+        assert_eq!(configured, fetched);
+        assert_eq!(configured.cmp(&fetched), Ordering::Equal);
+        assert!(HashSet::from([configured.clone()]).contains(&fetched));
+        // The groups are keyed by the configured repository
+        let groups = std::collections::BTreeMap::from([(configured, vec![1])]);
+        assert!(groups.contains_key(&fetched));
+        assert_ne!(RepoId::new("ab", "c"), RepoId::new("a", "bc"));
+    }
+
+    #[tokio::test]
+    async fn fetch_pulls_stops_at_the_maximum_of_prs() {
+        let forge = MemoryForge::default();
+        for number in 1..=5 {
+            forge.add_pull_request(pull_request(number, "me"));
         }
+        let app_state = app_state();
+        let config = Config {
+            max_pull_requests: Some(3),
+            ..config("me")
+        };
 
-        match state.active_panel {
-            ActivePanel::PullRequestsToReview => {
-                state.review_prs.render_table(prs_block, area, buf);
-            }
-            ActivePanel::MyPullRequests => {
-                state.assignee_prs.render_table(prs_block, area, buf);
-            }
+        fetch(forge, config, &app_state).await;
+
+        let state = app_state.read().unwrap();
+        let repo = RepoId::new("octo", "app");
+        assert_eq!(
+            listed_ids(&state.authored_prs.grouped_prs[&repo]),
+            ["1", "2", "3"]
+        );
+    }
+
+    #[tokio::test]
+    async fn fetch_pulls_follows_every_page() {
+        let forge = MemoryForge::default();
+        for number in 1..=250 {
+            forge.add_pull_request(pull_request(number, "me"));
+        }
+        let app_state = app_state();
+        let config = Config {
+            max_pull_requests: Some(220),
+            ..config("me")
         };
+
+        fetch(forge, config, &app_state).await;
+
+        let state = app_state.read().unwrap();
+        let repo = RepoId::new("octo", "app");
+        let listed = listed_ids(&state.authored_prs.grouped_prs[&repo]);
+        assert_eq!(listed.len(), 220);
+        assert_eq!(listed.last(), Some(&"220"));
+        assert_eq!(state.loading_progress.fetched_pulls, 220);
     }
 
-    fn render_footer(&self, state: &mut AppState, area: Rect, buf: &mut Buffer) {
-        // Create the block with common styling first
-        let mut bottom_box = Block::default()
-            .borders(ratatui::widgets::Borders::ALL)
-            .border_type(ratatui::widgets::BorderType::Rounded);
+    #[test]
+    fn progress_shows_the_loaded_repositories_and_prs() {
+        let widget = refresh_widget(MemoryForge::default(), &[]);
+        widget.state.write().unwrap().loading_progress = LoadingProgress {
+            total_repositories: 2,
+            pending_repositories: 1,
+            fetched_pulls: 150,
+        };
+        let area = Rect::new(0, 0, 160, 40);
+        let mut buf = Buffer::empty(area);
 
-        // Calculate inner area *before* setting title and potentially border style
-        let bottom_inner = bottom_box.inner(area);
+        (&widget).render(area, &mut buf);
 
-        // Determine title, content, and apply conditional styling
-        if state.searching || !state.search.value().is_empty() {
-            bottom_box = bottom_box
-                .title("Search")
-                .border_style(Style::default().fg(Color::Green)); // Green border when searching
+        let bottom: String = (0..area.width)
+            .map(|x| buf[(x, area.height - 2)].symbol())
+            .collect();
+        assert!(bottom.contains("Loading 1/2 repos • 150 PRs"), "{}", bottom);
+    }
 
-            // Render the block first to draw borders
-            bottom_box.render(area, buf);
+    #[tokio::test]
+    async fn fetch_pulls_loads_the_open_prs_when_the_closed_ones_fail() {
+        let app_state = app_state();
+        let repo = RepoId::new("octo", "app");
+        let previous: PullRequest = (&pull_request(2, "me")).into();
+        app_state
+            .write()
+            .unwrap()
+            .closed_prs
+            .set_group(&repo, vec![previous]);
 
-            // Calculate scroll for the search input
-            let width = bottom_inner.width.max(1) as usize; // Ensure width is at least 1
-            let scroll = state.search.visual_scroll(width);
-            let input_paragraph = Paragraph::new(state.search.value());
+        let forge = MemoryForge::default();
+        forge.add_pull_request(pull_request(1, "me"));
+        forge.fail_closed_pull_requests(
+            "octo",
+            "app",
+            forge::Error::Other("Timed out".to_string()),
+        );
+        fetch(forge, config("me"), &app_state).await;
 
-            // Render the search text itself inside the inner area
-            input_paragraph.render(bottom_inner, buf);
+        let state = app_state.read().unwrap();
+        assert_eq!(listed_ids(&state.authored_prs.grouped_prs[&repo]), ["1"]);
+        assert_eq!(listed_ids(&state.closed_prs.grouped_prs[&repo]), ["2"]);
+        assert_eq!(state.repository_states[&repo], LoadingState::Loaded);
+        assert_eq!(
+            state.error_log.messages(),
+            ["Could not list the closed prs: Timed out"]
+        );
+    }
 
-            // Calculate and store cursor position if searching is active
-            if state.searching {
-                let cursor_x_offset = (state.search.visual_cursor().max(scroll) - scroll) as u16;
-                // Store the calculated absolute position
-                state.cursor_position = Some(Position {
-                    x: bottom_inner.x + cursor_x_offset, // Absolute X position
-                    y: bottom_inner.y,                   // Absolute Y position
-                });
-            } else {
-                // Not actively searching, but might have text, don't show cursor
-                state.cursor_position = None;
-            }
-        } else {
-            // Not searching: Render help text
-            bottom_box = bottom_box.title("Help"); // Default title
+    #[tokio::test]
+    async fn fetch_pulls_loads_the_prs_when_the_issues_fail() {
+        let app_state = app_state();
+        let repo = RepoId::new("octo", "app");
+        let previous = Issue {
+            id: "2".to_string(),
+            ..Default::default()
+        };
+        app_state
+            .write()
+            .unwrap()
+            .issues
+            .set_group(&repo, vec![previous]);
 
-            let bottom_inner_parts =
-                Layout::horizontal([Constraint::Percentage(90), Constraint::Min(15)])
-                    .split(bottom_inner);
+        let forge = MemoryForge::default();
+        forge.add_pull_request(pull_request(1, "me"));
+        forge.fail_assigned_issues("octo", "app", forge::Error::Other("Timed out".to_string()));
+        fetch(forge, config("me"), &app_state).await;
 
-            // Render the block first
-            bottom_box.render(area, buf);
+        let state = app_state.read().unwrap();
+        assert_eq!(listed_ids(&state.authored_prs.grouped_prs[&repo]), ["1"]);
+        assert_eq!(listed_ids(&state.issues.grouped_prs[&repo]), ["2"]);
+        assert_eq!(state.repository_states[&repo], LoadingState::Loaded);
+        assert_eq!(
+            state.error_log.messages(),
+            ["Could not list the assigned issues: Timed out"]
+        );
+    }
 
-            let loading_state = match state.loading_state {
-                LoadingState::Loading => "Loading... ".yellow().into_right_aligned_line(),
-                LoadingState::Idle | LoadingState::Loaded => {
-                    "Loaded ✔  ".green().into_right_aligned_line()
-                }
-                LoadingState::Error(_) => "Error ✗ ".red().into_right_aligned_line(),
-            };
+    #[tokio::test]
+    async fn fetch_pulls_keeps_the_previous_prs_on_failure() {
+        let app_state = app_state();
+        let repo = RepoId::new("octo", "app");
+        let previous: PullRequest = (&pull_request(1, "me")).into();
+        app_state
+            .write()
+            .unwrap()
+            .authored_prs
+            .set_group(&repo, vec![previous]);
 
-            let help_line = Line::from(
-                "Scroll: ↑↓,j/k • Switch: TAB • Review: r • Keybindings: ? • Quit: q".green(),
-            );
+        let forge = MemoryForge::default();
+        forge.fail_repository(
+            "octo",
+            "app",
+            forge::Error::Status {
+                status: 500,
+                message: "Server error".to_string(),
+            },
+        );
+        fetch(forge, config("me"), &app_state).await;
 
-            // Render help text inside the inner area
-            help_line.render(bottom_inner_parts[0], buf);
-            loading_state.render(bottom_inner_parts[1], buf);
+        let state = app_state.read().unwrap();
+        assert_eq!(listed_ids(&state.authored_prs.grouped_prs[&repo]), ["1"]);
+        assert!(matches!(
+            state.repository_states[&repo],
+            LoadingState::Error(_)
+        ));
+    }
 
-            // No cursor when showing help
-            state.cursor_position = None;
+    #[test]
+    fn widget_starts_with_the_cached_prs() {
+        let repo = RepoId::new("octo", "app");
+        let mut cached = AppState::default();
+        cached
+            .review_prs
+            .set_group(&repo, vec![PullRequest::from(&pull_request(1, "other"))]);
+        let cache_file =
+            std::env::temp_dir().join(format!("gitme-widget-{}.json", std::process::id()));
+        Snapshot::from_state(&cached).save(&cache_file).unwrap();
+
+        let widget = PullRequestWidget::with_cache_file(
+            config("me"),
+            HashMap::new(),
+            true,
+            Some(cache_file.clone()),
+        );
+        std::fs::remove_file(&cache_file).unwrap();
+
+        let state = widget.state.read().unwrap();
+        assert_eq!(listed_ids(&state.review_prs.grouped_prs[&repo]), ["1"]);
+        assert!(state.stale_since.is_some());
+    }
+
+    async fn wait_for_refresh(widget: &PullRequestWidget) {
+        for _ in 0..100 {
+            if widget
+                .state
+                .read()
+                .unwrap()
+                .loading_progress
+                .pending_repositories
+                == 0
+            {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
         }
+        panic!("The refresh did not complete");
     }
 
-    fn render_help_popup(&self, screen_area: Rect, buf: &mut Buffer) {
-        let rows = KEYBINDINGS.iter().map(|(key, action)| {
-            Row::new(vec![
-                Cell::from(key.to_string()).style(Style::default().fg(Color::Cyan)), // Theming
-                Cell::from(action.to_string()).style(Style::default().fg(Color::Green)), // Theming
-            ])
+    // Two repositories on the same forge
+    #[tokio::test]
+    async fn selecting_an_issue_fetches_no_pr_details() {
+        let widget = refresh_widget(MemoryForge::default(), &[]);
+        let issue = Issue {
+            id: "4".to_string(),
+            url: "https://github.com/octo/app/issues/4".to_string(),
+            repo: RepoId::new("octo", "app"),
+            ..Default::default()
+        };
+
+        let mut state = widget.state.write().unwrap();
+        state.details.next_tab();
+        widget.select_item(&mut state, Some(issue.clone().into()));
+
+        assert_eq!(state.details.pr_details, Some(Item::Issue(issue)));
+        assert!(state.details.merge_info.is_empty());
+        assert!(state.details.checks.is_empty());
+    }
+
+    #[tokio::test]
+    async fn notification_actions_only_apply_to_notifications() {
+        let forge = Arc::new(MemoryForge::default());
+        forge.add_notification(forge::Notification {
+            thread_id: "7".to_string(),
+            owner: "octo".to_string(),
+            repo: "app".to_string(),
+            title: "Fix".to_string(),
+            kind: "PullRequest".to_string(),
+            number: Some(1),
+            url: "https://github.com/octo/app/pull/1".to_string(),
+            reason: "mention".to_string(),
+            unread: true,
+            updated_at: Utc::now(),
         });
+        let repo = RepoId::new("octo", "app");
+        let forges = HashMap::from([(repo.clone(), RepositoryForge::new(forge.clone()))]);
+        let widget = PullRequestWidget::with_cache_file(config("me"), forges, false, None);
+        {
+            let mut state = widget.state.write().unwrap();
+            state
+                .authored_prs
+                .set_group(&repo, vec![PullRequest::from(&pull_request(1, "me"))]);
+            state
+                .notifications
+                .set_group(&repo, vec![Notification::from(&forge.inbox()[0])]);
+            state.authored_prs.scroll_down();
+            state.notifications.scroll_down();
+            state.active_panel = ActivePanel::Authored;
+        }
 
-        let area = utils::centered_rect(screen_area, 30, 20, 35, 12); // Use the full screen_area for centering
-        let popup_block = utils::block_with_title(" Keybindings ")
-            .title_bottom(" Esc to close ")
-            .borders(ratatui::widgets::Borders::ALL)
-            .border_style(Style::default().fg(Color::LightCyan)); // Theming
+        // A pr is not a notification, whatever the panel
+        widget.update_notification(forge::NotificationAction::MarkDone);
+        assert_eq!(
+            widget.state.read().unwrap().notifications.grouped_prs.len(),
+            1
+        );
 
-        let help_table = Table::new(rows, [Constraint::Length(10), Constraint::Min(15)])
-            .block(popup_block)
-            .column_spacing(2);
+        widget.state.write().unwrap().active_panel = ActivePanel::Notifications;
+        widget.update_notification(forge::NotificationAction::MarkRead);
+        let listed = widget.state.read().unwrap().notifications.grouped_prs[&repo].clone();
+        let [Item::Notification(notification)] = listed.as_slice() else {
+            panic!("The notification is no longer listed");
+        };
+        assert!(!notification.unread);
 
-        ratatui::widgets::Clear.render(area, buf);
-        ratatui::prelude::Widget::render(help_table, area, buf);
+        widget.update_notification(forge::NotificationAction::MarkDone);
+        assert!(
+            widget
+                .state
+                .read()
+                .unwrap()
+                .notifications
+                .grouped_prs
+                .is_empty()
+        );
+        // The forge is updated in the background
+        for _ in 0..100 {
+            if forge.inbox().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(forge.inbox().is_empty());
     }
 
-    fn render_error_popup(&self, err_msg: &str, screen_area: Rect, buf: &mut Buffer) {
-        let popup_block = utils::block_with_title(" Errors ")
-            .title_bottom(" q to quit ")
-            .borders(ratatui::widgets::Borders::ALL)
-            .border_style(Style::default().fg(Color::Red)); // Theming
+    fn refresh_widget(forge: MemoryForge, ignored_teams: &[&str]) -> PullRequestWidget {
+        let api = Repository {
+            name: "api".to_string(),
+            ..repository()
+        };
+        let config = Config {
+            ignored_teams: ignored_teams.iter().map(ToString::to_string).collect(),
+            repositories: vec![repository(), api],
+            ..config("me")
+        };
+        let forge = RepositoryForge::new(Arc::new(forge));
+        let forges = config
+            .repositories
+            .iter()
+            .map(|r| (RepoId::from(r), forge.clone()))
+            .collect();
 
-        let error_paragraph = Paragraph::new(err_msg.red()) // Use reference, apply style
-            .block(popup_block)
-            .centered()
-            .wrap(Wrap { trim: true });
+        PullRequestWidget::with_cache_file(config, forges, false, None)
+    }
 
-        let area = utils::centered_rect(screen_area, 30, 20, 30, 10); // Use the full screen_area for centering
-        ratatui::widgets::Clear.render(area, buf);
-        error_paragraph.render(area, buf);
+    #[tokio::test]
+    async fn refresh_lists_the_review_requests_of_the_teams() {
+        let forge = MemoryForge::default();
+        forge.set_teams(vec!["octo/core".to_string(), "octo/docs".to_string()]);
+        for (name, number, team) in [("app", 1, "octo/core"), ("api", 2, "octo/docs")] {
+            forge.add_pull_request(forge::PullRequest {
+                repo: name.to_string(),
+                requested_teams: vec![team.to_string()],
+                ..pull_request(number, "other")
+            });
+        }
+
+        let widget = refresh_widget(forge, &["Octo/Docs"]);
+        widget.refresh_pull_requests();
+        wait_for_refresh(&widget).await;
+
+        let state = widget.state.read().unwrap();
+        assert_eq!(
+            listed_ids(&state.review_prs.grouped_prs[&RepoId::new("octo", "app")]),
+            ["1"]
+        );
+        // Ignored regardless of its case
+        assert!(
+            !state
+                .review_prs
+                .grouped_prs
+                .contains_key(&RepoId::new("octo", "api"))
+        );
     }
-}
 
-type OctoPullRequest = octocrab::models::pulls::PullRequest;
+    #[tokio::test]
+    async fn refresh_lists_the_teams_once_per_forge() {
+        let forge = MemoryForge::default();
+        forge.fail_teams(forge::Error::Other("Missing read:org scope".to_string()));
 
-impl From<&OctoPullRequest> for PullRequest {
-    fn from(pr: &OctoPullRequest) -> Self {
-        Self {
-            id: pr.number.to_string(),
-            title: pr.title.as_ref().unwrap().to_string(),
-            url: pr
-                .html_url
-                .as_ref()
-                .map(ToString::to_string)
-                .unwrap_or_default(),
-            repo: pr.base.repo.as_ref().unwrap().name.clone(),
-            body: pr.body.as_ref().cloned().unwrap_or_default(),
-            is_draft: pr.draft.unwrap_or_default(),
-            author: pr
-                .user
-                .as_ref()
-                .map(|a| {
-                    if let Some(email) = &a.email {
-                        format!("{} - {}", a.login, email)
-                    } else {
-                        a.login.clone()
-                    }
-                })
-                .unwrap_or_default(),
-            mergeable: pr.mergeable.unwrap_or_default(),
-            rebaseable: pr.rebaseable.unwrap_or_default(),
-        }
+        let widget = refresh_widget(forge, &[]);
+        widget.refresh_pull_requests();
+        wait_for_refresh(&widget).await;
+
+        let state = widget.state.read().unwrap();
+        assert_eq!(
+            state.error_log.messages(),
+            ["Could not list the teams: Missing read:org scope"]
+        );
+        assert!(
+            state
+                .repository_states
+                .values()
+                .all(|s| *s == LoadingState::Loaded)
+        );
     }
-}
 
-impl From<UserProfile> for Profile {
-    fn from(prof: UserProfile) -> Self {
-        Self {
-            id: prof.id.to_string(),
-            login: prof.login,
-            name: prof.name.unwrap_or_default(),
+    // The pr as listed in the authored panel, with its reviews fetched
+    fn listed_with_reviews(state: &mut AppState, number: u64) -> PullRequest {
+        let pr: PullRequest = (&forge::PullRequest {
+            url: format!("https://github.com/octo/app/pull/{}", number),
+            ..pull_request(number, "me")
+        })
+            .into();
+        let mut group = state
+            .authored_prs
+            .grouped_prs
+            .get(&pr.repo)
+            .cloned()
+            .unwrap_or_default();
+        group.push(pr.clone().into());
+        state.authored_prs.set_group(&pr.repo, group);
+        state.details.track_version(&pr);
+        state.details.reviews.insert(
+            pr.url.clone(),
+            Fetch::Loaded(ratatui::text::Text::from("Approved")),
+        );
+        pr
+    }
+
+    #[test]
+    fn refresh_only_forgets_the_details_of_the_changed_prs() {
+        let mut state = AppState::default();
+        let unchanged = listed_with_reviews(&mut state, 1);
+        let pushed = listed_with_reviews(&mut state, 2);
+        let updated = listed_with_reviews(&mut state, 3);
+        let repo = unchanged.repo.clone();
+
+        state.authored_prs.set_group(
+            &repo,
+            vec![
+                unchanged.clone(),
+                PullRequest {
+                    head_sha: "new".to_string(),
+                    ..pushed.clone()
+                },
+                PullRequest {
+                    updated_at: Some(Utc::now()),
+                    ..updated.clone()
+                },
+            ],
+        );
+        state.invalidate_details();
+
+        assert!(state.details.reviews.contains_key(&unchanged.url));
+        assert!(!state.details.reviews.contains_key(&pushed.url));
+        assert!(!state.details.reviews.contains_key(&updated.url));
+    }
+
+    #[test]
+    fn refresh_forgets_the_failed_details_and_the_running_checks() {
+        let mut state = AppState::default();
+        let pr = listed_with_reviews(&mut state, 1);
+        let check = |status| Check {
+            name: "build".to_string(),
+            status,
+            conclusion: String::new(),
+            url: None,
+        };
+        state
+            .details
+            .reviews
+            .insert(pr.url.clone(), Fetch::Failed("Timed out".to_string()));
+        state
+            .details
+            .merge_info
+            .insert(pr.url.clone(), MergeInfo::failed());
+        state.details.checks.insert(
+            pr.url.clone(),
+            Fetch::Loaded(vec![
+                check(CheckStatus::Success),
+                check(CheckStatus::Pending),
+            ]),
+        );
+
+        state.invalidate_details();
+
+        assert!(state.details.reviews.is_empty());
+        assert!(state.details.merge_info.is_empty());
+        assert!(state.details.checks.is_empty());
+    }
+
+    #[test]
+    fn refresh_only_forgets_the_timelines_of_the_changed_prs() {
+        let mut state = AppState::default();
+        let unchanged = listed_with_reviews(&mut state, 1);
+        let updated = listed_with_reviews(&mut state, 2);
+        let failed = listed_with_reviews(&mut state, 3);
+        for pr in [&unchanged, &updated] {
+            state.details.timelines.insert(
+                pr.url.clone(),
+                Fetch::Loaded(ratatui::text::Text::from("Commented")),
+            );
         }
+        state
+            .details
+            .timelines
+            .insert(failed.url.clone(), Fetch::Failed("Timed out".to_string()));
+
+        state.authored_prs.set_group(
+            &unchanged.repo,
+            vec![
+                unchanged.clone(),
+                PullRequest {
+                    updated_at: Some(Utc::now()),
+                    ..updated.clone()
+                },
+                failed.clone(),
+            ],
+        );
+        state.invalidate_details();
+
+        assert_eq!(
+            state.details.timelines.keys().collect::<Vec<_>>(),
+            [&unchanged.url]
+        );
     }
-}
 
-impl From<&PullRequest> for Row<'_> {
-    fn from(pr: &PullRequest) -> Self {
-        let pr = pr.clone();
-        Row::new(vec![pr.id, pr.title, pr.repo])
+    #[test]
+    fn selecting_a_changed_pr_forgets_its_details() {
+        let mut state = AppState::default();
+        let pr = listed_with_reviews(&mut state, 1);
+        state
+            .details
+            .timelines
+            .insert(pr.url.clone(), Fetch::Loading);
+
+        state.details.track_version(&pr);
+        assert!(state.details.reviews.contains_key(&pr.url));
+
+        state.details.track_version(&PullRequest {
+            head_sha: "new".to_string(),
+            ..pr.clone()
+        });
+        assert!(!state.details.reviews.contains_key(&pr.url));
+        assert!(!state.details.timelines.contains_key(&pr.url));
     }
 }
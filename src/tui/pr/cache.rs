@@ -0,0 +1,308 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    hash::{DefaultHasher, Hash, Hasher},
+    path::{Path, PathBuf},
+};
+
+use color_eyre::{Result, eyre::Context};
+use serde::{Deserialize, Serialize};
+
+use super::{AppState, Check, Fetch, Item, MergeInfo, Mergeability, Profile, RepoId};
+use crate::config::Config;
+
+/// Last fetched data, persisted between runs so the prs show up right away on startup
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(super) struct Snapshot {
+    // Unix timestamp, in seconds, at which the snapshot was taken
+    pub saved_at: i64,
+    pub review_prs: BTreeMap<RepoId, Vec<Item>>,
+    pub authored_prs: BTreeMap<RepoId, Vec<Item>>,
+    pub assigned_prs: BTreeMap<RepoId, Vec<Item>>,
+    pub involved_prs: BTreeMap<RepoId, Vec<Item>>,
+    pub closed_prs: BTreeMap<RepoId, Vec<Item>>,
+    pub issues: BTreeMap<RepoId, Vec<Item>>,
+    pub notifications: BTreeMap<RepoId, Vec<Item>>,
+    pub authors: HashMap<String, Profile>,
+    // Merge information and checks of the prs, by url
+    pub merge_info: HashMap<String, MergeInfo>,
+    pub checks: HashMap<String, Vec<Check>>,
+}
+
+impl Snapshot {
+    pub fn from_state(state: &AppState) -> Self {
+        Self {
+            saved_at: chrono::Utc::now().timestamp(),
+            review_prs: state.review_prs.grouped_prs.clone(),
+            authored_prs: state.authored_prs.grouped_prs.clone(),
+            assigned_prs: state.assigned_prs.grouped_prs.clone(),
+            involved_prs: state.involved_prs.grouped_prs.clone(),
+            closed_prs: state.closed_prs.grouped_prs.clone(),
+            issues: state.issues.grouped_prs.clone(),
+            notifications: state.notifications.grouped_prs.clone(),
+            authors: state.details.cached_authors.clone(),
+            // Leave out what is still being fetched or failed, it is fetched again
+            merge_info: state
+                .details
+                .merge_info
+                .iter()
+                .filter(|(_, info)| {
+                    !matches!(
+                        info.mergeable,
+                        Mergeability::Computing | Mergeability::Failed
+                    )
+                })
+                .map(|(url, info)| (url.clone(), info.clone()))
+                .collect(),
+            checks: state
+                .details
+                .checks
+                .iter()
+                .filter_map(|(url, checks)| match checks {
+                    Fetch::Loaded(checks) => Some((url.clone(), checks.clone())),
+                    _ => None,
+                })
+                .collect(),
+        }
+    }
+
+    /// Fills the state with the cached data of the given repositories, the groups of the
+    /// repositories that are no longer configured are left out
+    pub fn apply(self, state: &mut AppState, repositories: &HashSet<RepoId>) {
+        let configured = |mut groups: BTreeMap<RepoId, Vec<Item>>| {
+            groups.retain(|repo, _| repositories.contains(repo));
+            groups
+        };
+
+        state.review_prs.grouped_prs = configured(self.review_prs);
+        state.authored_prs.grouped_prs = configured(self.authored_prs);
+        state.assigned_prs.grouped_prs = configured(self.assigned_prs);
+        state.involved_prs.grouped_prs = configured(self.involved_prs);
+        state.closed_prs.grouped_prs = configured(self.closed_prs);
+        state.issues.grouped_prs = configured(self.issues);
+        state.notifications.grouped_prs = configured(self.notifications);
+        state.details.cached_authors = self.authors;
+        state.details.merge_info = self.merge_info;
+        state.details.checks = self
+            .checks
+            .into_iter()
+            .map(|(url, checks)| (url, Fetch::Loaded(checks)))
+            .collect();
+
+        state.review_prs.update_view();
+        state.authored_prs.update_view();
+        state.assigned_prs.update_view();
+        state.involved_prs.update_view();
+        state.closed_prs.update_view();
+        state.issues.update_view();
+        state.notifications.update_view();
+    }
+
+    /// Reads the snapshot of the previous run, a missing or unreadable cache is just ignored
+    pub fn load(cache_file: &Path) -> Option<Self> {
+        let contents = std::fs::read_to_string(cache_file).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    pub fn save(&self, cache_file: &Path) -> Result<()> {
+        if let Some(cache_dir) = cache_file.parent() {
+            std::fs::create_dir_all(cache_dir).wrap_err(format!(
+                "Failed to create cache directory {}",
+                cache_dir.display()
+            ))?;
+        }
+
+        std::fs::write(
+            cache_file,
+            serde_json::to_string(self).wrap_err("Failed to marshall cache")?,
+        )
+        .wrap_err("Failed to write cache file")
+    }
+}
+
+/// File of the snapshot, one per user and set of repositories so that switching between
+/// configurations doesn't show the prs of another one
+pub(super) fn cache_file(config: &Config) -> Option<PathBuf> {
+    let repositories: BTreeSet<(RepoId, Option<&String>)> = config
+        .repositories
+        .iter()
+        .map(|r| (RepoId::from(r), r.username.as_ref()))
+        .collect();
+
+    // The hasher might change with the compiler, which only costs a startup without cache
+    let mut hasher = DefaultHasher::new();
+    config.username.hash(&mut hasher);
+    repositories.hash(&mut hasher);
+
+    dirs::cache_dir().map(|dir| {
+        dir.join("gitme")
+            .join(format!("cache-{:016x}.json", hasher.finish()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tui::pr::{CheckStatus, Issue, PullRequest};
+
+    fn pull_request(repo: &RepoId, id: &str) -> PullRequest {
+        PullRequest {
+            id: id.to_string(),
+            url: format!("https://github.com/{}/pull/{}", repo, id),
+            repo: repo.clone(),
+            ..Default::default()
+        }
+    }
+
+    fn config(contents: &str) -> Config {
+        toml::from_str(contents).unwrap()
+    }
+
+    #[test]
+    fn snapshot_restores_the_state_it_was_taken_from() {
+        let repo = RepoId::new("octo", "app");
+        let pr = pull_request(&repo, "1");
+        let mut state = AppState::default();
+        state.review_prs.set_group(&repo, vec![pr.clone()]);
+        state.issues.set_group(
+            &repo,
+            vec![Issue {
+                id: "2".to_string(),
+                url: format!("https://github.com/{}/issues/2", repo),
+                repo: repo.clone(),
+                ..Default::default()
+            }],
+        );
+        let info = MergeInfo {
+            mergeable: Mergeability::Yes,
+            rebaseable: Mergeability::No,
+            mergeable_state: Some("clean".to_string()),
+        };
+        state
+            .details
+            .merge_info
+            .insert(pr.url.clone(), info.clone());
+        // Being fetched, it is fetched again rather than cached
+        state.details.merge_info.insert(
+            "computing".to_string(),
+            MergeInfo {
+                mergeable: Mergeability::Computing,
+                ..info.clone()
+            },
+        );
+        let check = Check {
+            name: "build".to_string(),
+            status: CheckStatus::Success,
+            conclusion: "success".to_string(),
+            url: None,
+        };
+        state
+            .details
+            .checks
+            .insert(pr.url.clone(), Fetch::Loaded(vec![check.clone()]));
+        state
+            .details
+            .checks
+            .insert("failed".to_string(), Fetch::Failed("timeout".to_string()));
+
+        let json = serde_json::to_string(&Snapshot::from_state(&state)).unwrap();
+        let snapshot: Snapshot = serde_json::from_str(&json).unwrap();
+        let mut restored = AppState::default();
+        snapshot.apply(&mut restored, &HashSet::from([repo.clone()]));
+
+        assert_eq!(
+            restored.review_prs.grouped_prs,
+            state.review_prs.grouped_prs
+        );
+        assert_eq!(restored.issues.grouped_prs, state.issues.grouped_prs);
+        assert!(restored.authored_prs.grouped_prs.is_empty());
+        assert_eq!(
+            restored.details.merge_info,
+            HashMap::from([(pr.url.clone(), info)])
+        );
+        assert_eq!(restored.details.checks.len(), 1);
+        assert!(matches!(
+            &restored.details.checks[&pr.url],
+            Fetch::Loaded(checks) if *checks == [check]
+        ));
+    }
+
+    #[test]
+    fn apply_leaves_out_the_repositories_no_longer_configured() {
+        let kept = RepoId::new("octo", "app");
+        let removed = RepoId::new("octo", "old");
+        let snapshot = Snapshot {
+            review_prs: BTreeMap::from([
+                (kept.clone(), vec![pull_request(&kept, "1").into()]),
+                (removed.clone(), vec![pull_request(&removed, "2").into()]),
+            ]),
+            notifications: BTreeMap::from([(
+                removed.clone(),
+                vec![pull_request(&removed, "3").into()],
+            )]),
+            ..Default::default()
+        };
+
+        let mut state = AppState::default();
+        snapshot.apply(&mut state, &HashSet::from([RepoId::new("Octo", "App")]));
+
+        assert_eq!(
+            state.review_prs.grouped_prs.keys().collect::<Vec<_>>(),
+            [&kept]
+        );
+        assert!(state.notifications.grouped_prs.is_empty());
+    }
+
+    #[test]
+    fn snapshot_is_saved_and_loaded_from_the_given_file() {
+        let repo = RepoId::new("octo", "app");
+        let mut state = AppState::default();
+        state
+            .authored_prs
+            .set_group(&repo, vec![pull_request(&repo, "1")]);
+        let cache_file = std::env::temp_dir()
+            .join(format!("gitme-{}", std::process::id()))
+            .join("cache.json");
+
+        Snapshot::from_state(&state).save(&cache_file).unwrap();
+        let loaded = Snapshot::load(&cache_file);
+        std::fs::remove_dir_all(cache_file.parent().unwrap()).unwrap();
+
+        assert_eq!(
+            loaded.map(|s| s.authored_prs),
+            Some(state.authored_prs.grouped_prs)
+        );
+    }
+
+    #[test]
+    fn cache_file_depends_on_the_user_and_the_repositories() {
+        let config_a = config(
+            r#"
+            username = "me"
+            repositories = [{ owner = "octo", name = "app" }, { owner = "octo", name = "api" }]
+            "#,
+        );
+        // Same repositories, in another order and casing
+        let config_b = config(
+            r#"
+            username = "me"
+            repositories = [{ owner = "Octo", name = "API" }, { owner = "octo", name = "app" }]
+            "#,
+        );
+        let other_user = config(
+            r#"
+            username = "you"
+            repositories = [{ owner = "octo", name = "app" }, { owner = "octo", name = "api" }]
+            "#,
+        );
+        let other_repositories = config(
+            r#"
+            username = "me"
+            repositories = [{ owner = "octo", name = "app" }]
+            "#,
+        );
+
+        assert_eq!(cache_file(&config_a), cache_file(&config_b));
+        assert_ne!(cache_file(&config_a), cache_file(&other_user));
+        assert_ne!(cache_file(&config_a), cache_file(&other_repositories));
+    }
+}
@@ -0,0 +1,102 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Clear, Paragraph, Widget, Wrap},
+};
+
+use crate::tui::utils;
+
+/// Errors that happened while fetching, kept until cleared so they can be looked at later
+#[derive(Debug, Default)]
+pub struct ErrorLogState {
+    entries: Vec<ErrorEntry>,
+    pub show: bool,
+    scroll: u16,
+}
+
+#[derive(Debug, Clone)]
+struct ErrorEntry {
+    // Unix timestamp, in seconds
+    time: i64,
+    repository: String,
+    message: String,
+}
+
+// Older errors are dropped past this amount
+const MAX_ENTRIES: usize = 200;
+
+impl ErrorLogState {
+    pub fn push(&mut self, repository: &str, message: String) {
+        if self.entries.len() == MAX_ENTRIES {
+            self.entries.remove(0);
+        }
+        self.entries.push(ErrorEntry {
+            time: chrono::Utc::now().timestamp(),
+            repository: repository.to_string(),
+            message,
+        });
+    }
+
+    #[cfg(test)]
+    pub fn messages(&self) -> Vec<&str> {
+        self.entries.iter().map(|e| e.message.as_str()).collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.scroll = 0;
+    }
+
+    pub fn scroll_down(&mut self) {
+        self.scroll = self
+            .scroll
+            .saturating_add(1)
+            .min(self.entries.len().saturating_sub(1) as u16);
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.scroll = self.scroll.saturating_sub(1);
+    }
+
+    pub fn render(&self, screen_area: Rect, buf: &mut Buffer) {
+        let popup_block = utils::block_with_title(format!(" Errors ({}) ", self.entries.len()))
+            .title_bottom(" j/k to scroll • c to clear • Esc to close ")
+            .border_style(Style::default().fg(Color::Red));
+
+        // Latest errors first
+        let lines: Vec<Line> = if self.entries.is_empty() {
+            vec![Line::from("No errors".dark_gray())]
+        } else {
+            self.entries
+                .iter()
+                .rev()
+                .map(|entry| {
+                    let time = chrono::DateTime::from_timestamp(entry.time, 0)
+                        .map(|t| {
+                            t.with_timezone(&chrono::Local)
+                                .format("%H:%M:%S")
+                                .to_string()
+                        })
+                        .unwrap_or_default();
+                    Line::from(vec![
+                        Span::styled(time, Style::default().fg(Color::DarkGray)),
+                        " ".into(),
+                        Span::styled(entry.repository.clone(), Style::default().fg(Color::Yellow)),
+                        " ".into(),
+                        entry.message.clone().red(),
+                    ])
+                })
+                .collect()
+        };
+
+        let area = utils::centered_rect(screen_area, 60, 50, 40, 10);
+        Clear.render(area, buf);
+        Paragraph::new(lines)
+            .block(popup_block)
+            .wrap(Wrap { trim: true })
+            .scroll((self.scroll, 0))
+            .render(area, buf);
+    }
+}
@@ -1,32 +1,63 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use crate::tui::utils;
+use chrono::{DateTime, Utc};
+
+use crate::{
+    forge,
+    tui::{markdown, utils},
+};
 use ratatui::{
     buffer::Buffer,
     layout::{Constraint, Layout, Rect},
     style::{Color, Style, Stylize},
-    text::{Line, Span},
+    text::{Line, Span, Text},
     widgets::{
-        Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, StatefulWidget, Widget, Wrap,
+        Block, Cell, Paragraph, Row, Scrollbar, ScrollbarOrientation, ScrollbarState,
+        StatefulWidget, Table, TableState, Widget, Wrap,
     },
 };
 
-use super::{Profile, PullRequest};
+use super::{
+    Check, CheckStatus, Closing, Fetch, Item, Label, LocalState, MergeInfo, Mergeability,
+    PrVersion, Profile, PullRequest, RepoId, Review, ReviewerState,
+};
 
 #[derive(Debug, Default, PartialOrd, PartialEq)]
 enum ActivePanel {
     #[default]
     Body,
+    Checks,
     Reviews,
+    Timeline,
 }
 
 #[derive(Debug, Default)]
 pub struct PullRequestsDetailsState {
     active_panel: ActivePanel,
-    pub pr_details: Option<PullRequest>,
+    pub pr_details: Option<Item>,
+    // Body of the pr or issue rendered from its markdown
+    body: Text<'static>,
     pub body_scroll: u16,
     pub scrollbar_state: ScrollbarState,
     pub cached_authors: HashMap<String, Profile>,
+    // Merge information of the prs, by url
+    pub merge_info: HashMap<String, MergeInfo>,
+    // Checks of the prs by url
+    pub checks: HashMap<String, Fetch<Vec<Check>>>,
+    // Submitted reviews of the prs by url, rendered once fetched
+    pub reviews: HashMap<String, Fetch<Text<'static>>>,
+    // Conversations of the prs by url, rendered once fetched
+    pub timelines: HashMap<String, Fetch<Text<'static>>>,
+    // Versions of the prs their details were fetched for, by url
+    fetched_versions: HashMap<String, PrVersion>,
+    checks_table_state: TableState,
+    // Repository of the selected group header, shown when there is no pr
+    pub repository: Option<RepoId>,
+    // Overviews of the repositories, `None` while loading
+    pub overviews: HashMap<RepoId, Option<forge::RepositoryOverview>>,
+    // Overviews fetched before the last refresh, these are fetched again once shown
+    pub stale_overviews: HashSet<RepoId>,
+    pub local_states: HashMap<RepoId, LocalState>,
 }
 
 impl PullRequestsDetailsState {
@@ -39,25 +70,147 @@ impl PullRequestsDetailsState {
 
     pub fn next_tab(&mut self) {
         self.active_panel = match self.active_panel {
-            ActivePanel::Body => ActivePanel::Reviews,
-            ActivePanel::Reviews => ActivePanel::Body,
+            ActivePanel::Body => ActivePanel::Checks,
+            ActivePanel::Checks => ActivePanel::Reviews,
+            ActivePanel::Reviews => ActivePanel::Timeline,
+            ActivePanel::Timeline => ActivePanel::Body,
         };
+        // The body, the reviews and the timeline share the scroll
+        self.body_scroll = 0;
+        self.scrollbar_state = ScrollbarState::default();
     }
 
-    pub fn set_pull_request(&mut self, pr: Option<PullRequest>) {
-        self.pr_details = pr;
+    pub fn is_checks_tab(&self) -> bool {
+        self.active_panel == ActivePanel::Checks
+    }
+
+    pub fn is_reviews_tab(&self) -> bool {
+        self.active_panel == ActivePanel::Reviews
+    }
+
+    pub fn is_timeline_tab(&self) -> bool {
+        self.active_panel == ActivePanel::Timeline
+    }
+
+    pub fn set_item(&mut self, item: Option<Item>) {
+        self.body = item
+            .as_ref()
+            .map(|item| markdown::render(item.body()))
+            .unwrap_or_default();
+        self.pr_details = item;
         self.body_scroll = 0;
         self.scrollbar_state = ScrollbarState::default();
+        self.checks_table_state = TableState::default();
+    }
+
+    /// Forgets the details fetched for another version of the pr
+    pub fn track_version(&mut self, pr: &PullRequest) {
+        let version = PrVersion::from(pr);
+        if self.fetched_versions.get(&pr.url) != Some(&version) {
+            self.merge_info.remove(&pr.url);
+            self.checks.remove(&pr.url);
+            self.reviews.remove(&pr.url);
+            self.timelines.remove(&pr.url);
+            self.fetched_versions.insert(pr.url.clone(), version);
+        }
+    }
+
+    /// Keeps the details fetched for the given versions of the prs, by url. The others are
+    /// forgotten along with the failed fetches and the checks still running, so that they are
+    /// fetched again
+    pub fn retain_versions(&mut self, versions: &HashMap<String, PrVersion>) {
+        self.fetched_versions
+            .retain(|url, version| versions.get(url) == Some(version));
+
+        let fetched = &self.fetched_versions;
+        self.merge_info.retain(|url, info| {
+            fetched.contains_key(url) && info.mergeable != Mergeability::Failed
+        });
+        self.checks.retain(|url, checks| {
+            fetched.contains_key(url)
+                && match checks {
+                    Fetch::Loading => true,
+                    Fetch::Loaded(checks) => {
+                        checks.iter().all(|c| c.status != CheckStatus::Pending)
+                    }
+                    Fetch::Failed(_) => false,
+                }
+        });
+        self.reviews.retain(|url, reviews| {
+            fetched.contains_key(url) && !matches!(reviews, Fetch::Failed(_))
+        });
+        self.timelines.retain(|url, timeline| {
+            fetched.contains_key(url) && !matches!(timeline, Fetch::Failed(_))
+        });
+
+        self.stale_overviews = self
+            .overviews
+            .iter()
+            .filter(|(_, overview)| overview.is_some())
+            .map(|(repo, _)| repo.clone())
+            .collect();
+    }
+
+    pub fn set_repository(&mut self, repository: Option<RepoId>) {
+        self.repository = repository;
+    }
+
+    fn current_checks(&self) -> &[Check] {
+        self.pr_details
+            .as_ref()
+            .and_then(|item| match self.checks.get(item.url()) {
+                Some(Fetch::Loaded(checks)) => Some(checks.as_slice()),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn select_next_check(&mut self) {
+        let total = self.current_checks().len();
+        let next = match self.checks_table_state.selected() {
+            Some(i) => (i + 1).min(total.saturating_sub(1)),
+            None => 0,
+        };
+        if total > 0 {
+            self.checks_table_state.select(Some(next));
+        }
+    }
+
+    pub fn select_previous_check(&mut self) {
+        let previous = self.checks_table_state.selected().unwrap_or(0);
+        if !self.current_checks().is_empty() {
+            self.checks_table_state
+                .select(Some(previous.saturating_sub(1)));
+        }
+    }
+
+    pub fn selected_check(&self) -> Option<&Check> {
+        self.checks_table_state
+            .selected()
+            .and_then(|i| self.current_checks().get(i))
     }
 
     pub fn render(&mut self, area: Rect, buf: &mut Buffer) {
         let (title_area, tab_area, footer_area) = self.calculate_details_layout(area);
 
         let title_block = utils::block_with_title("Title");
-        let details_title = match self.active_panel {
-            ActivePanel::Body => Line::from(vec!["Details".bold(), " - Reviews".dark_gray()]),
-            ActivePanel::Reviews => Line::from(vec!["Details - ".dark_gray(), "Reviews".bold()]),
-        };
+        let tabs = [
+            ("Details", ActivePanel::Body),
+            ("Checks", ActivePanel::Checks),
+            ("Reviews", ActivePanel::Reviews),
+            ("Timeline", ActivePanel::Timeline),
+        ];
+        let mut details_title = Line::default();
+        for (i, (name, panel)) in tabs.into_iter().enumerate() {
+            if i > 0 {
+                details_title.push_span(" - ".dark_gray());
+            }
+            if panel == self.active_panel {
+                details_title.push_span(name.bold());
+            } else {
+                details_title.push_span(name.dark_gray());
+            }
+        }
         let details_block = utils::block_with_title(details_title);
 
         // Split the footer into different blocks
@@ -65,60 +218,121 @@ impl PullRequestsDetailsState {
             Constraint::Min(30),
             Constraint::Max(13),
             Constraint::Max(13),
+            Constraint::Max(13),
+            Constraint::Max(11),
         ])
         .split(footer_area);
 
         let author_block = utils::block_with_title("Author");
         let mergeable_block = utils::block_with_title("Mergeable");
         let rebaseable_block = utils::block_with_title("Rebaseable");
+        let merge_state_block = utils::block_with_title("State");
+        let checks_block = utils::block_with_title("Checks");
 
-        let get_status_span = |value: bool| {
-            if value {
-                Span::styled("Yes", Style::default().fg(Color::Green))
-            } else {
-                Span::styled("No", Style::default().fg(Color::Red))
+        let get_status_span = |value: Mergeability| match value {
+            Mergeability::Yes => Span::styled("Yes", Style::default().fg(Color::Green)),
+            Mergeability::No => Span::styled("No", Style::default().fg(Color::Red)),
+            Mergeability::Computing => {
+                Span::styled("Computing", Style::default().fg(Color::Yellow))
             }
+            Mergeability::Unknown => Span::styled("Unknown", Style::default().fg(Color::DarkGray)),
+            Mergeability::Failed => Span::styled("Failed", Style::default().fg(Color::Red)),
+        };
+
+        let get_merge_state_span = |value: Option<&str>| {
+            let color = match value {
+                Some("clean" | "has_hooks") => Color::Green,
+                Some("unstable" | "behind") => Color::Yellow,
+                Some("blocked" | "dirty") => Color::Red,
+                _ => Color::DarkGray,
+            };
+            Span::styled(
+                value.unwrap_or("unknown").to_string(),
+                Style::default().fg(color),
+            )
         };
 
         if let Some(pr_details) = &self.pr_details {
-            Paragraph::new(&*pr_details.title)
+            Paragraph::new(pr_details.title())
                 .block(title_block)
                 .wrap(Wrap { trim: true })
                 .render(title_area, buf);
 
-            // let body_content = tui_markdown::from_str(&pr_details.body);
-            let body_inner = details_block.inner(tab_area);
-            let body_paragraph = Paragraph::new(&*pr_details.body)
-                .block(details_block)
-                .wrap(Wrap { trim: true })
-                .scroll((self.body_scroll, 0));
+            match self.active_panel {
+                ActivePanel::Body => {
+                    let inner = details_block.inner(tab_area);
+                    details_block.render(tab_area, buf);
 
-            body_paragraph.render(tab_area, buf);
+                    // The metadata stays on top, only the body scrolls
+                    let metadata = Self::metadata_lines(pr_details);
+                    let metadata_height: usize = metadata
+                        .iter()
+                        .map(|line| line.width().max(1).div_ceil(inner.width.max(1) as usize))
+                        .sum();
+                    let [metadata_area, body_inner] = Layout::vertical([
+                        Constraint::Length(metadata_height as u16 + 1),
+                        Constraint::Fill(1),
+                    ])
+                    .areas(inner);
 
-            // Check if there needs to be a scrollbar displayed meaning that the total lines
-            // wrapped  are greater than the inner body viewport
-            let wrapped_lines = textwrap::wrap(&pr_details.body, body_inner.width as usize);
-            let total_lines_after_wrapping = wrapped_lines.len();
-            let viewport_height = body_inner.height as usize;
+                    Paragraph::new(metadata)
+                        .wrap(Wrap { trim: true })
+                        .render(metadata_area, buf);
 
-            if total_lines_after_wrapping > viewport_height {
-                self.scrollbar_state = self
-                    .scrollbar_state
-                    .content_length(total_lines_after_wrapping)
-                    .viewport_content_length(viewport_height);
+                    Self::render_scrollable(
+                        self.body.clone(),
+                        self.body_scroll,
+                        &mut self.scrollbar_state,
+                        body_inner,
+                        tab_area,
+                        buf,
+                    );
+                }
+                ActivePanel::Checks => {
+                    let checks = self.checks.get(pr_details.url());
+                    Self::render_checks(
+                        checks,
+                        &mut self.checks_table_state,
+                        details_block,
+                        tab_area,
+                        buf,
+                    );
+                }
+                ActivePanel::Reviews => {
+                    let inner = details_block.inner(tab_area);
+                    details_block.render(tab_area, buf);
 
-                Scrollbar::new(ScrollbarOrientation::VerticalRight).render(
-                    tab_area,
-                    buf,
-                    &mut self.scrollbar_state,
-                );
+                    let reviews = Self::fetched_text(self.reviews.get(pr_details.url()));
+                    Self::render_scrollable(
+                        reviews,
+                        self.body_scroll,
+                        &mut self.scrollbar_state,
+                        inner,
+                        tab_area,
+                        buf,
+                    );
+                }
+                ActivePanel::Timeline => {
+                    let inner = details_block.inner(tab_area);
+                    details_block.render(tab_area, buf);
+
+                    let timeline = Self::fetched_text(self.timelines.get(pr_details.url()));
+                    Self::render_scrollable(
+                        timeline,
+                        self.body_scroll,
+                        &mut self.scrollbar_state,
+                        inner,
+                        tab_area,
+                        buf,
+                    );
+                }
             }
 
             // If we have the author in the cache, get it frm there
-            let author = if let Some(prof) = self.cached_authors.get(&pr_details.author) {
+            let author = if let Some(prof) = self.cached_authors.get(pr_details.author()) {
                 format!("{} ({})", prof.name, prof.login)
             } else {
-                pr_details.author.clone()
+                pr_details.author().to_string()
             };
 
             Paragraph::new(author)
@@ -126,24 +340,537 @@ impl PullRequestsDetailsState {
                 .wrap(Wrap { trim: true })
                 .render(footer_layout[0], buf);
 
-            let mergeable_span = get_status_span(pr_details.mergeable);
+            let merge_info = self
+                .merge_info
+                .get(pr_details.url())
+                .cloned()
+                .unwrap_or_default();
+
+            // The mergeability doesn't apply anymore to closed prs
+            let (mergeable_span, rebaseable_span, merge_state_span) = match pr_details {
+                Item::Issue(_) => (
+                    Span::styled("-", Style::default().fg(Color::DarkGray)),
+                    Span::styled("-", Style::default().fg(Color::DarkGray)),
+                    Span::styled("issue", Style::default().fg(Color::Green)),
+                ),
+                Item::Notification(_) => (
+                    Span::styled("-", Style::default().fg(Color::DarkGray)),
+                    Span::styled("-", Style::default().fg(Color::DarkGray)),
+                    Span::styled("notified", Style::default().fg(Color::Blue)),
+                ),
+                Item::PullRequest(pr) if let Some(closing) = &pr.closing => (
+                    Span::styled("-", Style::default().fg(Color::DarkGray)),
+                    Span::styled("-", Style::default().fg(Color::DarkGray)),
+                    Span::styled(
+                        match closing {
+                            Closing::Merged { .. } => "merged",
+                            Closing::Closed => "closed",
+                        },
+                        Style::default().fg(closing.color()),
+                    ),
+                ),
+                Item::PullRequest(_) => (
+                    get_status_span(merge_info.mergeable),
+                    get_status_span(merge_info.rebaseable),
+                    get_merge_state_span(merge_info.mergeable_state.as_deref()),
+                ),
+            };
+
             Paragraph::new(mergeable_span)
                 .block(mergeable_block)
                 .wrap(Wrap { trim: true })
                 .render(footer_layout[1], buf);
 
-            let rebaseable_span = get_status_span(pr_details.rebaseable);
             Paragraph::new(rebaseable_span)
                 .block(rebaseable_block)
                 .wrap(Wrap { trim: true })
                 .render(footer_layout[2], buf);
+
+            Paragraph::new(merge_state_span)
+                .block(merge_state_block)
+                .wrap(Wrap { trim: true })
+                .render(footer_layout[3], buf);
+
+            let checks_span = match pr_details {
+                Item::PullRequest(pr) => {
+                    Span::styled(pr.checks.label(), Style::default().fg(pr.checks.color()))
+                }
+                Item::Issue(_) | Item::Notification(_) => {
+                    Span::styled("-", Style::default().fg(Color::DarkGray))
+                }
+            };
+            Paragraph::new(checks_span)
+                .block(checks_block)
+                .wrap(Wrap { trim: true })
+                .render(footer_layout[4], buf);
         } else {
+            if let Some(repo) = &self.repository {
+                Paragraph::new(repo.to_string())
+                    .block(title_block)
+                    .render(title_area, buf);
+
+                let overview =
+                    Self::overview_lines(self.overviews.get(repo), self.local_states.get(repo));
+                Paragraph::new(overview)
+                    .block(utils::block_with_title("Repository"))
+                    .wrap(Wrap { trim: true })
+                    .render(tab_area, buf);
+            } else {
+                title_block.render(title_area, buf);
+                details_block.render(tab_area, buf);
+            }
+
             // Render the empty blocks
-            title_block.render(title_area, buf);
-            details_block.render(tab_area, buf);
             author_block.render(footer_layout[0], buf);
             mergeable_block.render(footer_layout[1], buf);
             rebaseable_block.render(footer_layout[2], buf);
+            merge_state_block.render(footer_layout[3], buf);
+            checks_block.render(footer_layout[4], buf);
         }
     }
+
+    // Labels, branches, people and dates of the pr or issue, one line each
+    fn metadata_lines(item: &Item) -> Vec<Line<'static>> {
+        let mut lines = vec![];
+
+        if !item.labels().is_empty() {
+            lines.push(Self::labels_line(item.labels()));
+        }
+
+        match item {
+            Item::PullRequest(pr) => lines.extend(Self::pull_request_lines(pr)),
+            Item::Notification(notification) => lines.push(Line::from(vec![
+                "Notification: ".dark_gray(),
+                notification.kind.clone().into(),
+                " · ".dark_gray(),
+                notification.reason.clone().into(),
+                if notification.unread {
+                    " · unread".blue()
+                } else {
+                    " · read".dark_gray()
+                },
+            ])),
+            Item::Issue(_) => {}
+        }
+
+        if !item.assignees().is_empty() {
+            let assignees: Vec<String> =
+                item.assignees().iter().map(|a| format!("@{}", a)).collect();
+            lines.push(Line::from(vec![
+                "Assignees: ".dark_gray(),
+                assignees.join(", ").into(),
+            ]));
+        }
+
+        if let Some(milestone) = item.milestone() {
+            lines.push(Line::from(vec![
+                "Milestone: ".dark_gray(),
+                milestone.clone().into(),
+            ]));
+        }
+
+        let mut dates = Line::default();
+        if let Some(created_at) = item.created_at() {
+            dates.push_span("Opened: ".dark_gray());
+            dates.push_span(format_date(created_at));
+        }
+        if let Some(updated_at) = item.updated_at() {
+            dates.push_span("  Updated: ".dark_gray());
+            dates.push_span(format_date(updated_at));
+        }
+        if dates.width() > 0 {
+            lines.push(dates);
+        }
+
+        if let Item::PullRequest(pr) = item
+            && let Some(changes) = &pr.changes
+        {
+            let mut line = Line::from(vec![
+                "Changes: ".dark_gray(),
+                format!("+{}", changes.additions).green(),
+                " ".into(),
+                format!("-{}", changes.deletions).red(),
+            ]);
+            if let Some(files) = changes.changed_files {
+                line.push_span(format!(" in {} files", files));
+            }
+            lines.push(line);
+        }
+
+        lines
+    }
+
+    fn labels_line(labels: &[Label]) -> Line<'static> {
+        let mut line = Line::from("Labels: ".dark_gray());
+        for label in labels.iter() {
+            let (r, g, b) = label.rgb();
+            // Dark text on light labels, and the other way around
+            let luminance = 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32;
+            let fg = if luminance > 150.0 {
+                Color::Black
+            } else {
+                Color::White
+            };
+            line.push_span(Span::styled(
+                format!(" {} ", label.name),
+                Style::default().fg(fg).bg(Color::Rgb(r, g, b)),
+            ));
+            line.push_span(" ");
+        }
+        line
+    }
+
+    // The branches of the pr, then its reviewers
+    fn pull_request_lines(pr: &PullRequest) -> Vec<Line<'static>> {
+        let mut lines = vec![];
+
+        let mut branches = Line::from(vec![
+            "Branch: ".dark_gray(),
+            pr.head_branch.clone().cyan(),
+            " → ".into(),
+            pr.base_branch.clone().cyan(),
+        ]);
+        if pr.from_fork {
+            branches.push_span(" (fork)".yellow());
+        }
+        lines.push(branches);
+
+        if !pr.reviewers.is_empty() {
+            let mut line = Line::from("Reviewers: ".dark_gray());
+            for reviewer in pr.reviewers.iter() {
+                let style = Style::default().fg(reviewer.state.color());
+                line.push_span(Span::styled(reviewer.state.glyph(), style));
+                line.push_span(format!(" @{}  ", reviewer.login));
+            }
+            lines.push(line);
+        }
+
+        lines
+    }
+
+    // Renders the text scrolled, with a scrollbar along the tab when it doesn't fit
+    fn render_scrollable(
+        text: Text<'static>,
+        scroll: u16,
+        scrollbar_state: &mut ScrollbarState,
+        area: Rect,
+        tab_area: Rect,
+        buf: &mut Buffer,
+    ) {
+        // Not trimmed, the indentation of lists and code blocks matters
+        let paragraph = Paragraph::new(text)
+            .wrap(Wrap { trim: false })
+            .scroll((scroll, 0));
+
+        // Check if there needs to be a scrollbar displayed meaning that the total
+        // lines wrapped  are greater than the inner viewport
+        let total_lines_after_wrapping = paragraph.line_count(area.width);
+        let viewport_height = area.height as usize;
+
+        paragraph.render(area, buf);
+
+        if total_lines_after_wrapping > viewport_height {
+            *scrollbar_state = scrollbar_state
+                .content_length(total_lines_after_wrapping)
+                .viewport_content_length(viewport_height);
+
+            Scrollbar::new(ScrollbarOrientation::VerticalRight).render(
+                tab_area,
+                buf,
+                scrollbar_state,
+            );
+        }
+    }
+
+    // Text of a tab fetched on demand, along with its loading state
+    fn fetched_text(fetch: Option<&Fetch<Text<'static>>>) -> Text<'static> {
+        match fetch {
+            Some(Fetch::Loaded(text)) => text.clone(),
+            Some(Fetch::Loading) => Text::from("Loading...".yellow()),
+            Some(Fetch::Failed(err)) => Text::from(format!("Failed: {}", err).red()),
+            // Neither cached nor being fetched, e.g. when offline
+            None => Text::from("Not available".dark_gray()),
+        }
+    }
+
+    /// Every review with its author, state and date, followed by its body. Rendered once when
+    /// the reviews are fetched, the markdown being too slow to render on every frame
+    pub fn review_lines(reviews: &[Review]) -> Text<'static> {
+        if reviews.is_empty() {
+            return Text::from("No reviews".dark_gray());
+        }
+
+        let mut lines = vec![];
+        for review in reviews.iter() {
+            let style = Style::default().fg(review.state.color());
+            let mut header = Line::from(vec![
+                Span::styled(review.state.glyph(), style),
+                format!(" @{} ", review.author).bold(),
+                Span::styled(review.state.label(), style),
+            ]);
+            if let Some(submitted_at) = &review.submitted_at {
+                header.push_span(format!(" · {}", format_date(submitted_at)).dark_gray());
+            }
+            lines.push(header);
+
+            if !review.body.trim().is_empty() {
+                lines.extend(markdown::render(&review.body).lines);
+            }
+            lines.push(Line::default());
+        }
+        Text::from(lines)
+    }
+
+    /// Every entry of the conversation, oldest first, with its author and how long ago it
+    /// happened, the comments and reviews followed by their body
+    pub fn timeline_lines(timeline: &[forge::TimelineItem]) -> Text<'static> {
+        if timeline.is_empty() {
+            return Text::from("No activity".dark_gray());
+        }
+
+        let mut lines = vec![];
+        for item in timeline.iter() {
+            let (glyph, action, body) = match &item.event {
+                forge::TimelineEvent::Comment { body } => {
+                    ("●".blue(), "commented".into(), Some(body))
+                }
+                forge::TimelineEvent::Review { state, body } => {
+                    let state = ReviewerState::from(*state);
+                    let style = Style::default().fg(state.color());
+                    (
+                        Span::styled(state.glyph(), style),
+                        Span::styled(state.label(), style),
+                        Some(body),
+                    )
+                }
+                forge::TimelineEvent::Commit { sha, message } => (
+                    "•".dark_gray(),
+                    format!("pushed {} {}", sha, message)
+                        .trim_end()
+                        .to_string()
+                        .into(),
+                    None,
+                ),
+                forge::TimelineEvent::Labeled { label } => (
+                    "+".green(),
+                    format!("added the label {}", label).into(),
+                    None,
+                ),
+                forge::TimelineEvent::Unlabeled { label } => (
+                    "-".red(),
+                    format!("removed the label {}", label).into(),
+                    None,
+                ),
+                forge::TimelineEvent::ForcePushed { before, after } => {
+                    let sha = |sha: &Option<String>| sha.clone().unwrap_or("?".to_string());
+                    (
+                        "↻".yellow(),
+                        format!("force-pushed {} → {}", sha(before), sha(after)).into(),
+                        None,
+                    )
+                }
+                forge::TimelineEvent::ReviewRequested { reviewer } => (
+                    "●".yellow(),
+                    format!("requested a review from @{}", reviewer).into(),
+                    None,
+                ),
+                forge::TimelineEvent::ReadyForReview => {
+                    ("◆".green(), "marked as ready for review".into(), None)
+                }
+                forge::TimelineEvent::Other(description) => {
+                    ("·".dark_gray(), description.clone().into(), None)
+                }
+            };
+
+            let mut header = Line::from(vec![glyph, " ".into()]);
+            if let Some(author) = &item.author {
+                header.push_span(format!("@{} ", author).bold());
+            }
+            header.push_span(action);
+            if let Some(created_at) = &item.created_at {
+                header.push_span(format!(" · {}", relative_time(created_at)).dark_gray());
+            }
+            lines.push(header);
+
+            if let Some(body) = body.filter(|body| !body.trim().is_empty()) {
+                lines.extend(markdown::render(body).lines);
+                lines.push(Line::default());
+            }
+        }
+        Text::from(lines)
+    }
+
+    // Summary of the repository on the forge and of its local clone
+    fn overview_lines(
+        overview: Option<&Option<forge::RepositoryOverview>>,
+        local_state: Option<&LocalState>,
+    ) -> Vec<Line<'static>> {
+        let mut lines = vec![];
+
+        match overview {
+            Some(Some(overview)) => {
+                lines.push(match &overview.description {
+                    Some(description) => Line::from(description.clone()),
+                    None => Line::from("No description".dark_gray()),
+                });
+                lines.push(Line::default());
+
+                if let Some(branch) = &overview.default_branch {
+                    let checks = CheckStatus::from(overview.default_branch_checks);
+                    lines.push(Line::from(vec![
+                        "Default branch: ".dark_gray(),
+                        branch.clone().cyan(),
+                        "  ".into(),
+                        Span::styled(
+                            format!("{} {}", checks.glyph(), checks.label()),
+                            Style::default().fg(checks.color()),
+                        ),
+                    ]));
+                }
+
+                let count = |count: Option<u64>| {
+                    count.map_or_else(|| "?".to_string(), |count| count.to_string())
+                };
+                lines.push(Line::from(vec![
+                    "Open: ".dark_gray(),
+                    format!("{} pull requests", count(overview.open_pull_requests)).into(),
+                    " · ".dark_gray(),
+                    format!("{} issues", count(overview.open_issues)).into(),
+                ]));
+
+                let mut release = Line::from("Latest release: ".dark_gray());
+                match &overview.latest_release {
+                    Some(latest) => {
+                        release.push_span(latest.name.clone());
+                        if latest.name != latest.tag {
+                            release.push_span(format!(" ({})", latest.tag).dark_gray());
+                        }
+                        if let Some(published_at) = &latest.published_at {
+                            release.push_span(format!("  {}", format_date(published_at)));
+                        }
+                    }
+                    None => release.push_span("none".dark_gray()),
+                }
+                lines.push(release);
+            }
+            // Neither cached nor being fetched, e.g. when offline
+            None => lines.push(Line::from("Not available".dark_gray())),
+            Some(None) => lines.push(Line::from("Loading...".yellow())),
+        }
+
+        let mut local = Line::from("Local: ".dark_gray());
+        match local_state {
+            Some(LocalState::Status {
+                branch,
+                ahead_behind,
+                changed_files,
+            }) => {
+                local.push_span(branch.clone().cyan());
+                if let Some((ahead, behind)) = ahead_behind {
+                    local.push_span(format!(" ↑{} ↓{}", ahead, behind));
+                }
+                local.push_span(" · ".dark_gray());
+                if *changed_files == 0 {
+                    local.push_span("clean".green());
+                } else {
+                    local.push_span(format!("{} changed files", changed_files).yellow());
+                }
+            }
+            Some(LocalState::Loading) => local.push_span("Loading...".yellow()),
+            Some(LocalState::Failed(err)) => local.push_span(err.clone().red()),
+            Some(LocalState::Unconfigured) | None => {
+                local.push_span("no system_path configured".dark_gray())
+            }
+        }
+        lines.push(Line::default());
+        lines.push(local);
+
+        if let Some(Some(overview)) = overview {
+            lines.push(Line::from(vec![
+                "URL: ".dark_gray(),
+                overview.url.clone().into(),
+            ]));
+        }
+
+        lines
+    }
+
+    fn render_checks(
+        checks: Option<&Fetch<Vec<Check>>>,
+        table_state: &mut TableState,
+        block: Block,
+        area: Rect,
+        buf: &mut Buffer,
+    ) {
+        let checks = match checks {
+            Some(Fetch::Loaded(checks)) if !checks.is_empty() => checks,
+            Some(Fetch::Loaded(_)) => {
+                return Paragraph::new("No checks".dark_gray())
+                    .block(block)
+                    .render(area, buf);
+            }
+            // Neither cached nor being fetched, e.g. when offline
+            None => {
+                return Paragraph::new("Not available".dark_gray())
+                    .block(block)
+                    .render(area, buf);
+            }
+            Some(Fetch::Loading) => {
+                return Paragraph::new("Loading...".yellow())
+                    .block(block)
+                    .render(area, buf);
+            }
+            Some(Fetch::Failed(err)) => {
+                return Paragraph::new(format!("Failed: {}", err).red())
+                    .block(block)
+                    .wrap(Wrap { trim: true })
+                    .render(area, buf);
+            }
+        };
+
+        let rows = checks.iter().map(|check| {
+            let style = Style::default().fg(check.status.color());
+            Row::new([
+                Cell::from(Span::styled(check.status.glyph(), style)),
+                Cell::from(check.name.clone()),
+                Cell::from(Span::styled(check.conclusion.clone(), style)),
+            ])
+        });
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(1),
+                Constraint::Fill(1),
+                Constraint::Length(16),
+            ],
+        )
+        .block(block)
+        .row_highlight_style(
+            Style::default()
+                .bg(Color::Rgb(76, 55, 67)) // #4c3743
+                .add_modifier(ratatui::style::Modifier::BOLD),
+        );
+
+        StatefulWidget::render(table, area, buf, table_state);
+    }
+}
+
+fn format_date(date: &DateTime<Utc>) -> String {
+    date.with_timezone(&chrono::Local)
+        .format("%Y-%m-%d %H:%M")
+        .to_string()
+}
+
+// e.g. `5m ago` or `3d ago`, the date itself once it is older than a month
+fn relative_time(date: &DateTime<Utc>) -> String {
+    let elapsed = Utc::now().signed_duration_since(date);
+    match elapsed.num_seconds() {
+        // Also when the clock is slightly behind the forge
+        ..60 => "just now".to_string(),
+        60..3600 => format!("{}m ago", elapsed.num_minutes()),
+        3600..86400 => format!("{}h ago", elapsed.num_hours()),
+        86400..2_592_000 => format!("{}d ago", elapsed.num_days()),
+        _ => format_date(date),
+    }
 }
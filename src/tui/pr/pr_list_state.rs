@@ -1,21 +1,22 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use ratatui::{
     buffer::Buffer,
     layout::Rect,
-    style::{Color, Style},
-    text::Span,
-    widgets::{Block, Row, StatefulWidget, Table, TableState},
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Cell, Row, StatefulWidget, Table, TableState},
 };
 
-use super::PullRequest;
+use super::{Item, LoadingState, PullRequest, RepoId, ReviewStatus};
 
 #[derive(Debug, Default)]
 pub struct PullRequestsListState {
-    pub grouped_prs: BTreeMap<String, Vec<PullRequest>>,
-    pub filtered_prs: BTreeMap<String, Vec<PullRequest>>,
+    pub grouped_prs: BTreeMap<RepoId, Vec<Item>>,
+    pub filtered_prs: BTreeMap<RepoId, Vec<Item>>,
     pub table_state: TableState,
     filter_query: Option<String>,
+    status_filter: Option<ReviewStatus>,
 }
 
 impl PullRequestsListState {
@@ -93,20 +94,30 @@ impl PullRequestsListState {
         indexes
     }
 
-    pub fn find_selected(&self) -> Option<&PullRequest> {
-        if let Some(index) = self.table_state.selected() {
-            if let Some(pr) = self.find_by_index(index) {
-                return Some(pr);
-            }
+    pub fn find_selected(&self) -> Option<&Item> {
+        if let Some(index) = self.table_state.selected()
+            && let Some(pr) = self.find_by_index(index)
+        {
+            return Some(pr);
         }
 
         None
     }
 
-    fn find_by_index(&self, index: usize) -> Option<&PullRequest> {
+    /// Returns the repository of the group when its header row is selected
+    pub fn selected_repository(&self) -> Option<&RepoId> {
+        let selected = self.table_state.selected()?;
+        self.filtered_prs
+            .keys()
+            .zip(self.repository_indexes())
+            .find(|(_, index)| *index == selected)
+            .map(|(repo, _)| repo)
+    }
+
+    fn find_by_index(&self, index: usize) -> Option<&Item> {
         let mut current_index = 0;
 
-        for (_repo, prs) in self.grouped_prs.iter() {
+        for (_repo, prs) in self.filtered_prs.iter() {
             if current_index == index {
                 // Here we're returning none, since it matches a header row
                 return None;
@@ -125,6 +136,45 @@ impl PullRequestsListState {
         None
     }
 
+    /// Replaces the prs of the repository, the group is removed when there are none
+    pub fn set_group<T: Into<Item>>(&mut self, repo: &RepoId, prs: Vec<T>) {
+        if prs.is_empty() {
+            self.grouped_prs.remove(repo);
+        } else {
+            self.grouped_prs
+                .insert(repo.clone(), prs.into_iter().map(Into::into).collect());
+        }
+        self.update_view();
+
+        // If the map is not empty, and theres not a previously selected state
+        if !self.grouped_prs.is_empty() && self.table_state.selected().is_none() {
+            self.table_state.select(Some(0));
+        }
+    }
+
+    /// Applies the change to the prs of every repository, dropping the groups left empty
+    pub fn update_groups(&mut self, mut update: impl FnMut(&mut Vec<Item>)) {
+        self.grouped_prs.values_mut().for_each(&mut update);
+        self.grouped_prs.retain(|_, prs| !prs.is_empty());
+        self.update_view();
+    }
+
+    /// Selects the visible pr with the given url, returns whether there is one
+    pub fn select_url(&mut self, url: &str) -> bool {
+        let mut index = 0;
+        for prs in self.filtered_prs.values() {
+            // Skip the header row of the group
+            index += 1;
+            if let Some(position) = prs.iter().position(|pr| pr.url() == url) {
+                self.table_state.select(Some(index + position));
+                return true;
+            }
+            index += prs.len();
+        }
+
+        false
+    }
+
     pub fn set_filter_query(&mut self, query: Option<String>) {
         self.filter_query = query;
         self.update_view()
@@ -135,21 +185,57 @@ impl PullRequestsListState {
         self.update_view();
     }
 
+    pub fn status_filter(&self) -> Option<ReviewStatus> {
+        self.status_filter
+    }
+
+    pub fn set_status_filter(&mut self, status: Option<ReviewStatus>) {
+        self.status_filter = status;
+        self.update_view()
+    }
+
     pub fn update_view(&mut self) {
+        // First keep only the prs matching the status filter
+        let grouped_prs: BTreeMap<RepoId, Vec<Item>> = match self.status_filter {
+            Some(status) => self
+                .grouped_prs
+                .iter()
+                .filter_map(|(repo, prs)| {
+                    let matches: Vec<Item> = prs
+                        .iter()
+                        .filter(|item| {
+                            item.pull_request()
+                                .is_some_and(|pr| pr.review_status == Some(status))
+                        })
+                        .cloned()
+                        .collect();
+                    (!matches.is_empty()).then(|| (repo.clone(), matches))
+                })
+                .collect(),
+            None => self.grouped_prs.clone(),
+        };
+
         let mut filtered_prs = BTreeMap::new();
         // Check for an active filter and it's not ""
         if let Some(query) = self.filter_query.as_ref().filter(|q| !q.is_empty()) {
-            for (repo, prs) in self.grouped_prs.iter() {
-                // If the query matches the repo name add all prs
-                if repo.to_lowercase().contains(&query.to_lowercase()) {
+            for (repo, prs) in grouped_prs.iter() {
+                // If the query matches the repo owner/name add all prs
+                if repo
+                    .to_string()
+                    .to_lowercase()
+                    .contains(&query.to_lowercase())
+                {
                     filtered_prs.insert(repo.clone(), prs.clone());
                 } else {
-                    let matches: Vec<PullRequest> = prs
+                    let matches: Vec<Item> = prs
                         .iter()
                         .filter(|pr| {
                             // Search in the line with the same format of the display
-                            let line_text =
-                                format!("#{} - {}", pr.id.to_lowercase(), pr.title.to_lowercase());
+                            let line_text = format!(
+                                "#{} - {}",
+                                pr.id().to_lowercase(),
+                                pr.title().to_lowercase()
+                            );
                             line_text.contains(&query.to_lowercase())
                         })
                         .cloned()
@@ -160,7 +246,7 @@ impl PullRequestsListState {
                 }
             }
         } else {
-            filtered_prs = self.grouped_prs.clone();
+            filtered_prs = grouped_prs;
         }
 
         // Assign the filtered prs
@@ -185,40 +271,317 @@ impl PullRequestsListState {
         }
     }
 
-    pub fn render_table(&mut self, block: Block, area: Rect, buf: &mut Buffer) {
+    pub fn render_table(
+        &mut self,
+        block: Block,
+        repository_states: &HashMap<RepoId, LoadingState>,
+        ambiguous_names: &HashSet<String>,
+        area: Rect,
+        buf: &mut Buffer,
+    ) {
         let mut rows = Vec::new();
         for (group, prs) in self.filtered_prs.iter() {
             // Set repo title with a color
-            let repo = Span::styled(
-                format!("▼ {} ({})", group, prs.len()),
+            let mut repo = Line::from(Span::styled(
+                format!("▼ {} ({})", group.label(ambiguous_names), prs.len()),
                 Style::default().fg(Color::Yellow),
-            );
-            rows.push(Row::new([repo]));
+            ));
+            match repository_states.get(group) {
+                Some(LoadingState::Loading) => repo.push_span(" ⟳".dark_gray()),
+                Some(LoadingState::Error(err)) => repo.push_span(format!(" ✗ {}", err).red()),
+                _ => {}
+            }
+            rows.push(Row::new([
+                Cell::default(),
+                Cell::default(),
+                Cell::from(repo),
+            ]));
             let prs_len = prs.len();
-            prs.iter().enumerate().for_each(|(i, pr)| {
+            prs.iter().enumerate().for_each(|(i, item)| {
                 let mut prefix = "├─";
                 if i == prs_len - 1 {
                     prefix = "└─";
                 }
-                rows.push(Row::new([format!(
-                    "  {} #{} - {}{}",
-                    prefix,
-                    pr.id,
-                    if pr.is_draft { "✏️ " } else { "" },
-                    pr.title
-                )]));
+                // Notifications about a check suite or a release have no number
+                let number = if item.id().is_empty() {
+                    String::new()
+                } else {
+                    format!("#{} - ", item.id())
+                };
+                let tree = format!("  {} {}", prefix, number);
+                let (glyph, checks, line) = match item {
+                    Item::PullRequest(pr) => Self::pull_request_cells(tree, pr),
+                    Item::Issue(issue) => (
+                        Span::default(),
+                        Span::default(),
+                        Line::from(tree + &issue.title),
+                    ),
+                    Item::Notification(notification) => {
+                        let mut line = Line::from(tree + &notification.title);
+                        line.push_span(format!(" · {}", notification.reason).dark_gray());
+                        let glyph = if notification.unread {
+                            "●".blue()
+                        } else {
+                            Span::default()
+                        };
+                        (glyph, Span::default(), line)
+                    }
+                };
+                rows.push(Row::new([
+                    Cell::from(glyph),
+                    Cell::from(checks),
+                    Cell::from(line),
+                ]));
             });
         }
 
+        // Repositories that failed before any pr could be listed still show their error. They
+        // come last so the rows of the groups keep their index
+        let mut failed: Vec<(&RepoId, &String)> = repository_states
+            .iter()
+            .filter(|(repo, _)| !self.grouped_prs.contains_key(*repo))
+            .filter_map(|(repo, state)| match state {
+                LoadingState::Error(err) => Some((repo, err)),
+                _ => None,
+            })
+            .collect();
+        failed.sort();
+        for (group, err) in failed {
+            let mut repo = Line::from(Span::styled(
+                format!("▼ {} (0)", group.label(ambiguous_names)),
+                Style::default().fg(Color::Yellow),
+            ));
+            repo.push_span(format!(" ✗ {}", err).red());
+            rows.push(Row::new([
+                Cell::default(),
+                Cell::default(),
+                Cell::from(repo),
+            ]));
+        }
+
         // Build the table and return it
-        let t = Table::new(rows, [ratatui::layout::Constraint::Fill(1)])
-            .block(block)
-            .row_highlight_style(
-                Style::default()
-                    .bg(Color::Rgb(76, 55, 67)) // #4c3743
-                    .add_modifier(ratatui::style::Modifier::BOLD),
-            );
+        let t = Table::new(
+            rows,
+            [
+                ratatui::layout::Constraint::Length(1),
+                ratatui::layout::Constraint::Length(1),
+                ratatui::layout::Constraint::Fill(1),
+            ],
+        )
+        .block(block)
+        .row_highlight_style(
+            Style::default()
+                .bg(Color::Rgb(76, 55, 67)) // #4c3743
+                .add_modifier(ratatui::style::Modifier::BOLD),
+        );
 
         StatefulWidget::render(t, area, buf, &mut self.table_state);
     }
+
+    // Review status, checks and title of the row of a pr
+    fn pull_request_cells(
+        tree: String,
+        pr: &PullRequest,
+    ) -> (Span<'static>, Span<'static>, Line<'static>) {
+        let mut line = Line::from(format!(
+            "{}{}{}",
+            tree,
+            if pr.is_draft { "✏️ " } else { "" },
+            pr.title
+        ));
+        if let Some(team) = &pr.requested_team {
+            line.push_span(format!(" via @{}", team).dark_gray());
+        }
+        if let Some(closing) = &pr.closing {
+            line.push_span(Span::styled(
+                format!(" [{}]", closing.label()),
+                Style::default().fg(closing.color()),
+            ));
+        }
+        if !pr.involvements.is_empty() {
+            let reasons: Vec<&str> = pr.involvements.iter().map(|i| i.label()).collect();
+            line.push_span(format!(" · {}", reasons.join(", ")).dark_gray());
+        }
+        let glyph = match &pr.review_status {
+            Some(status) => Span::styled(status.glyph(), Style::default().fg(status.color())),
+            None => Span::default(),
+        };
+        let checks = Span::styled(pr.checks.glyph(), Style::default().fg(pr.checks.color()));
+        (glyph, checks, line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tui::pr::{CheckStatus, Involvement, Issue};
+
+    fn render(
+        state: &mut PullRequestsListState,
+        repository_states: &HashMap<RepoId, LoadingState>,
+    ) -> Vec<String> {
+        let area = Rect::new(0, 0, 60, 4);
+        let mut buf = Buffer::empty(area);
+        state.render_table(
+            Block::new(),
+            repository_states,
+            &HashSet::new(),
+            area,
+            &mut buf,
+        );
+        (0..area.height)
+            .map(|y| {
+                let row: String = (0..area.width).map(|x| buf[(x, y)].symbol()).collect();
+                row.trim().to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn failed_repositories_without_prs_have_a_header() {
+        let loaded = RepoId::new("octo", "app");
+        let failed = RepoId::new("octo", "gone");
+        let mut state = PullRequestsListState::default();
+        state.set_group(
+            &loaded,
+            vec![PullRequest {
+                id: "1".to_string(),
+                title: "Fix".to_string(),
+                ..Default::default()
+            }],
+        );
+        let repository_states = HashMap::from([
+            (loaded, LoadingState::Loaded),
+            (failed, LoadingState::Error("Not found".to_string())),
+        ]);
+
+        let rows = render(&mut state, &repository_states);
+
+        assert_eq!(rows[0], "▼ app (1)");
+        assert_eq!(rows[1], "└─ #1 - Fix");
+        assert_eq!(rows[2], "▼ gone (0) ✗ Not found");
+        // The header is shown but can't be selected
+        state.scroll_down();
+        state.scroll_down();
+        assert_eq!(state.table_state.selected(), Some(1));
+    }
+
+    #[test]
+    fn status_filter_keeps_the_prs_with_that_review_status() {
+        let app = RepoId::new("octo", "app");
+        let api = RepoId::new("octo", "api");
+        let pr = |id: &str, status| PullRequest {
+            id: id.to_string(),
+            title: format!("Pr {}", id),
+            review_status: Some(status),
+            ..Default::default()
+        };
+        let mut state = PullRequestsListState::default();
+        state.set_group(
+            &app,
+            vec![
+                pr("1", ReviewStatus::Approved),
+                pr("2", ReviewStatus::Requested),
+            ],
+        );
+        state.set_group(&api, vec![pr("3", ReviewStatus::Approved)]);
+
+        state.set_status_filter(Some(ReviewStatus::Requested));
+        let rows = render(&mut state, &HashMap::new());
+        assert_eq!(rows[0], "▼ app (1)");
+        assert_eq!(rows[1], "●     └─ #2 - Pr 2");
+        // Repositories without matching prs are hidden
+        assert_eq!(rows[2], "");
+
+        state.set_status_filter(None);
+        let rows = render(&mut state, &HashMap::new());
+        assert_eq!(rows[0], "▼ api (1)");
+        assert_eq!(rows[1], "✓     └─ #3 - Pr 3");
+    }
+
+    #[test]
+    fn issues_have_no_status_nor_checks() {
+        let repo = RepoId::new("octo", "app");
+        let mut state = PullRequestsListState::default();
+        state.set_group(
+            &repo,
+            vec![
+                Item::from(Issue {
+                    id: "1".to_string(),
+                    title: "Crash".to_string(),
+                    ..Default::default()
+                }),
+                Item::from(PullRequest {
+                    id: "2".to_string(),
+                    title: "Fix".to_string(),
+                    review_status: Some(ReviewStatus::Requested),
+                    ..Default::default()
+                }),
+            ],
+        );
+
+        let rows = render(&mut state, &HashMap::new());
+        assert_eq!(rows[1], "├─ #1 - Crash");
+        assert_eq!(rows[2], "●     └─ #2 - Fix");
+
+        state.set_status_filter(Some(ReviewStatus::Requested));
+        let rows = render(&mut state, &HashMap::new());
+        assert_eq!(rows[0], "▼ app (1)");
+        assert_eq!(rows[1], "●     └─ #2 - Fix");
+    }
+
+    #[test]
+    fn involved_prs_show_why_they_are_listed() {
+        let repo = RepoId::new("octo", "app");
+        let mut state = PullRequestsListState::default();
+        state.set_group(
+            &repo,
+            vec![PullRequest {
+                id: "1".to_string(),
+                title: "Fix".to_string(),
+                involvements: vec![Involvement::Mentioned, Involvement::Commented],
+                ..Default::default()
+            }],
+        );
+
+        let rows = render(&mut state, &HashMap::new());
+
+        assert_eq!(rows[1], "└─ #1 - Fix · mentioned, commented");
+    }
+
+    #[test]
+    fn checks_column_shows_the_colored_ci_status() {
+        let repo = RepoId::new("octo", "app");
+        let pr = |id: &str, checks| PullRequest {
+            id: id.to_string(),
+            title: "Fix".to_string(),
+            checks,
+            ..Default::default()
+        };
+        let mut state = PullRequestsListState::default();
+        state.set_group(
+            &repo,
+            vec![pr("1", CheckStatus::Failure), pr("2", CheckStatus::None)],
+        );
+        let area = Rect::new(0, 0, 60, 3);
+        let mut buf = Buffer::empty(area);
+        state.render_table(
+            Block::new(),
+            &HashMap::new(),
+            &HashSet::new(),
+            area,
+            &mut buf,
+        );
+
+        let glyphs = |y| {
+            (0..area.width)
+                .map(|x| &buf[(x, y)])
+                .filter(|cell| cell.symbol() == "■")
+                .map(|cell| cell.fg)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(glyphs(1), [Color::Red]);
+        // Without checks there is nothing to show
+        assert!(glyphs(2).is_empty());
+    }
 }
@@ -0,0 +1,327 @@
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    hash::{BuildHasher, RandomState},
+    time::{Duration, Instant},
+};
+
+use crate::forge;
+
+use super::RepoId;
+
+// Delay before the first retry, doubled on every consecutive failure
+const BASE_DELAY: Duration = Duration::from_secs(2);
+const MAX_DELAY: Duration = Duration::from_secs(120);
+// Consecutive failures after which the repository is left alone for a while
+const CIRCUIT_BREAKER_THRESHOLD: u32 = 5;
+const CIRCUIT_BREAKER_COOLDOWN: Duration = Duration::from_secs(10 * 60);
+
+/// Source of time of the scheduler, so that it can be driven by hand
+pub trait Clock: Debug + Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// What happens with a repository after a failed fetch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// Fetched again once the delay has elapsed
+    Retry { attempt: u32, delay: Duration },
+    /// Retrying can't help, e.g. a missing repository or an invalid token
+    Permanent(String),
+    /// Failed too many times in a row, not fetched until the cooldown elapses
+    CircuitOpen { cooldown: Duration },
+}
+
+impl Outcome {
+    /// Explains the outcome to the user, along with the error itself
+    pub fn describe(&self, err: &forge::Error) -> String {
+        match self {
+            Outcome::Retry { attempt, delay } => format!(
+                "{}, retrying in {}s (attempt {})",
+                err,
+                delay.as_secs().max(1),
+                attempt
+            ),
+            Outcome::Permanent(hint) => format!("{}, {}", err, hint),
+            Outcome::CircuitOpen { cooldown } => format!(
+                "{}, too many failures, paused for {}m",
+                err,
+                cooldown.as_secs() / 60
+            ),
+        }
+    }
+}
+
+/// Keeps track of the failing repositories, and decides when they are fetched again
+#[derive(Debug)]
+pub struct RetryScheduler<C: Clock = SystemClock> {
+    clock: C,
+    // Returns a random factor in `[0, 1)` spreading the retries of the repositories
+    jitter: fn() -> f64,
+    failures: HashMap<RepoId, Failure>,
+}
+
+#[derive(Debug, Clone)]
+struct Failure {
+    // Consecutive failures, reset once the repository is fetched
+    count: u32,
+    // When the repository can be fetched again, none if it shouldn't until asked explicitly
+    retry_at: Option<Instant>,
+    // Whether the retry is done by the scheduler, or left to the next refresh
+    scheduled: bool,
+}
+
+impl Default for RetryScheduler {
+    fn default() -> Self {
+        Self::new(SystemClock, random_jitter)
+    }
+}
+
+impl<C: Clock> RetryScheduler<C> {
+    pub fn new(clock: C, jitter: fn() -> f64) -> Self {
+        Self {
+            clock,
+            jitter,
+            failures: HashMap::new(),
+        }
+    }
+
+    /// Records a failed fetch of the repository
+    pub fn on_failure(&mut self, repository: &RepoId, err: &forge::Error) -> Outcome {
+        let now = self.clock.now();
+        let failure = self.failures.entry(repository.clone()).or_insert(Failure {
+            count: 0,
+            retry_at: None,
+            scheduled: false,
+        });
+        failure.count += 1;
+
+        if let Some(hint) = permanent_failure_hint(err) {
+            failure.retry_at = None;
+            failure.scheduled = false;
+            return Outcome::Permanent(hint.to_string());
+        }
+
+        if failure.count >= CIRCUIT_BREAKER_THRESHOLD {
+            failure.retry_at = Some(now + CIRCUIT_BREAKER_COOLDOWN);
+            failure.scheduled = false;
+            return Outcome::CircuitOpen {
+                cooldown: CIRCUIT_BREAKER_COOLDOWN,
+            };
+        }
+
+        // Exponential backoff with jitter, between half and the whole of the delay
+        let backoff = BASE_DELAY
+            .saturating_mul(2_u32.saturating_pow(failure.count - 1))
+            .min(MAX_DELAY);
+        let delay = backoff.mul_f64(0.5 + (self.jitter)() / 2.0);
+        failure.retry_at = Some(now + delay);
+        failure.scheduled = true;
+
+        Outcome::Retry {
+            attempt: failure.count,
+            delay,
+        }
+    }
+
+    /// Forgets the failures of the repository after a successful fetch
+    pub fn on_success(&mut self, repository: &RepoId) {
+        self.failures.remove(repository);
+    }
+
+    /// Whether the repository can be fetched by a periodic refresh, which isn't the case while
+    /// its retry is pending or after a failure retrying can't help
+    pub fn allows(&self, repository: &RepoId) -> bool {
+        match self.failures.get(repository) {
+            None => true,
+            Some(Failure { retry_at, .. }) => retry_at.is_some_and(|at| at <= self.clock.now()),
+        }
+    }
+
+    /// Returns the repositories whose retry is due, they won't be returned again unless they
+    /// fail once more
+    pub fn take_due(&mut self) -> Vec<RepoId> {
+        let now = self.clock.now();
+        self.failures
+            .iter_mut()
+            .filter(|(_, f)| f.scheduled && f.retry_at.is_some_and(|at| at <= now))
+            .map(|(repository, failure)| {
+                failure.scheduled = false;
+                repository.clone()
+            })
+            .collect()
+    }
+
+    /// Forgets every failure, e.g. when the user asks for a refresh
+    pub fn reset(&mut self) {
+        self.failures.clear();
+    }
+}
+
+// Errors that will keep on happening until the user fixes something, along with what to fix
+fn permanent_failure_hint(err: &forge::Error) -> Option<&'static str> {
+    let forge::Error::Status { status, message } = err else {
+        return None;
+    };
+
+    match status {
+        401 => Some("check the token of the forge"),
+        // Github also answers with a forbidden status when the secondary rate limit is hit
+        403 if !message.to_lowercase().contains("rate limit") => {
+            Some("check the token has access to the repository")
+        }
+        404 => Some("check the owner and name of the repository, or that the token can see it"),
+        _ => None,
+    }
+}
+
+fn random_jitter() -> f64 {
+    // Hashers are randomly seeded, which is enough to spread the retries
+    (RandomState::new().hash_one(()) as f64) / (u64::MAX as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    /// Clock only moving when told to
+    #[derive(Debug, Clone)]
+    struct FakeClock(Arc<Mutex<Instant>>);
+
+    impl FakeClock {
+        fn new() -> Self {
+            Self(Arc::new(Mutex::new(Instant::now())))
+        }
+
+        fn advance(&self, duration: Duration) {
+            *self.0.lock().unwrap() += duration;
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> Instant {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    // Retries happen after half of the backoff
+    fn no_jitter() -> f64 {
+        0.0
+    }
+
+    fn scheduler() -> (RetryScheduler<FakeClock>, FakeClock) {
+        let clock = FakeClock::new();
+        (RetryScheduler::new(clock.clone(), no_jitter), clock)
+    }
+
+    fn server_error() -> forge::Error {
+        forge::Error::Status {
+            status: 502,
+            message: "Bad gateway".to_string(),
+        }
+    }
+
+    fn repo() -> RepoId {
+        RepoId::new("octo", "app")
+    }
+
+    #[test]
+    fn retries_back_off_exponentially() {
+        let (mut scheduler, _) = scheduler();
+
+        let delays: Vec<Outcome> = (0..4)
+            .map(|_| scheduler.on_failure(&repo(), &server_error()))
+            .collect();
+
+        assert_eq!(
+            delays,
+            [(1, 1), (2, 2), (3, 4), (4, 8)].map(|(attempt, secs)| Outcome::Retry {
+                attempt,
+                delay: Duration::from_secs(secs),
+            })
+        );
+    }
+
+    #[test]
+    fn retry_is_due_once_the_delay_elapsed() {
+        let (mut scheduler, clock) = scheduler();
+        scheduler.on_failure(&repo(), &server_error());
+
+        assert!(!scheduler.allows(&repo()));
+        assert!(scheduler.take_due().is_empty());
+
+        clock.advance(Duration::from_secs(1));
+        assert!(scheduler.allows(&repo()));
+        assert_eq!(scheduler.take_due(), [repo()]);
+        // Only retried once, until it fails again
+        assert!(scheduler.take_due().is_empty());
+
+        scheduler.on_success(&repo());
+        assert!(scheduler.allows(&repo()));
+    }
+
+    #[test]
+    fn circuit_opens_after_consecutive_failures() {
+        let (mut scheduler, clock) = scheduler();
+        for _ in 1..CIRCUIT_BREAKER_THRESHOLD {
+            scheduler.on_failure(&repo(), &server_error());
+        }
+
+        let outcome = scheduler.on_failure(&repo(), &server_error());
+
+        assert_eq!(
+            outcome,
+            Outcome::CircuitOpen {
+                cooldown: CIRCUIT_BREAKER_COOLDOWN
+            }
+        );
+        clock.advance(MAX_DELAY);
+        assert!(!scheduler.allows(&repo()));
+        // Left to the periodic refresh once the cooldown elapsed
+        clock.advance(CIRCUIT_BREAKER_COOLDOWN);
+        assert!(scheduler.allows(&repo()));
+        assert!(scheduler.take_due().is_empty());
+    }
+
+    #[test]
+    fn missing_repository_is_not_retried() {
+        let (mut scheduler, clock) = scheduler();
+        let err: forge::Error =
+            crate::github::Error::NotFound("Could not resolve repository octo/app".to_string())
+                .into();
+
+        let outcome = scheduler.on_failure(&repo(), &err);
+
+        assert!(matches!(outcome, Outcome::Permanent(_)));
+        clock.advance(CIRCUIT_BREAKER_COOLDOWN);
+        assert!(!scheduler.allows(&repo()));
+        assert!(scheduler.take_due().is_empty());
+
+        scheduler.reset();
+        assert!(scheduler.allows(&repo()));
+    }
+
+    #[test]
+    fn secondary_rate_limit_is_retried() {
+        let (mut scheduler, _) = scheduler();
+        let err = forge::Error::Status {
+            status: 403,
+            message: "You have exceeded a secondary rate limit".to_string(),
+        };
+
+        let outcome = scheduler.on_failure(&repo(), &err);
+
+        assert!(matches!(outcome, Outcome::Retry { attempt: 1, .. }));
+    }
+}
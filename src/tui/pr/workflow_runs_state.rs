@@ -0,0 +1,631 @@
+use chrono::{DateTime, Utc};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Rect},
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Cell, Clear, Paragraph, Row, StatefulWidget, Table, TableState, Widget},
+};
+
+use crate::{forge, tui::utils};
+
+use super::{CheckStatus, Fetch, RepoId};
+
+// Lines moved at once when paging through a log
+const LOG_PAGE: usize = 20;
+
+/// Workflow runs of a repository, along with the jobs of a run and the log of a job
+#[derive(Debug, Default)]
+pub struct WorkflowRunsState {
+    pub show: bool,
+    pub repo: Option<RepoId>,
+    // Number and head commit of the pr the runs were opened from, if any
+    pull_request: Option<(String, String)>,
+    // Whether the runs of the default branch are listed rather than the ones of the pr
+    default_branch: bool,
+    view: View,
+    runs: Fetch<Vec<forge::WorkflowRun>>,
+    runs_table: TableState,
+    // Run whose jobs are listed, and job whose log is shown
+    run: Option<forge::WorkflowRun>,
+    jobs: Fetch<Vec<forge::WorkflowJob>>,
+    jobs_table: TableState,
+    job: Option<forge::WorkflowJob>,
+    log: Fetch<Vec<String>>,
+    log_scroll: usize,
+    search: String,
+    pub searching: bool,
+    // Index in the log of the line matching the search being shown
+    current_match: Option<usize>,
+    // Outcome of the last action, e.g. a cancellation being requested
+    notice: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum View {
+    #[default]
+    Runs,
+    Jobs,
+    Log,
+}
+
+impl WorkflowRunsState {
+    /// Shows the runs of the pr if any, of the default branch of the repository otherwise
+    pub fn open(&mut self, repo: RepoId, pull_request: Option<(String, String)>) {
+        *self = Self {
+            show: true,
+            repo: Some(repo),
+            default_branch: pull_request.is_none(),
+            pull_request,
+            ..Default::default()
+        };
+    }
+
+    pub fn close(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Goes back to the previous view, returns false when there is none and the runs are closed
+    pub fn back(&mut self) -> bool {
+        match self.view {
+            View::Runs => {
+                self.close();
+                return false;
+            }
+            View::Jobs => {
+                self.view = View::Runs;
+                self.run = None;
+            }
+            View::Log => {
+                self.view = View::Jobs;
+                self.job = None;
+                self.clear_search();
+            }
+        }
+        self.notice = None;
+
+        true
+    }
+
+    pub fn filter(&self) -> forge::RunFilter {
+        match &self.pull_request {
+            Some((_, sha)) if !self.default_branch => forge::RunFilter::Commit(sha.clone()),
+            _ => forge::RunFilter::DefaultBranch,
+        }
+    }
+
+    /// Switches between the runs of the pr and the ones of the default branch, returns whether
+    /// the runs have to be fetched again
+    pub fn toggle_branch(&mut self) -> bool {
+        if self.view != View::Runs || self.pull_request.is_none() {
+            return false;
+        }
+        self.default_branch = !self.default_branch;
+        self.reload_runs();
+
+        true
+    }
+
+    pub fn reload_runs(&mut self) {
+        self.runs = Fetch::Loading;
+        self.runs_table = TableState::default();
+    }
+
+    pub fn set_runs(
+        &mut self,
+        repo: &RepoId,
+        filter: &forge::RunFilter,
+        runs: Result<Vec<forge::WorkflowRun>, forge::Error>,
+    ) {
+        // The list changed since the runs were requested, e.g. the runs of the default branch of
+        // another repository are shown
+        if !self.show || self.repo.as_ref() != Some(repo) || self.filter() != *filter {
+            return;
+        }
+        if runs.as_ref().is_ok_and(|r| !r.is_empty()) {
+            self.runs_table.select(Some(0));
+        }
+        self.runs = runs.into();
+    }
+
+    /// The run whose jobs are listed, or the selected one in the list of runs
+    pub fn selected_run(&self) -> Option<&forge::WorkflowRun> {
+        match (self.view, &self.runs) {
+            (View::Runs, Fetch::Loaded(runs)) => runs.get(self.runs_table.selected()?),
+            (View::Runs, _) => None,
+            _ => self.run.as_ref(),
+        }
+    }
+
+    /// The job whose log is shown, or the selected one in the list of jobs
+    pub fn selected_job(&self) -> Option<&forge::WorkflowJob> {
+        match (self.view, &self.jobs) {
+            (View::Jobs, Fetch::Loaded(jobs)) => jobs.get(self.jobs_table.selected()?),
+            (View::Log, _) => self.job.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Goes one level deeper, from a run to its jobs or from a job to its log. Returns the run or
+    /// job to fetch
+    pub fn enter(&mut self) -> Option<Entered> {
+        match self.view {
+            View::Runs => {
+                let run = self.selected_run()?.clone();
+                let id = run.id;
+                self.run = Some(run);
+                self.jobs = Fetch::Loading;
+                self.jobs_table = TableState::default();
+                self.view = View::Jobs;
+                Some(Entered::Run(id))
+            }
+            View::Jobs => {
+                let job = self.selected_job()?.clone();
+                let id = job.id;
+                self.job = Some(job);
+                self.log = Fetch::Loading;
+                self.log_scroll = 0;
+                self.view = View::Log;
+                Some(Entered::Job(id))
+            }
+            View::Log => None,
+        }
+    }
+
+    pub fn set_jobs(&mut self, run_id: u64, jobs: Result<Vec<forge::WorkflowJob>, forge::Error>) {
+        if self.run.as_ref().is_none_or(|r| r.id != run_id) {
+            return;
+        }
+        if jobs.as_ref().is_ok_and(|j| !j.is_empty()) {
+            self.jobs_table.select(Some(0));
+        }
+        self.jobs = jobs.into();
+    }
+
+    pub fn set_log(&mut self, job_id: u64, log: Result<String, forge::Error>) {
+        if self.job.as_ref().is_none_or(|j| j.id != job_id) {
+            return;
+        }
+        self.log = log
+            .map(|log| log.lines().map(clean_log_line).collect())
+            .into();
+    }
+
+    pub fn set_notice(&mut self, notice: String) {
+        self.notice = Some(notice);
+    }
+
+    pub fn select_next(&mut self) {
+        match self.view {
+            View::Runs => select_next(&mut self.runs_table, self.runs.len()),
+            View::Jobs => select_next(&mut self.jobs_table, self.jobs.len()),
+            View::Log => self.scroll_log(1),
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        match self.view {
+            View::Runs => self.runs_table.select_previous(),
+            View::Jobs => self.jobs_table.select_previous(),
+            View::Log => self.scroll_log(-1),
+        }
+    }
+
+    pub fn page_down(&mut self) {
+        self.scroll_log(LOG_PAGE as isize);
+    }
+
+    pub fn page_up(&mut self) {
+        self.scroll_log(-(LOG_PAGE as isize));
+    }
+
+    fn scroll_log(&mut self, lines: isize) {
+        let last = self.log.len().saturating_sub(1);
+        self.log_scroll = self.log_scroll.saturating_add_signed(lines).min(last);
+    }
+
+    pub fn start_search(&mut self) {
+        if self.view == View::Log {
+            self.searching = true;
+            self.search.clear();
+            self.current_match = None;
+        }
+    }
+
+    pub fn push_search(&mut self, c: char) {
+        self.search.push(c);
+        // Look for the first match from where the log is, as the query is typed
+        self.current_match = None;
+        self.find_match(true);
+    }
+
+    pub fn pop_search(&mut self) {
+        self.search.pop();
+        self.current_match = None;
+        self.find_match(true);
+    }
+
+    /// Stops typing the query, keeping it to go through the matches
+    pub fn end_search(&mut self) {
+        self.searching = false;
+    }
+
+    pub fn clear_search(&mut self) {
+        self.searching = false;
+        self.search.clear();
+        self.current_match = None;
+    }
+
+    pub fn next_match(&mut self) {
+        self.find_match(true);
+    }
+
+    pub fn previous_match(&mut self) {
+        self.find_match(false);
+    }
+
+    // Moves to the next (or previous) line matching the search, wrapping around the log
+    fn find_match(&mut self, forward: bool) {
+        let Fetch::Loaded(lines) = &self.log else {
+            return;
+        };
+        if self.search.is_empty() || lines.is_empty() {
+            return;
+        }
+
+        let query = self.search.to_lowercase();
+        let len = lines.len();
+        let start = match self.current_match {
+            Some(current) if forward => current + 1,
+            Some(current) => current + len - 1,
+            None => self.log_scroll,
+        };
+        let found = (0..len)
+            .map(|offset| {
+                if forward {
+                    (start + offset) % len
+                } else {
+                    (start + len - offset) % len
+                }
+            })
+            .find(|i| lines[*i].to_lowercase().contains(&query));
+
+        if let Some(found) = found {
+            self.current_match = Some(found);
+            self.log_scroll = found;
+        }
+    }
+
+    pub fn render(&mut self, screen_area: Rect, buf: &mut Buffer) {
+        let area = utils::centered_rect(screen_area, 85, 80, 60, 12);
+        Clear.render(area, buf);
+
+        let repo = self
+            .repo
+            .as_ref()
+            .map(|r| r.to_string())
+            .unwrap_or_default();
+        let mut title = Line::from(format!(" Workflow runs of {} ", repo));
+        let help = match self.view {
+            View::Runs => {
+                let which = match (&self.pull_request, self.default_branch) {
+                    (Some((number, _)), false) => format!("PR #{}", number),
+                    _ => "default branch".to_string(),
+                };
+                title.push_span(format!("[{}] ", which).cyan());
+                " Enter: jobs • Tab: PR/default branch • R: re-run failed • C: cancel • o: open • Esc: close "
+            }
+            View::Jobs => {
+                if let Some(run) = &self.run {
+                    title.push_span(format!("› {} #{} ", run.name, run.number).cyan());
+                }
+                " Enter: log • R: re-run failed • C: cancel • o: open • Esc: back "
+            }
+            View::Log => {
+                if let Some(job) = &self.job {
+                    title.push_span(format!("› {} ", job.name).cyan());
+                }
+                " /: search • n/N: next/previous match • Ctrl+d/u: page • Esc: back "
+            }
+        };
+
+        let mut block = utils::block_with_title(title)
+            .border_style(Style::default().fg(Color::LightBlue))
+            .title_bottom(help);
+        if let Some(notice) = &self.notice {
+            block =
+                block.title_bottom(Line::from(format!(" {} ", notice).yellow()).right_aligned());
+        }
+
+        match self.view {
+            View::Runs => self.render_runs(block, area, buf),
+            View::Jobs => self.render_jobs(block, area, buf),
+            View::Log => self.render_log(block, area, buf),
+        }
+    }
+
+    fn render_runs(&mut self, block: Block, area: Rect, buf: &mut Buffer) {
+        let runs = match &self.runs {
+            Fetch::Loaded(runs) if !runs.is_empty() => runs,
+            fetch => return render_placeholder(fetch, "No workflow runs", block, area, buf),
+        };
+
+        let rows = runs.iter().map(|run| {
+            let (glyph, style) = state_glyph(run.state);
+            Row::new([
+                Cell::from(Span::styled(glyph, style)),
+                Cell::from(format!("{} #{}", run.name, run.number)),
+                Cell::from(run.event.clone().dark_gray()),
+                Cell::from(run.branch.clone().unwrap_or_default().cyan()),
+                Cell::from(format_duration(
+                    run.started_at,
+                    Some(run.updated_at),
+                    run.state,
+                )),
+                Cell::from(Span::styled(run.conclusion.clone(), style)),
+            ])
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(1),
+                Constraint::Fill(2),
+                Constraint::Length(20),
+                Constraint::Fill(1),
+                Constraint::Length(8),
+                Constraint::Length(16),
+            ],
+        );
+
+        render_table(table, block, area, buf, &mut self.runs_table);
+    }
+
+    fn render_jobs(&mut self, block: Block, area: Rect, buf: &mut Buffer) {
+        let jobs = match &self.jobs {
+            Fetch::Loaded(jobs) if !jobs.is_empty() => jobs,
+            fetch => return render_placeholder(fetch, "No jobs", block, area, buf),
+        };
+
+        let rows = jobs.iter().map(|job| {
+            let (glyph, style) = state_glyph(job.state);
+            Row::new([
+                Cell::from(Span::styled(glyph, style)),
+                Cell::from(job.name.clone()),
+                Cell::from(format_duration(job.started_at, job.completed_at, job.state)),
+                Cell::from(Span::styled(job.conclusion.clone(), style)),
+            ])
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(1),
+                Constraint::Fill(1),
+                Constraint::Length(8),
+                Constraint::Length(16),
+            ],
+        );
+
+        render_table(table, block, area, buf, &mut self.jobs_table);
+    }
+
+    fn render_log(&self, mut block: Block, area: Rect, buf: &mut Buffer) {
+        if self.searching || !self.search.is_empty() {
+            let found = match self.current_match {
+                Some(line) => format!(" (line {})", line + 1),
+                None => " (no match)".to_string(),
+            };
+            block = block.title_bottom(
+                Line::from(vec![
+                    format!(" /{}", self.search).yellow(),
+                    if self.searching {
+                        "▏".yellow()
+                    } else {
+                        "".into()
+                    },
+                    format!("{} ", found).dark_gray(),
+                ])
+                .right_aligned(),
+            );
+        }
+
+        let lines = match &self.log {
+            Fetch::Loaded(lines) if !lines.is_empty() => lines,
+            fetch => return render_placeholder(fetch, "Empty log", block, area, buf),
+        };
+
+        // Only the visible lines are built, logs can be long
+        let height = block.inner(area).height as usize;
+        let query = self.search.to_lowercase();
+        let visible: Vec<Line> = lines
+            .iter()
+            .enumerate()
+            .skip(self.log_scroll)
+            .take(height)
+            .map(|(i, line)| {
+                if Some(i) == self.current_match {
+                    Line::from(line.as_str()).black().on_yellow()
+                } else if !query.is_empty() && line.to_lowercase().contains(&query) {
+                    Line::from(line.as_str()).yellow()
+                } else if line.starts_with("##[error]") {
+                    Line::from(line.as_str()).red()
+                } else if line.starts_with("##[group]") {
+                    Line::from(line.as_str()).bold()
+                } else {
+                    Line::from(line.as_str())
+                }
+            })
+            .collect();
+
+        Paragraph::new(visible).block(block).render(area, buf);
+    }
+}
+
+/// What [`WorkflowRunsState::enter`] went into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Entered {
+    Run(u64),
+    Job(u64),
+}
+
+impl<T> Fetch<Vec<T>> {
+    fn len(&self) -> usize {
+        match self {
+            Fetch::Loaded(items) => items.len(),
+            _ => 0,
+        }
+    }
+}
+
+impl<T> From<Result<T, forge::Error>> for Fetch<T> {
+    fn from(result: Result<T, forge::Error>) -> Self {
+        match result {
+            Ok(value) => Fetch::Loaded(value),
+            Err(err) => Fetch::Failed(err.to_string()),
+        }
+    }
+}
+
+fn select_next(table_state: &mut TableState, len: usize) {
+    if table_state.selected().is_some_and(|i| i + 1 < len) {
+        table_state.select_next();
+    }
+}
+
+fn state_glyph(state: forge::CheckState) -> (&'static str, Style) {
+    let status = CheckStatus::from(Some(state));
+    (status.glyph(), Style::default().fg(status.color()))
+}
+
+fn render_placeholder<T>(
+    fetch: &Fetch<T>,
+    empty: &str,
+    block: Block,
+    area: Rect,
+    buf: &mut Buffer,
+) {
+    let text = match fetch {
+        Fetch::Loading => "Loading...".yellow(),
+        Fetch::Failed(err) => err.clone().red(),
+        Fetch::Loaded(_) => empty.to_string().dark_gray(),
+    };
+    Paragraph::new(text).block(block).render(area, buf);
+}
+
+fn render_table(
+    table: Table,
+    block: Block,
+    area: Rect,
+    buf: &mut Buffer,
+    table_state: &mut TableState,
+) {
+    let table = table.block(block).row_highlight_style(
+        Style::default()
+            .bg(Color::Rgb(76, 55, 67)) // #4c3743
+            .add_modifier(ratatui::style::Modifier::BOLD),
+    );
+    StatefulWidget::render(table, area, buf, table_state);
+}
+
+// Time a run or job took, or has been running for
+fn format_duration(
+    started_at: Option<DateTime<Utc>>,
+    ended_at: Option<DateTime<Utc>>,
+    state: forge::CheckState,
+) -> String {
+    let Some(started_at) = started_at else {
+        return String::new();
+    };
+    let ended_at = match (state, ended_at) {
+        (forge::CheckState::Pending, _) | (_, None) => Utc::now(),
+        (_, Some(ended_at)) => ended_at,
+    };
+
+    let seconds = (ended_at - started_at).num_seconds().max(0);
+    match seconds {
+        0..60 => format!("{}s", seconds),
+        60..3600 => format!("{}m {}s", seconds / 60, seconds % 60),
+        _ => format!("{}h {}m", seconds / 3600, seconds % 3600 / 60),
+    }
+}
+
+// Github prefixes every line with a timestamp and keeps the colors of the output, neither of
+// which can be shown as is
+fn clean_log_line(line: &str) -> String {
+    let line = match line.split_once(' ') {
+        Some((timestamp, rest)) if DateTime::parse_from_rfc3339(timestamp).is_ok() => rest,
+        _ => line,
+    };
+
+    let mut cleaned = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        // Skip the escape sequences, e.g. `\x1b[36;1m`, up to their final letter
+        if c == '\x1b' {
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            cleaned.push(c);
+        }
+    }
+
+    cleaned
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(id: u64) -> forge::WorkflowRun {
+        forge::WorkflowRun {
+            id,
+            name: "CI".to_string(),
+            number: id,
+            event: "push".to_string(),
+            branch: Some("main".to_string()),
+            state: forge::CheckState::Success,
+            conclusion: "success".to_string(),
+            url: String::new(),
+            started_at: None,
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn runs_of_another_repository_are_ignored() {
+        let mut state = WorkflowRunsState::default();
+        let previous = RepoId::new("octo", "app");
+        let current = RepoId::new("octo", "lib");
+        state.open(current.clone(), None);
+
+        // Requested before the runs of the other repository were opened
+        state.set_runs(
+            &previous,
+            &forge::RunFilter::DefaultBranch,
+            Ok(vec![run(1)]),
+        );
+        assert!(matches!(state.runs, Fetch::Loading));
+
+        state.set_runs(&current, &forge::RunFilter::DefaultBranch, Ok(vec![run(2)]));
+        assert_eq!(state.selected_run().map(|r| r.id), Some(2));
+    }
+
+    #[test]
+    fn runs_of_another_filter_are_ignored() {
+        let mut state = WorkflowRunsState::default();
+        let repo = RepoId::new("octo", "app");
+        state.open(repo.clone(), Some(("1".to_string(), "sha".to_string())));
+
+        state.set_runs(&repo, &forge::RunFilter::DefaultBranch, Ok(vec![run(1)]));
+        assert!(matches!(state.runs, Fetch::Loading));
+
+        state.set_runs(
+            &repo,
+            &forge::RunFilter::Commit("sha".to_string()),
+            Ok(vec![run(2)]),
+        );
+        assert_eq!(state.selected_run().map(|r| r.id), Some(2));
+    }
+}